@@ -0,0 +1,92 @@
+// Expose an agent as a Tool so other agents can delegate to it
+use anyhow::Error;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::core::{Runnable, RunnableConfig, RunnableInterrupt};
+use crate::tools::{Tool, ToolContext};
+use super::agent::{Agent, AgentOutput};
+
+type AgentRunFuture = Pin<Box<dyn Future<Output = Result<String, Error>> + Send>>;
+
+/// Tool adapter running a whole agent (including its tool calls) and returning its final answer
+///
+/// The tool input may be plain text or a JSON object with an `input` (or `request`/`query`) field.
+#[derive(Clone)]
+pub struct AgentTool {
+    name: String,
+    description: String,
+    run: Arc<dyn Fn(String, RunnableConfig) -> AgentRunFuture + Send + Sync>,
+}
+
+impl AgentTool {
+    pub fn new<A>(name: &str, description: &str, agent: Arc<A>) -> Self
+    where
+        A: Agent + Runnable<HashMap<String, String>, AgentOutput> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            description: format!("{} Parameters: {{\"input\": \"the request for this agent\"}}", description),
+            run: Arc::new(move |input, config| {
+                let agent = agent.clone();
+                Box::pin(async move { crate::run_agent_with_config(agent.as_ref(), input, config).await })
+            }),
+        }
+    }
+
+    /// Run the agent under the caller's config, so callbacks, tags, metadata and cancellation reach the sub-agent
+    pub fn invoke_with_config(&self, input: &str, config: RunnableConfig) -> AgentRunFuture {
+        let request = Self::parse_input(input);
+        let run = self.run.clone();
+        let name = self.name.clone();
+        Box::pin(async move {
+            run(request, config).await.map_err(|e| {
+                // Keep interrupts downcastable for the caller
+                if e.is::<RunnableInterrupt>() {
+                    e
+                } else {
+                    Error::msg(format!("Agent tool {} failed: {}", name, e))
+                }
+            })
+        })
+    }
+
+    /// Extract the request text from the tool input
+    pub fn parse_input(input: &str) -> String {
+        if let Ok(Value::Object(params)) = serde_json::from_str::<Value>(input) {
+            for key in ["input", "request", "query"] {
+                if let Some(text) = params.get(key).and_then(|v| v.as_str()) {
+                    return text.to_string();
+                }
+            }
+        }
+        input.to_string()
+    }
+}
+
+impl Tool for AgentTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        self.invoke_with_config(input, RunnableConfig::default())
+    }
+
+    // Called by the parent agent's tool loop with its config, cancelling the parent run also stops the sub-agent
+    fn invoke_with_context(&self, input: &str, context: ToolContext) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        let config = context.config.unwrap_or_default().with_cancellation_token(context.cancellation_token);
+        self.invoke_with_config(input, config)
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -4,10 +4,12 @@ use std::sync::Arc;
 use log::info;
 
 use crate::{
-    Agent, AgentAction, AgentFinish, AgentOutput, BaseMemory, ModelChatMessage, ChatMessageContent, ChatModel,
-    McpClient, McpToolAdapter, OpenAIChatModel, Runnable, Tool, parse_model_output
+    Agent, AgentAction, CacheHit, AgentFinish, AgentOutput, BaseMemory, ModelChatMessage, ChatMessageContent, ChatModel,
+    ContentPart, McpClient, McpToolAdapter, OpenAIChatModel, Runnable, RunnableConfig, Tool, parse_model_output
 };
 use serde_json::Value;
+use crate::telemetry::traced;
+use super::agent_tool::AgentTool;
 
 /// McpAgent is an intelligent agent implementation based on MCP services
 /// It can connect to MCP servers, process user inputs, call tools, and generate responses
@@ -15,7 +17,8 @@ pub struct McpAgent {
     client: Arc<dyn McpClient>,
     tools: Vec<Box<dyn Tool + Send + Sync>>,
     system_prompt: String,
-    openai_model: Option<OpenAIChatModel>,
+    // Any ChatModel implementation, OpenAIChatModel in production or FakeChatModel in tests
+    chat_model: Option<Arc<dyn ChatModel>>,
     memory: Option<Box<dyn BaseMemory>>,
 }
 
@@ -26,7 +29,7 @@ impl McpAgent {
             client,
             tools: Vec::new(),
             system_prompt,
-            openai_model: None, // Default to not setting OpenAI model
+            chat_model: None, // Default to not setting a chat model
             memory: None, // Default to not setting memory module
         }
     }
@@ -37,18 +40,39 @@ impl McpAgent {
             client,
             tools: Vec::new(),
             system_prompt,
-            openai_model: Some(openai_model),
+            chat_model: Some(Arc::new(openai_model)),
             memory: None, // Default to not setting memory module
         }
     }
     
+    /// Create a new McpAgent instance with any ChatModel implementation
+    pub fn with_chat_model(client: Arc<dyn McpClient>, system_prompt: String, chat_model: Arc<dyn ChatModel>) -> Self {
+        Self {
+            client,
+            tools: Vec::new(),
+            system_prompt,
+            chat_model: Some(chat_model),
+            memory: None, // Default to not setting memory module
+        }
+    }
+    
+    /// Replace the chat model used by this agent
+    pub fn set_chat_model(&mut self, chat_model: Arc<dyn ChatModel>) {
+        self.chat_model = Some(chat_model);
+    }
+    
+    /// Set the memory module used by this agent
+    pub fn set_memory(&mut self, memory: Box<dyn BaseMemory>) {
+        self.memory = Some(memory);
+    }
+    
     /// Create a new McpAgent instance with specified memory module
     pub fn with_memory(client: Arc<dyn McpClient>, system_prompt: String, memory: Box<dyn BaseMemory>) -> Self {
         Self {
             client,
             tools: Vec::new(),
             system_prompt,
-            openai_model: None,
+            chat_model: None,
             memory: Some(memory),
         }
     }
@@ -59,7 +83,7 @@ impl McpAgent {
             client,
             tools: Vec::new(),
             system_prompt,
-            openai_model: Some(openai_model),
+            chat_model: Some(Arc::new(openai_model)),
             memory: Some(memory),
         }
     }
@@ -99,6 +123,17 @@ impl McpAgent {
         
         Ok(())
     }
+
+    // Built-in tools are cloneable when the builtin-tools feature is enabled
+    #[cfg(feature = "builtin-tools")]
+    fn clone_builtin_tool(tool: &dyn Tool) -> Option<Box<dyn Tool + Send + Sync>> {
+        crate::tools::builtin::clone_builtin_tool(tool)
+    }
+
+    #[cfg(not(feature = "builtin-tools"))]
+    fn clone_builtin_tool(_tool: &dyn Tool) -> Option<Box<dyn Tool + Send + Sync>> {
+        None
+    }
 }
 
 impl Agent for McpAgent {
@@ -118,6 +153,11 @@ impl Agent for McpAgent {
                     mcp_tool_adapter.get_mcp_tool(),
                 );
                 cloned_tools.push(Box::new(cloned_adapter));
+            } else if let Some(agent_tool) = tool.as_any().downcast_ref::<AgentTool>() {
+                // Agent tools share the wrapped agent, cloning is cheap
+                cloned_tools.push(Box::new(agent_tool.clone()));
+            } else if let Some(builtin_tool) = Self::clone_builtin_tool(tool.as_ref()) {
+                cloned_tools.push(builtin_tool);
             } else {
                 // For other types of tools, we skip or need to implement other cloning mechanisms
                 // Here we can add logs or error handling
@@ -158,12 +198,12 @@ impl Agent for McpAgent {
 
 impl Clone for McpAgent {
     fn clone(&self) -> Self {
-        // Create a new McpAgent instance, but do not copy the tool list (simplified implementation)
+        // Create a new McpAgent instance, MCP tool adapters are recreated, other tools are not copied
         Self {
             client: Arc::clone(&self.client),
-            tools: Vec::new(), // Do not copy tools because Box<dyn Tool> cannot be directly cloned
+            tools: Agent::tools(self),
             system_prompt: self.system_prompt.clone(),
-            openai_model: self.openai_model.clone(), // Clone OpenAI model instance
+            chat_model: self.chat_model.clone(),
             memory: self.memory.clone(), // Clone memory module
         }
     }
@@ -174,6 +214,17 @@ impl Runnable<std::collections::HashMap<String, String>, AgentOutput> for McpAge
         &self,
         input: std::collections::HashMap<String, String>,
     ) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, anyhow::Error>> + Send>> {
+        self.invoke_with_config(input, None)
+    }
+
+    fn invoke_with_config(
+        &self,
+        input: std::collections::HashMap<String, String>,
+        config: Option<RunnableConfig>,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, anyhow::Error>> + Send>> {
+        // Cancelling the config drops the in-flight model request
+        let config = config.unwrap_or_default();
+        let callbacks = config.callbacks.clone();
         // Capture system prompt in advance
         let system_prompt = self.system_prompt.clone();
         let input_text = input
@@ -183,6 +234,8 @@ impl Runnable<std::collections::HashMap<String, String>, AgentOutput> for McpAge
             .to_string()
             .trim()
             .to_string();
+        // Images or files attached to the request, a JSON array of content parts under the "parts" key
+        let input_parts = input.get("parts").map(|parts| ContentPart::parse_list(&Value::String(parts.clone()))).unwrap_or_default();
 
         // Capture tool descriptions in advance to avoid using self in async move
         let tool_descriptions: String = if !self.tools.is_empty() {
@@ -214,16 +267,21 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
         };
 
         // Capture OpenAI model instance in advance to avoid using self in async move
-        let openai_model_clone = self.openai_model.clone();
+        let chat_model_clone = self.chat_model.clone();
 
-        Box::pin(async move {
+        let agent_attributes = vec![
+            ("agent.tool_count", Value::from(self.tools.len())),
+            ("agent.input_chars", Value::from(input_text.chars().count())),
+        ];
+
+        let agent_run = async move {
             // Check if input is empty
-            if input_text.is_empty() {
+            if input_text.is_empty() && input_parts.is_empty() {
                 let mut return_values = std::collections::HashMap::new();
                 return_values.insert("answer".to_string(), "Please enter valid content".to_string());
-                // Get model name from OpenAI model, use default value if not available
-                let model_name = if let Some(ref openai_model) = openai_model_clone {
-                    openai_model.model_name().map(|s| s.to_string()).unwrap_or("unknown".to_string())
+                // Get model name from the chat model, use default value if not available
+                let model_name = if let Some(ref chat_model) = chat_model_clone {
+                    chat_model.model_name().map(|s| s.to_string()).unwrap_or("unknown".to_string())
                 } else {
                     "unknown".to_string()
                 };
@@ -231,14 +289,13 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                 return Ok(AgentOutput::Finish(AgentFinish { return_values }));
             }
 
-            // Use the passed OpenAI model instance or create a new instance
-            let model = if let Some(ref openai_model) = openai_model_clone {
-                // Use the passed OpenAI model instance
-                openai_model
+            // Use the configured chat model
+            let model = if let Some(ref chat_model) = chat_model_clone {
+                chat_model
             } else {
-                // If no OpenAI model instance is provided, return an error
+                // If no chat model is configured, return an error
                 let mut return_values = std::collections::HashMap::new();
-                return_values.insert("answer".to_string(), "No OpenAI model provided".to_string());
+                return_values.insert("answer".to_string(), "No chat model provided".to_string());
                 return_values.insert("model".to_string(), "unknown".to_string());
                 return Ok(AgentOutput::Finish(AgentFinish { return_values }));
             };
@@ -293,6 +350,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                 content: enhanced_system_prompt_with_summary,
                 name: None,
                 additional_kwargs: std::collections::HashMap::new(),
+                parts: Vec::new(),
             }));
 
             // If there is a memory module, load memory variables and add them to the message list
@@ -306,9 +364,18 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                                     if let serde_json::Value::Object(msg_obj) = message {
                                         let role = msg_obj.get("role").and_then(|v| v.as_str()).unwrap_or("unknown");
                                         let content = msg_obj.get("content").and_then(|v| v.as_str()).unwrap_or("");
+                                        // Attachments are kept at the top level or in the message metadata depending on the memory
+                                        // Histories written before placeholders were introduced may still hold inline data
+                                        let parts: Vec<ContentPart> = msg_obj.get("parts")
+                                            .or_else(|| msg_obj.get("metadata").and_then(|m| m.get("parts")))
+                                            .map(ContentPart::parse_list)
+                                            .unwrap_or_default()
+                                            .iter()
+                                            .map(ContentPart::for_history)
+                                            .collect();
                                         
                                         // Skip empty content messages
-                                        if content.trim().is_empty() {
+                                        if content.trim().is_empty() && parts.is_empty() {
                                             continue;
                                         }
                                         
@@ -328,6 +395,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                                                     content: content.to_string(),
                                                     name: None,
                                                     additional_kwargs: std::collections::HashMap::new(),
+                                                    parts,
                                                 }));
                                             },
                                             "ai" | "assistant" => {
@@ -337,6 +405,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                                                     content: content.to_string(),
                                                     name: None,
                                                     additional_kwargs: std::collections::HashMap::new(),
+                                                    parts: Vec::new(),
                                                 }));
                                             },
                                             "tool" => {
@@ -348,6 +417,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                                                     content: content_str,
                                                     name: None,
                                                     additional_kwargs: std::collections::HashMap::new(),
+                                                    parts: Vec::new(),
                                                 }));
                                             },
                                             _ => {
@@ -373,6 +443,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                 content: input_text.clone(),
                 name: None,
                 additional_kwargs: std::collections::HashMap::new(),
+                parts: input_parts.clone(),
             }));
             // info!("Added current user message: role=user, content={}", input_text);
             
@@ -396,7 +467,38 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
             }
 
             // Call the language model
-            let result = model.invoke(messages).await;
+            let llm_model_name = model.model_name().map(|s| s.to_string()).unwrap_or("unknown".to_string());
+            if !callbacks.is_empty() {
+                let prompts: Vec<String> = messages.iter().map(|msg| match msg {
+                    ModelChatMessage::System(content)
+                    | ModelChatMessage::Human(content)
+                    | ModelChatMessage::AIMessage(content)
+                    | ModelChatMessage::ToolMessage(content) => content.content.clone(),
+                }).collect();
+                for callback in &callbacks {
+                    callback.on_llm_start(&llm_model_name, &prompts);
+                }
+            }
+            let llm_attributes = vec![
+                ("llm.model", Value::String(llm_model_name.clone())),
+                ("llm.message_count", Value::from(messages.len())),
+            ];
+            let result = traced("llm.call", llm_attributes, model.invoke(messages), |completion| {
+                completion.usage.as_ref().map(|usage| vec![
+                    ("llm.usage.prompt_tokens", Value::from(usage.prompt_tokens)),
+                    ("llm.usage.completion_tokens", Value::from(usage.completion_tokens)),
+                    ("llm.usage.total_tokens", Value::from(usage.total_tokens)),
+                ]).unwrap_or_default()
+            }).await;
+            for callback in &callbacks {
+                if let Some(hit) = result.as_ref().ok().and_then(|completion| CacheHit::from_message(&completion.message)) {
+                    callback.on_llm_cache_hit(&llm_model_name, &hit);
+                }
+                match &result {
+                    Ok(_) => callback.on_llm_end(&llm_model_name),
+                    Err(e) => callback.on_llm_error(&llm_model_name, &e.to_string()),
+                }
+            }
 
             match result {
                 Ok(completion) => {
@@ -413,6 +515,10 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                     if let Some(memory) = &memory_clone {
                         let mut inputs = std::collections::HashMap::new();
                         inputs.insert("input".to_string(), serde_json::Value::String(input_text.clone()));
+                        if !input_parts.is_empty() {
+                            let history_parts: Vec<ContentPart> = input_parts.iter().map(ContentPart::for_history).collect();
+                            inputs.insert("parts".to_string(), serde_json::to_value(&history_parts).unwrap_or_default());
+                        }
                         
                         // Preprocess content, if it's JSON string format, extract the content field
                         let processed_content = if content.starts_with('"') && content.ends_with('"') {
@@ -493,7 +599,7 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                 Err(e) => {
                     // Return error message when an error occurs
                     // Get model name from OpenAI model, use default value if not available
-                    let model_name = if let Some(ref model) = openai_model_clone {
+                    let model_name = if let Some(ref model) = chat_model_clone {
                         model.model_name().map(|s| s.to_string()).unwrap_or("unknown".to_string())
                     } else {
                         "unknown".to_string()
@@ -503,6 +609,10 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                     if let Some(memory) = &memory_clone {
                         let mut inputs = std::collections::HashMap::new();
                         inputs.insert("input".to_string(), serde_json::Value::String(input_text.clone()));
+                        if !input_parts.is_empty() {
+                            let history_parts: Vec<ContentPart> = input_parts.iter().map(ContentPart::for_history).collect();
+                            inputs.insert("parts".to_string(), serde_json::to_value(&history_parts).unwrap_or_default());
+                        }
                         
                         let mut outputs = std::collections::HashMap::new();
                         outputs.insert("output".to_string(), serde_json::Value::String(format!("Model invocation failed: {}", e)));
@@ -518,6 +628,23 @@ Available tools:\n{}\n\nWhen you need to use a tool, please respond in the follo
                     Ok(AgentOutput::Finish(AgentFinish { return_values }))
                 }
             }
+        };
+
+        Box::pin(async move {
+            let output = traced("agent.invoke", agent_attributes, config.run(agent_run), |output| {
+                let kind = match output {
+                    AgentOutput::Action(action) => format!("action:{}", action.tool),
+                    AgentOutput::Finish(_) => "finish".to_string(),
+                };
+                vec![("agent.output", Value::String(kind))]
+            }).await?;
+            for callback in &config.callbacks {
+                match &output {
+                    AgentOutput::Action(action) => callback.on_agent_action(action),
+                    AgentOutput::Finish(finish) => callback.on_agent_finish(finish),
+                }
+            }
+            Ok(output)
         })
     }
 
@@ -1,7 +1,13 @@
 // Agent module definition
 mod agent;
 mod mcp_agent;
+mod agent_tool;
+mod supervisor;
+mod plan_execute;
 
 // Re-export module content
 pub use agent::{Agent, AgentAction, AgentFinish, AgentOutput, AgentRunner, SimpleAgent, SimpleAgentRunner};
-pub use mcp_agent::McpAgent;
\ No newline at end of file
+pub use mcp_agent::McpAgent;
+pub use agent_tool::AgentTool;
+pub use supervisor::{SupervisorAgent, SubAgentMemory};
+pub use plan_execute::{PlanAndExecuteAgent, Plan, PlanStep, PlanStepStatus};
@@ -0,0 +1,455 @@
+// Plan-and-execute agent: plan the whole task upfront, run each step with a ReAct sub-agent, re-plan on failure
+use anyhow::Error;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::core::{Runnable, RunnableConfig};
+use crate::models::{ChatMessage, ChatMessageContent, ChatModel};
+use crate::tools::Tool;
+use super::agent::{Agent, AgentFinish, AgentOutput};
+use super::agent_tool::AgentTool;
+
+const PLANNER_PROMPT: &str = "You are a planner. Break the user's objective into a short list of concrete steps, \
+each one executable by an assistant that can call tools. Do not add steps that are not needed. \
+Reply only with JSON: {\"steps\": [\"step 1\", \"step 2\"]}.";
+
+const SYNTHESIS_PROMPT: &str = "You are given an objective and the results of the steps executed to reach it. \
+Write the final answer for the user based only on these results.";
+
+/// Execution state of a plan step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStepStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// One step of a plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanStep {
+    pub description: String,
+    pub status: PlanStepStatus,
+    /// Executor answer, or the error message of a failed step
+    pub result: Option<String>,
+}
+
+impl PlanStep {
+    pub fn new(description: &str) -> Self {
+        Self {
+            description: description.to_string(),
+            status: PlanStepStatus::Pending,
+            result: None,
+        }
+    }
+}
+
+/// An inspectable step list for one objective
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plan {
+    pub objective: String,
+    pub steps: Vec<PlanStep>,
+}
+
+impl Plan {
+    pub fn new(objective: &str, steps: Vec<PlanStep>) -> Self {
+        Self { objective: objective.to_string(), steps }
+    }
+
+    pub fn completed_steps(&self) -> impl Iterator<Item = &PlanStep> {
+        self.steps.iter().filter(|s| s.status == PlanStepStatus::Completed)
+    }
+
+    /// Render the plan as a numbered list for display or review
+    pub fn to_markdown(&self) -> String {
+        self.steps.iter().enumerate()
+            .map(|(i, step)| format!("{}. [{:?}] {}", i + 1, step.status, step.description))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Agent that plans first, then executes each step with a sub-agent
+///
+/// Call `plan` to get the step list for review and `execute` to run an (optionally edited) plan,
+/// or invoke the agent to do both.
+#[derive(Clone)]
+pub struct PlanAndExecuteAgent {
+    planner: Arc<dyn ChatModel>,
+    executor: AgentTool,
+    max_replans: usize,
+    max_steps: usize,
+}
+
+impl PlanAndExecuteAgent {
+    /// `executor` runs each step, typically an McpAgent with the tools the plan needs
+    pub fn new<A>(planner: Arc<dyn ChatModel>, executor: Arc<A>) -> Self
+    where
+        A: Agent + Runnable<HashMap<String, String>, AgentOutput> + 'static,
+    {
+        Self {
+            planner,
+            executor: AgentTool::new("executor", "Executes one plan step.", executor),
+            max_replans: 2,
+            max_steps: 10,
+        }
+    }
+
+    /// Maximum number of re-plans after failed steps (default 2)
+    pub fn with_max_replans(mut self, max_replans: usize) -> Self {
+        self.max_replans = max_replans;
+        self
+    }
+
+    /// Maximum number of steps in a plan (default 10)
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps.max(1);
+        self
+    }
+
+    fn message(content: String) -> ChatMessageContent {
+        ChatMessageContent { content, name: None, additional_kwargs: HashMap::new(), parts: Vec::new() }
+    }
+
+    async fn ask(&self, system_prompt: &str, request: String) -> Result<String, Error> {
+        let completion = self.planner.invoke(vec![
+            ChatMessage::System(Self::message(system_prompt.to_string())),
+            ChatMessage::Human(Self::message(request)),
+        ]).await?;
+        match completion.message {
+            ChatMessage::AIMessage(content) => Ok(content.content),
+            other => Err(Error::msg(format!("Unexpected planner message: {:?}", other))),
+        }
+    }
+
+    async fn ask_planner(&self, request: String) -> Result<Vec<PlanStep>, Error> {
+        let content = self.ask(PLANNER_PROMPT, request).await?;
+
+        let start = content.find(['{', '[']).unwrap_or(0);
+        let end = content.rfind(['}', ']']).map(|i| i + 1).unwrap_or(content.len());
+        let parsed: Value = serde_json::from_str(&content[start..end])
+            .map_err(|e| Error::msg(format!("Planner reply is not valid JSON ({}): {}", e, content)))?;
+        let steps = match &parsed {
+            Value::Array(steps) => steps,
+            other => other.get("steps").and_then(|s| s.as_array())
+                .ok_or_else(|| Error::msg(format!("Planner reply has no steps: {}", content)))?,
+        };
+
+        let steps: Vec<PlanStep> = steps.iter()
+            .filter_map(|s| s.as_str())
+            .filter(|s| !s.trim().is_empty())
+            .map(PlanStep::new)
+            .collect();
+        if steps.len() > self.max_steps {
+            return Err(Error::msg(format!("Plan has {} steps, more than the limit of {}", steps.len(), self.max_steps)));
+        }
+        Ok(steps)
+    }
+
+    /// Produce the initial plan for an objective without executing it
+    pub async fn plan(&self, objective: &str) -> Result<Plan, Error> {
+        let steps = self.ask_planner(format!("Objective: {}", objective)).await?;
+        if steps.is_empty() {
+            return Err(Error::msg("Planner returned an empty plan"));
+        }
+        Ok(Plan::new(objective, steps))
+    }
+
+    // Replace the failed step and everything after it with a new plan for the remaining work
+    async fn replan(&self, plan: &mut Plan, failed: usize) -> Result<(), Error> {
+        let done: String = plan.completed_steps()
+            .map(|s| format!("- {} => {}\n", s.description, s.result.clone().unwrap_or_default()))
+            .collect();
+        let failed_step = &plan.steps[failed];
+        let request = format!(
+            "Objective: {}\nCompleted steps:\n{}\nFailed step: {}\nError: {}\nPlan only the remaining steps needed to reach the objective.",
+            plan.objective,
+            if done.is_empty() { "(none)\n".to_string() } else { done },
+            failed_step.description,
+            failed_step.result.clone().unwrap_or_default()
+        );
+
+        let remaining = self.ask_planner(request).await?;
+        // Dropping the failed step without replacement would report the objective as reached
+        if remaining.is_empty() {
+            return Err(Error::msg(format!("Planner returned no remaining steps after step {} failed", failed + 1)));
+        }
+        if failed + remaining.len() > self.max_steps {
+            return Err(Error::msg("Re-planned steps exceed the step limit"));
+        }
+        plan.steps.truncate(failed);
+        plan.steps.extend(remaining);
+        Ok(())
+    }
+
+    // Input for the executor: the objective, what is already done and the current step
+    fn step_request(plan: &Plan, index: usize) -> String {
+        let done: String = plan.completed_steps()
+            .map(|s| format!("- {} => {}\n", s.description, s.result.clone().unwrap_or_default()))
+            .collect();
+        format!(
+            "Overall objective: {}\n{}Current step: {}",
+            plan.objective,
+            if done.is_empty() { String::new() } else { format!("Completed steps:\n{}", done) },
+            plan.steps[index].description
+        )
+    }
+
+    /// Write the final answer from the results of an executed plan
+    pub async fn synthesize(&self, plan: &Plan) -> Result<String, Error> {
+        let results: String = plan.completed_steps()
+            .map(|s| format!("- {} => {}\n", s.description, s.result.clone().unwrap_or_default()))
+            .collect();
+        self.ask(SYNTHESIS_PROMPT, format!("Objective: {}\nStep results:\n{}", plan.objective, results)).await
+    }
+
+    /// Execute a plan step by step, re-planning when a step fails; returns the final plan state
+    pub async fn execute(&self, mut plan: Plan, config: &RunnableConfig) -> Result<Plan, Error> {
+        for callback in &config.callbacks {
+            callback.on_plan(&plan, 0);
+        }
+
+        let mut replans = 0;
+        let mut index = 0;
+        while index < plan.steps.len() {
+            config.ensure_not_cancelled()?;
+            plan.steps[index].status = PlanStepStatus::Running;
+            for callback in &config.callbacks {
+                callback.on_plan_step_start(index, &plan.steps[index]);
+            }
+
+            let request = Self::step_request(&plan, index);
+            // The executor inherits callbacks, tags, metadata and cancellation of the plan run
+            let result = config.run(self.executor.invoke_with_config(&request, config.child())).await;
+
+            let step = &mut plan.steps[index];
+            match result {
+                Ok(answer) => {
+                    step.status = PlanStepStatus::Completed;
+                    step.result = Some(answer);
+                }
+                Err(e) => {
+                    step.status = PlanStepStatus::Failed;
+                    step.result = Some(e.to_string());
+                }
+            }
+            for callback in &config.callbacks {
+                callback.on_plan_step_end(index, &plan.steps[index]);
+            }
+
+            if plan.steps[index].status == PlanStepStatus::Completed {
+                index += 1;
+                continue;
+            }
+
+            config.ensure_not_cancelled()?;
+            if replans >= self.max_replans {
+                return Err(Error::msg(format!(
+                    "Step {} failed after {} re-plans: {}",
+                    index + 1, replans, plan.steps[index].result.clone().unwrap_or_default()
+                )));
+            }
+            replans += 1;
+            warn!("Plan step {} failed, re-planning ({}/{})", index + 1, replans, self.max_replans);
+            self.replan(&mut plan, index).await?;
+            info!("Revised plan:\n{}", plan.to_markdown());
+            for callback in &config.callbacks {
+                callback.on_plan(&plan, replans);
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+impl Agent for PlanAndExecuteAgent {
+    fn tools(&self) -> Vec<Box<dyn Tool + Send + Sync>> {
+        vec![Box::new(self.executor.clone())]
+    }
+
+    fn clone_agent(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
+
+impl Runnable<HashMap<String, String>, AgentOutput> for PlanAndExecuteAgent {
+    fn invoke(&self, input: HashMap<String, String>) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, Error>> + Send>> {
+        self.invoke_with_config(input, None)
+    }
+
+    fn invoke_with_config(
+        &self,
+        input: HashMap<String, String>,
+        config: Option<RunnableConfig>,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, Error>> + Send>> {
+        let agent = self.clone();
+        let objective = input.get("input").cloned().unwrap_or_default().trim().to_string();
+        let config = config.unwrap_or_default();
+
+        Box::pin(async move {
+            let child = config.child();
+            config.run(async move {
+                let plan = agent.plan(&objective).await?;
+                let plan = agent.execute(plan, &child).await?;
+
+                let answer = agent.synthesize(&plan).await?;
+                let return_values = HashMap::from([
+                    ("answer".to_string(), answer),
+                    ("plan".to_string(), serde_json::to_string(&plan)?),
+                ]);
+                Ok(AgentOutput::Finish(AgentFinish { return_values }))
+            }).await
+        })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<HashMap<String, String>, AgentOutput> + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callbacks::CallbackHandler;
+    use crate::models::{FakeChatModel, FakeResponse};
+    use crate::test_utils::fake_agent;
+    use crate::McpAgent;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct PlanRecorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl CallbackHandler for PlanRecorder {
+        fn on_plan(&self, plan: &Plan, revision: usize) {
+            self.events.lock().unwrap().push(format!("plan#{}:{}", revision, plan.steps.len()));
+        }
+
+        fn on_plan_step_end(&self, index: usize, step: &PlanStep) {
+            self.events.lock().unwrap().push(format!("step{}:{:?}", index + 1, step.status));
+        }
+
+        fn on_tool_start(&self, tool_name: &str, _input: &str) {
+            self.events.lock().unwrap().push(format!("tool:{}", tool_name));
+        }
+    }
+
+    fn executor(model: &FakeChatModel) -> Arc<McpAgent> {
+        Arc::new(fake_agent("Executor", model))
+    }
+
+    #[tokio::test]
+    async fn test_plan_execute_and_replan() {
+        let planner = FakeChatModel::new(vec![
+            FakeResponse::text(r#"{"steps": ["Deploy an ERC20", "Mint 100 tokens", "Transfer 50 to 0xabc"]}"#),
+            FakeResponse::text(r#"{"steps": ["Mint 100 tokens with the owner account", "Transfer 50 to 0xabc"]}"#),
+        ]);
+        let executor_model = FakeChatModel::new(vec![
+            FakeResponse::answer("Deployed at 0x123"),
+            // Unknown tools make the executor run fail
+            FakeResponse::tool_call("mint_tokens", serde_json::json!({ "amount": 100 })),
+            FakeResponse::answer("Minted 100"),
+            FakeResponse::answer("Transferred 50"),
+        ]);
+        let agent = PlanAndExecuteAgent::new(Arc::new(planner.clone()), executor(&executor_model));
+
+        // The plan can be reviewed before execution
+        let plan = agent.plan("Deploy an ERC20, then mint, then transfer half to 0xabc").await.unwrap();
+        assert_eq!(plan.steps.len(), 3);
+        assert!(plan.steps.iter().all(|s| s.status == PlanStepStatus::Pending));
+
+        let recorder = Arc::new(PlanRecorder::default());
+        let config = RunnableConfig::new().with_callback(recorder.clone());
+        let plan = agent.execute(plan, &config).await.unwrap();
+
+        assert_eq!(plan.steps.len(), 3);
+        assert!(plan.steps.iter().all(|s| s.status == PlanStepStatus::Completed));
+        assert_eq!(plan.steps[1].description, "Mint 100 tokens with the owner account");
+        assert!(plan.steps[2].result.as_ref().unwrap().contains("Transferred 50"));
+        assert_eq!(*recorder.events.lock().unwrap(), vec![
+            "plan#0:3", "step1:Completed", "step2:Failed", "plan#1:3", "step2:Completed", "step3:Completed",
+        ]);
+
+        // The re-plan request carried the completed step and the error
+        let replan_request = planner.requests().pop().unwrap();
+        assert!(matches!(&replan_request[1], ChatMessage::Human(c) if c.content.contains("Deployed at 0x123") && c.content.contains("mint_tokens does not exist")));
+        // Executor steps see earlier results
+        let last_step = executor_model.requests().pop().unwrap();
+        assert!(matches!(last_step.last(), Some(ChatMessage::Human(c)) if c.content.contains("Minted 100")));
+    }
+
+    #[tokio::test]
+    async fn test_answer_is_synthesized_from_step_results() {
+        let planner = FakeChatModel::new(vec![
+            FakeResponse::text(r#"{"steps": ["Check the balance of 0xabc", "Check the ETH price"]}"#),
+            FakeResponse::text("0xabc holds 1 ETH, worth 3000 USDT"),
+        ]);
+        let executor_model = FakeChatModel::new(vec![
+            FakeResponse::answer("1 ETH"),
+            FakeResponse::answer("3000 USDT"),
+        ]);
+        let agent = PlanAndExecuteAgent::new(Arc::new(planner.clone()), executor(&executor_model));
+
+        let output = agent.invoke(HashMap::from([("input".to_string(), "What is 0xabc worth?".to_string())])).await.unwrap();
+        let AgentOutput::Finish(finish) = output else { panic!("expected a finish") };
+        assert_eq!(finish.return_values["answer"], "0xabc holds 1 ETH, worth 3000 USDT");
+
+        // The synthesis sees every step result, not only the last one
+        let synthesis_request = planner.requests().pop().unwrap();
+        assert!(matches!(&synthesis_request[1], ChatMessage::Human(c) if c.content.contains("1 ETH") && c.content.contains("3000 USDT")));
+    }
+
+    #[tokio::test]
+    async fn test_empty_replan_fails() {
+        let planner = FakeChatModel::new(vec![
+            FakeResponse::text(r#"["Deploy an ERC20", "Mint 100 tokens"]"#),
+            FakeResponse::text(r#"{"steps": []}"#),
+        ]);
+        let executor_model = FakeChatModel::new(vec![
+            FakeResponse::answer("Deployed at 0x123"),
+            FakeResponse::tool_call("mint_tokens", serde_json::json!({ "amount": 100 })),
+        ]);
+        let agent = PlanAndExecuteAgent::new(Arc::new(planner), executor(&executor_model));
+
+        let plan = agent.plan("Deploy and mint").await.unwrap();
+        let error = agent.execute(plan, &RunnableConfig::new()).await.unwrap_err();
+        assert!(error.to_string().contains("no remaining steps after step 2 failed"));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_replans() {
+        let planner = FakeChatModel::new(vec![FakeResponse::text(r#"["Do it"]"#)]);
+        let executor_model = FakeChatModel::new(vec![FakeResponse::tool_call("boom", serde_json::json!({}))]);
+        let agent = PlanAndExecuteAgent::new(Arc::new(planner), executor(&executor_model)).with_max_replans(0);
+
+        let result = agent.invoke(HashMap::from([("input".to_string(), "Task".to_string())])).await;
+        assert!(result.unwrap_err().to_string().contains("boom"));
+    }
+
+    #[tokio::test]
+    async fn test_executor_runs_under_plan_config() {
+        let planner = FakeChatModel::new(vec![FakeResponse::text(r#"{"steps": ["Check the balance of 0xabc"]}"#)]);
+        let executor_model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("lookup", serde_json::json!({ "input": "0xabc" })),
+            FakeResponse::answer("0xabc holds 1 ETH"),
+        ]);
+        let lookup_model = FakeChatModel::new(vec![FakeResponse::answer("1 ETH")]);
+        let mut executor_agent = Arc::try_unwrap(executor(&executor_model)).ok().unwrap();
+        executor_agent.add_tool(Box::new(AgentTool::new("lookup", "Looks up balances.", executor(&lookup_model))));
+        let agent = PlanAndExecuteAgent::new(Arc::new(planner), Arc::new(executor_agent));
+
+        let recorder = Arc::new(PlanRecorder::default());
+        let config = RunnableConfig::new().with_callback(recorder.clone());
+        let plan = agent.plan("Check the balance of 0xabc").await.unwrap();
+        agent.execute(plan, &config).await.unwrap();
+
+        // Tool calls inside the executor reach the plan's callbacks
+        assert_eq!(*recorder.events.lock().unwrap(), vec!["plan#0:1", "tool:lookup", "step1:Completed"]);
+    }
+}
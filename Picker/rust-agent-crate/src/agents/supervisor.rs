@@ -0,0 +1,390 @@
+// Supervisor agent routing each request to one specialist sub-agent
+use anyhow::Error;
+use log::{info, warn};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::core::{Runnable, RunnableConfig};
+use crate::memory::BaseMemory;
+use crate::models::{ChatMessage, ChatMessageContent, ChatModel};
+use crate::tools::Tool;
+use super::agent::{Agent, AgentFinish, AgentOutput};
+use super::agent_tool::AgentTool;
+use super::mcp_agent::McpAgent;
+
+const SUPERVISOR_PROMPT: &str = "You are a supervisor that routes user requests to specialist agents.";
+
+/// How a sub-agent's conversation memory relates to the supervisor's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubAgentMemory {
+    /// The sub-agent reads and writes the supervisor's memory
+    Shared,
+    /// The sub-agent keeps its own memory (or none), the supervisor records the exchange in its memory
+    Isolated,
+}
+
+#[derive(Clone)]
+struct SubAgent {
+    tool: AgentTool,
+    memory: SubAgentMemory,
+}
+
+/// Agent delegating each request to the best suited sub-agent, chosen by a router model
+///
+/// Each sub-agent only sees its own tools, which keeps specialist prompts small.
+#[derive(Clone)]
+pub struct SupervisorAgent {
+    router: Arc<dyn ChatModel>,
+    system_prompt: String,
+    sub_agents: Vec<SubAgent>,
+    memory: Option<Box<dyn BaseMemory>>,
+}
+
+impl SupervisorAgent {
+    pub fn new(router: Arc<dyn ChatModel>) -> Self {
+        Self {
+            router,
+            system_prompt: SUPERVISOR_PROMPT.to_string(),
+            sub_agents: Vec::new(),
+            memory: None,
+        }
+    }
+
+    pub fn with_system_prompt(mut self, system_prompt: &str) -> Self {
+        self.system_prompt = system_prompt.to_string();
+        self
+    }
+
+    /// Set the supervisor memory, call before adding sub-agents with shared memory
+    pub fn with_memory(mut self, memory: Box<dyn BaseMemory>) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Add an McpAgent as a sub-agent
+    pub fn with_sub_agent(mut self, name: &str, description: &str, mut agent: McpAgent, memory: SubAgentMemory) -> Self {
+        let memory = match (memory, &self.memory) {
+            (SubAgentMemory::Shared, Some(shared)) => {
+                agent.set_memory(shared.clone());
+                SubAgentMemory::Shared
+            }
+            (SubAgentMemory::Shared, None) => {
+                warn!("Sub-agent {} requested shared memory but the supervisor has none, using isolated memory", name);
+                SubAgentMemory::Isolated
+            }
+            (SubAgentMemory::Isolated, _) => SubAgentMemory::Isolated,
+        };
+        self.sub_agents.push(SubAgent {
+            tool: AgentTool::new(name, description, Arc::new(agent)),
+            memory,
+        });
+        self
+    }
+
+    /// Add any agent already wrapped as an AgentTool, it keeps its own memory
+    pub fn with_agent_tool(mut self, tool: AgentTool) -> Self {
+        self.sub_agents.push(SubAgent { tool, memory: SubAgentMemory::Isolated });
+        self
+    }
+
+    pub fn sub_agent_names(&self) -> Vec<String> {
+        self.sub_agents.iter().map(|s| s.tool.name().to_string()).collect()
+    }
+
+    fn message(content: String) -> ChatMessageContent {
+        ChatMessageContent { content, name: None, additional_kwargs: HashMap::new(), parts: Vec::new() }
+    }
+
+    // Recent conversation lines from the supervisor memory, used as routing context
+    async fn history_context(memory: &Option<Box<dyn BaseMemory>>) -> String {
+        let Some(memory) = memory else {
+            return String::new();
+        };
+        let variables = match memory.load_memory_variables(&HashMap::new()).await {
+            Ok(variables) => variables,
+            Err(e) => {
+                warn!("Failed to load supervisor memory: {}", e);
+                return String::new();
+            }
+        };
+        let Some(Value::Array(history)) = variables.get("chat_history") else {
+            return String::new();
+        };
+        history.iter().rev().take(6).rev()
+            .filter_map(|msg| Some(format!("{}: {}", msg.get("role")?.as_str()?, msg.get("content")?.as_str()?)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// Routing decision returned by the router model
+enum Route {
+    Delegate { agent: usize, input: String },
+    Answer(String),
+}
+
+async fn route(
+    router: &Arc<dyn ChatModel>,
+    system_prompt: &str,
+    sub_agents: &[SubAgent],
+    history: &str,
+    input: &str,
+) -> Result<Route, Error> {
+    if sub_agents.is_empty() {
+        return Err(Error::msg("SupervisorAgent has no sub-agents"));
+    }
+    if sub_agents.len() == 1 {
+        return Ok(Route::Delegate { agent: 0, input: input.to_string() });
+    }
+
+    let agent_list: String = sub_agents.iter()
+        .map(|s| format!("- {}: {}\n", s.tool.name(), s.tool.description()))
+        .collect();
+    let mut prompt = format!(
+        "{}\nAvailable agents:\n{}\nReply only with JSON: {{\"agent\": \"<agent name>\", \"input\": \"<request for that agent>\"}}. \
+If no agent fits, reply {{\"content\": \"<your answer>\"}}.",
+        system_prompt, agent_list
+    );
+    if !history.is_empty() {
+        prompt.push_str(&format!("\n\nRecent conversation:\n{}", history));
+    }
+
+    let completion = router.invoke(vec![
+        ChatMessage::System(SupervisorAgent::message(prompt)),
+        ChatMessage::Human(SupervisorAgent::message(input.to_string())),
+    ]).await?;
+    let content = match completion.message {
+        ChatMessage::AIMessage(content) => content.content,
+        other => return Err(Error::msg(format!("Unexpected router message: {:?}", other))),
+    };
+
+    let start = content.find('{').unwrap_or(0);
+    let end = content.rfind('}').map(|i| i + 1).unwrap_or(content.len());
+    let decision: Value = serde_json::from_str(&content[start..end])
+        .map_err(|e| Error::msg(format!("Router reply is not valid JSON ({}): {}", e, content)))?;
+
+    if let Some(name) = decision.get("agent").and_then(|v| v.as_str()) {
+        let agent = sub_agents.iter()
+            .position(|s| s.tool.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| Error::msg(format!("Router selected unknown agent: {}", name)))?;
+        let input = decision.get("input").and_then(|v| v.as_str()).unwrap_or(input).to_string();
+        return Ok(Route::Delegate { agent, input });
+    }
+    if let Some(answer) = decision.get("content").and_then(|v| v.as_str()) {
+        return Ok(Route::Answer(answer.to_string()));
+    }
+    Err(Error::msg(format!("Router reply has neither agent nor content: {}", content)))
+}
+
+impl Agent for SupervisorAgent {
+    fn tools(&self) -> Vec<Box<dyn Tool + Send + Sync>> {
+        self.sub_agents.iter()
+            .map(|s| Box::new(s.tool.clone()) as Box<dyn Tool + Send + Sync>)
+            .collect()
+    }
+
+    fn clone_agent(&self) -> Box<dyn Agent> {
+        Box::new(self.clone())
+    }
+}
+
+impl Runnable<HashMap<String, String>, AgentOutput> for SupervisorAgent {
+    fn invoke(&self, input: HashMap<String, String>) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, Error>> + Send>> {
+        self.invoke_with_config(input, None)
+    }
+
+    fn invoke_with_config(
+        &self,
+        input: HashMap<String, String>,
+        config: Option<RunnableConfig>,
+    ) -> Pin<Box<dyn std::future::Future<Output = Result<AgentOutput, Error>> + Send>> {
+        let supervisor = self.clone();
+        let input_text = input.get("input").cloned().unwrap_or_default().trim().to_string();
+        let config = config.unwrap_or_default();
+
+        Box::pin(async move {
+            let child = config.child();
+            config.run(async move {
+                let history = SupervisorAgent::history_context(&supervisor.memory).await;
+                let decision = route(&supervisor.router, &supervisor.system_prompt, &supervisor.sub_agents, &history, &input_text).await?;
+
+                let (answer, agent_name, memory_mode) = match decision {
+                    Route::Answer(answer) => (answer, "supervisor".to_string(), SubAgentMemory::Isolated),
+                    Route::Delegate { agent, input } => {
+                        let sub_agent = &supervisor.sub_agents[agent];
+                        info!("Supervisor routed request to {}", sub_agent.tool.name());
+                        let answer = sub_agent.tool.invoke_with_config(&input, child).await?;
+                        (answer, sub_agent.tool.name().to_string(), sub_agent.memory)
+                    }
+                };
+
+                // Shared sub-agents already saved the exchange to the supervisor memory
+                if let (Some(memory), SubAgentMemory::Isolated) = (&supervisor.memory, memory_mode) {
+                    let inputs = HashMap::from([("input".to_string(), Value::String(input_text.clone()))]);
+                    let outputs = HashMap::from([("output".to_string(), Value::String(answer.clone()))]);
+                    if let Err(e) = memory.save_context(&inputs, &outputs).await {
+                        warn!("Failed to save supervisor context: {}", e);
+                    }
+                }
+
+                let return_values = HashMap::from([
+                    ("answer".to_string(), answer),
+                    ("agent".to_string(), agent_name),
+                ]);
+                Ok(AgentOutput::Finish(AgentFinish { return_values }))
+            }).await
+        })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<HashMap<String, String>, AgentOutput> + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SimpleMemory;
+    use crate::models::{FakeChatModel, FakeResponse};
+    use crate::test_utils::fake_agent;
+    use serde_json::json;
+
+    fn mcp_agent(model: &FakeChatModel) -> McpAgent {
+        fake_agent("Specialist", model)
+    }
+
+    fn inputs(text: &str) -> HashMap<String, String> {
+        HashMap::from([("input".to_string(), text.to_string())])
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_routes_and_shares_memory() {
+        let router = FakeChatModel::new(vec![
+            FakeResponse::text(r#"{"agent": "wallet_agent", "input": "balance of 0xabc"}"#),
+            FakeResponse::text(r#"{"agent": "task_agent", "input": "list my tasks"}"#),
+            FakeResponse::text(r#"{"content": "I can only help with wallets and tasks"}"#),
+        ]);
+        let wallet_model = FakeChatModel::new(vec![FakeResponse::answer("1 ETH")]);
+        let task_model = FakeChatModel::new(vec![FakeResponse::answer("2 tasks")]);
+        let shared = SimpleMemory::new();
+
+        let supervisor = SupervisorAgent::new(Arc::new(router.clone()))
+            .with_memory(Box::new(shared.clone()))
+            .with_sub_agent("wallet_agent", "Answers wallet questions.", mcp_agent(&wallet_model), SubAgentMemory::Shared)
+            .with_sub_agent("task_agent", "Manages Picker tasks.", mcp_agent(&task_model), SubAgentMemory::Isolated);
+        assert_eq!(supervisor.sub_agent_names(), vec!["wallet_agent", "task_agent"]);
+
+        let AgentOutput::Finish(finish) = supervisor.invoke(inputs("How much ETH do I have?")).await.unwrap() else {
+            panic!("supervisor must finish");
+        };
+        assert_eq!(finish.return_values["agent"], "wallet_agent");
+        assert!(finish.return_values["answer"].contains("1 ETH"));
+        // The wallet agent received the rewritten request
+        let wallet_request = wallet_model.requests().pop().unwrap();
+        assert!(matches!(wallet_request.last(), Some(ChatMessage::Human(c)) if c.content == "balance of 0xabc"));
+
+        let AgentOutput::Finish(finish) = supervisor.invoke(inputs("What are my tasks?")).await.unwrap() else {
+            panic!("supervisor must finish");
+        };
+        assert_eq!(finish.return_values["agent"], "task_agent");
+
+        let AgentOutput::Finish(finish) = supervisor.invoke(inputs("Tell me a joke")).await.unwrap() else {
+            panic!("supervisor must finish");
+        };
+        assert_eq!(finish.return_values["agent"], "supervisor");
+
+        // Shared sub-agent wrote its exchange, isolated exchanges were recorded by the supervisor
+        let history = shared.load_memory_variables(&HashMap::new()).await.unwrap()["chat_history"].clone();
+        assert_eq!(history.as_array().unwrap().len(), 6);
+        assert_eq!(history[0]["content"], json!("balance of 0xabc"));
+        assert_eq!(history[2]["content"], json!("What are my tasks?"));
+
+        // The router saw the conversation so far
+        let last_route = router.requests().pop().unwrap();
+        assert!(matches!(&last_route[0], ChatMessage::System(c) if c.content.contains("Recent conversation")));
+    }
+
+    #[tokio::test]
+    async fn test_agent_tool_inside_mcp_agent() {
+        let specialist_model = FakeChatModel::new(vec![FakeResponse::answer("0xabc holds 1 ETH")]);
+        let wallet_tool = AgentTool::new("wallet_agent", "Answers wallet questions.", Arc::new(mcp_agent(&specialist_model)));
+
+        let parent_model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("wallet_agent", json!({ "input": "balance of 0xabc" })),
+            FakeResponse::answer("You have 1 ETH"),
+        ]);
+        let mut parent = mcp_agent(&parent_model);
+        parent.add_tool(Box::new(wallet_tool));
+
+        let answer = crate::run_agent(&parent, "How much ETH do I have?".to_string()).await.unwrap();
+        assert!(answer.contains("You have 1 ETH"));
+        assert_eq!(specialist_model.call_count(), 1);
+        let feedback = parent_model.requests().pop().unwrap();
+        assert!(matches!(feedback.last(), Some(ChatMessage::Human(c)) if c.content.contains("0xabc holds 1 ETH")));
+    }
+
+    #[derive(Default)]
+    struct RecordingCallback {
+        tools: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl crate::callbacks::CallbackHandler for RecordingCallback {
+        fn on_tool_start(&self, tool_name: &str, _input: &str) {
+            self.tools.lock().unwrap().push(tool_name.to_string());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_passes_config_to_sub_agent() {
+        let router = FakeChatModel::new(vec![FakeResponse::text(r#"{"agent": "wallet_agent", "input": "balance of 0xabc"}"#)]);
+        let wallet_model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("lookup", json!({ "address": "0xabc" })),
+            FakeResponse::answer("1 ETH"),
+        ]);
+        let mut wallet_agent = mcp_agent(&wallet_model);
+        let lookup_model = FakeChatModel::new(vec![FakeResponse::answer("0xabc holds 1 ETH")]);
+        wallet_agent.add_tool(Box::new(AgentTool::new("lookup", "Looks up balances.", Arc::new(mcp_agent(&lookup_model)))));
+
+        let supervisor = SupervisorAgent::new(Arc::new(router))
+            .with_sub_agent("wallet_agent", "Answers wallet questions.", wallet_agent, SubAgentMemory::Isolated);
+
+        // The caller's callbacks observe the tool calls made inside the sub-agent
+        let callback = Arc::new(RecordingCallback::default());
+        let config = RunnableConfig::new().with_callback(callback.clone());
+        let output = supervisor.invoke_with_config(inputs("How much ETH do I have?"), Some(config)).await.unwrap();
+        assert!(matches!(output, AgentOutput::Finish(_)));
+        assert_eq!(*callback.tools.lock().unwrap(), vec!["lookup".to_string()]);
+
+        // A cancelled caller stops the sub-agent as well
+        let config = RunnableConfig::new();
+        config.cancel();
+        let error = supervisor.invoke_with_config(inputs("How much ETH do I have?"), Some(config)).await.unwrap_err();
+        assert!(error.is::<crate::core::RunnableInterrupt>());
+    }
+
+    #[tokio::test]
+    async fn test_agent_tool_runs_under_parent_config() {
+        let wallet_model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("lookup", json!({ "input": "balance of 0xabc" })),
+            FakeResponse::answer("1 ETH"),
+        ]);
+        let lookup_model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("price", json!({ "input": "ETH" })),
+            FakeResponse::answer("0xabc holds 1 ETH"),
+        ]);
+        let price_model = FakeChatModel::new(vec![FakeResponse::answer("1 ETH is 3000 USDT")]);
+        let mut lookup_agent = mcp_agent(&lookup_model);
+        lookup_agent.add_tool(Box::new(AgentTool::new("price", "Quotes prices.", Arc::new(mcp_agent(&price_model)))));
+        let mut wallet_agent = mcp_agent(&wallet_model);
+        wallet_agent.add_tool(Box::new(AgentTool::new("lookup", "Looks up balances.", Arc::new(lookup_agent))));
+
+        // The tool call made inside the sub-agent reaches the caller's callback
+        let callback = Arc::new(RecordingCallback::default());
+        let config = RunnableConfig::new().with_callback(callback.clone());
+        let answer = crate::run_agent_with_config(&wallet_agent, "How much ETH do I have?".to_string(), config).await.unwrap();
+        assert!(answer.contains("1 ETH"));
+        assert_eq!(*callback.tools.lock().unwrap(), vec!["lookup".to_string(), "price".to_string()]);
+    }
+}
@@ -0,0 +1,353 @@
+// rust-agent CLI: chat with an agent, call MCP tools, inspect memory sessions and run eval datasets
+use anyhow::{Error, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use rust_agent::eval::{EvalDataset, EvalReport, EvalRunner};
+use rust_agent::memory::{generate_session_id, MemoryExportFormat};
+use rust_agent::spec::MemoryBackend;
+use rust_agent::{Agent, AgentSpec, CompositeMemory, CompositeMemoryConfig, McpClient};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+#[derive(Parser)]
+#[command(name = "rust-agent", version, about = "Chat with agents and MCP servers, inspect memory sessions and run evaluations")]
+struct Cli {
+    /// Agent spec file (TOML, YAML or JSON)
+    #[arg(short, long, global = true, env = "RUST_AGENT_CONFIG", default_value = "rust-agent.toml")]
+    config: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Chat interactively with the configured model and MCP tools
+    Chat {
+        /// Continue an existing memory session instead of starting a new one
+        #[arg(long)]
+        session: Option<String>,
+    },
+    /// List or call MCP tools directly
+    Tools {
+        #[command(subcommand)]
+        command: ToolsCommand,
+    },
+    /// Inspect and export memory sessions
+    Memory {
+        #[command(subcommand)]
+        command: MemoryCommand,
+    },
+    /// Run a JSONL eval dataset against the configured agent
+    Eval {
+        dataset: PathBuf,
+        /// Report of a previous run, regressions make the command fail
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+        /// Save the report as JSON
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ToolsCommand {
+    /// List the tools of all configured MCP servers
+    List,
+    /// Call a tool by its namespaced name, e.g. `chain.transfer_coin`
+    Call {
+        name: String,
+        /// Arguments as a JSON object
+        #[arg(long, default_value = "{}")]
+        args: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MemoryCommand {
+    /// List the sessions stored in the memory data directory
+    List,
+    /// Show statistics and the latest messages of a session
+    Show {
+        session: String,
+        #[arg(long, default_value_t = 10)]
+        last: usize,
+    },
+    /// Export a session as JSON or Markdown
+    Export {
+        session: String,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+        /// Write to a file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Json,
+    Markdown,
+}
+
+impl From<ExportFormat> for MemoryExportFormat {
+    fn from(format: ExportFormat) -> Self {
+        match format {
+            ExportFormat::Json => MemoryExportFormat::Json,
+            ExportFormat::Markdown => MemoryExportFormat::Markdown,
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let cli = Cli::parse();
+    if let Err(e) = run(cli).await {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let spec = AgentSpec::load(&cli.config).await?;
+    match cli.command {
+        Command::Chat { session } => chat(&spec, session).await,
+        Command::Tools { command: ToolsCommand::List } => {
+            let client = spec.mcp_client().await?;
+            for tool in client.get_tools().await? {
+                println!("{}\n    {}", tool.name, tool.description);
+            }
+            Ok(())
+        }
+        Command::Tools { command: ToolsCommand::Call { name, args } } => {
+            let args: HashMap<String, Value> = serde_json::from_str(&args)
+                .map_err(|e| Error::msg(format!("--args must be a JSON object: {}", e)))?;
+            let client = spec.mcp_client().await?;
+            let result = client.call_tool(&name, args).await?;
+            println!("{}", serde_json::to_string_pretty(&result)?);
+            Ok(())
+        }
+        Command::Memory { command: MemoryCommand::List } => list_sessions(&spec).await,
+        Command::Memory { command: MemoryCommand::Show { session, last } } => {
+            print!("{}", show_session(&spec, &session, last).await?);
+            Ok(())
+        }
+        Command::Memory { command: MemoryCommand::Export { session, format, output } } => {
+            match output {
+                Some(path) => {
+                    open_existing_session(&spec, &session).await?.export_session_to_file(&path, format.into()).await?;
+                    println!("Exported session {} to {}", session, path.display());
+                }
+                None => println!("{}", open_existing_session(&spec, &session).await?.export_session_to_string(format.into()).await?),
+            }
+            Ok(())
+        }
+        Command::Eval { dataset, baseline, output } => eval(&spec, dataset, baseline, output).await,
+    }
+}
+
+// Session ids become file names, so anything but a plain id (e.g. `../x`) is refused
+fn validate_session_id(session_id: &str) -> Result<()> {
+    let plain = !session_id.is_empty()
+        && session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !plain {
+        return Err(Error::msg(format!("Invalid session id {:?}, only letters, digits, '_' and '-' are allowed", session_id)));
+    }
+    Ok(())
+}
+
+// Summarization is off, so show and export never call the model or rewrite the stored summary
+async fn open_existing_session(spec: &AgentSpec, session_id: &str) -> Result<CompositeMemory> {
+    validate_session_id(session_id)?;
+    if spec.memory.backend != MemoryBackend::Composite {
+        let backend = format!("{:?}", spec.memory.backend).to_lowercase();
+        return Err(Error::msg(format!("Memory backend \"{}\" does not persist sessions, use \"composite\"", backend)));
+    }
+    let history = spec.memory.data_dir.join(format!("{}_history.jsonl", session_id));
+    if !tokio::fs::try_exists(&history).await.unwrap_or(false) {
+        return Err(Error::msg(format!("Session {} not found in {}", session_id, spec.memory.data_dir.display())));
+    }
+    CompositeMemory::with_config(CompositeMemoryConfig {
+        data_dir: spec.memory.data_dir.clone(),
+        session_id: Some(session_id.to_string()),
+        summary_threshold: spec.memory.summary_threshold,
+        recent_messages_count: spec.memory.recent_messages,
+        auto_generate_summary: false,
+    }).await
+}
+
+async fn show_session(spec: &AgentSpec, session_id: &str, last: usize) -> Result<String> {
+    let memory = open_existing_session(spec, session_id).await?;
+    let mut out = format!("{}\n", serde_json::to_string_pretty(&memory.get_memory_stats().await?)?);
+    if let Some(summary) = memory.get_summary().await? {
+        out.push_str(&format!("\nSummary: {}\n", summary));
+    }
+    out.push('\n');
+    for message in memory.get_recent_messages(last).await? {
+        out.push_str(&format!("[{}] {}: {}\n", message.timestamp, message.role, message.content));
+    }
+    Ok(out)
+}
+
+async fn chat(spec: &AgentSpec, session: Option<String>) -> Result<()> {
+    if let Some(session_id) = &session {
+        validate_session_id(session_id)?;
+    }
+    // The session is chosen here so it can be printed, the spec decides the memory backend
+    let session_id = session.unwrap_or_else(generate_session_id);
+    let mut spec = spec.clone();
+    spec.memory.session_id = Some(session_id.clone());
+    let agent = spec.build().await?;
+    println!("Session {} with model {}, {} tools. Type /tools to list them, /exit to quit.", session_id, spec.model.model, agent.agent.tools().len());
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    loop {
+        eprint!("> ");
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        match line.trim() {
+            "" => continue,
+            "/exit" | "/quit" => break,
+            "/tools" => {
+                for tool in agent.agent.tools() {
+                    println!("{}: {}", tool.name(), tool.description());
+                }
+            }
+            input => match agent.run(input.to_string()).await {
+                Ok(answer) => println!("{}", answer),
+                Err(e) => eprintln!("Error: {:#}", e),
+            },
+        }
+    }
+    if spec.memory.backend == MemoryBackend::Composite {
+        println!("Session {} saved", session_id);
+    }
+    Ok(())
+}
+
+async fn list_sessions(spec: &AgentSpec) -> Result<()> {
+    let mut sessions = Vec::new();
+    let mut entries = match tokio::fs::read_dir(&spec.memory.data_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    while let Some(entry) = entries.next_entry().await? {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if let Some(session_id) = name.strip_suffix("_history.jsonl") {
+            let modified: chrono::DateTime<chrono::Local> = entry.metadata().await?.modified()?.into();
+            sessions.push((modified, session_id.to_string()));
+        }
+    }
+    // Most recently used first
+    sessions.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (modified, session_id) in sessions {
+        println!("{}  {}", modified.format("%Y-%m-%d %H:%M:%S"), session_id);
+    }
+    Ok(())
+}
+
+async fn eval(spec: &AgentSpec, dataset: PathBuf, baseline: Option<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    let dataset = EvalDataset::from_jsonl_file(&dataset).await?;
+    // Cases are independent, so no memory is carried between them
+    let mut spec = spec.clone();
+    spec.memory.backend = MemoryBackend::None;
+    let agent = spec.build().await?;
+    let report = agent.eval(&EvalRunner::with_default_scorers(), &dataset).await?;
+    let baseline = match baseline {
+        Some(path) => Some(EvalReport::load(path).await?),
+        None => None,
+    };
+    println!("{}", report.to_markdown(baseline.as_ref()));
+    if let Some(path) = output {
+        report.save(path).await?;
+    }
+    let regressions = baseline.map(|baseline| report.regressions(&baseline).len()).unwrap_or(0);
+    if regressions > 0 {
+        return Err(Error::msg(format!("{} cases regressed against the baseline", regressions)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_agent::memory::ChatMessage;
+    use rust_agent::SpecFormat;
+
+    fn spec_with_data_dir(data_dir: &std::path::Path) -> AgentSpec {
+        let mut spec = AgentSpec::parse("[model]\nmodel = \"gpt-4o-mini\"", SpecFormat::Toml).unwrap();
+        spec.memory.data_dir = data_dir.to_path_buf();
+        spec
+    }
+
+    async fn seed_session(spec: &AgentSpec, session_id: &str) {
+        let memory = CompositeMemory::with_config(CompositeMemoryConfig {
+            data_dir: spec.memory.data_dir.clone(),
+            session_id: Some(session_id.to_string()),
+            auto_generate_summary: false,
+            ..Default::default()
+        }).await.unwrap();
+        for (role, content) in [("user", "How much is the red mug?"), ("assistant", "It costs 5 USDT")] {
+            memory.add_message(ChatMessage {
+                id: generate_session_id(),
+                role: role.to_string(),
+                content: content.to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                metadata: None,
+            }).await.unwrap();
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let cli = Cli::try_parse_from(["rust-agent", "--config", "agent.yaml", "memory", "show", "s1", "--last", "3"]).unwrap();
+        assert_eq!(cli.config, PathBuf::from("agent.yaml"));
+        assert!(matches!(cli.command, Command::Memory { command: MemoryCommand::Show { ref session, last: 3 } } if session == "s1"));
+
+        let cli = Cli::try_parse_from(["rust-agent", "memory", "export", "s1", "--format", "json", "--output", "s1.json"]).unwrap();
+        assert!(matches!(cli.command, Command::Memory { command: MemoryCommand::Export { format: ExportFormat::Json, output: Some(_), .. } }));
+
+        let cli = Cli::try_parse_from(["rust-agent", "tools", "call", "chain.transfer_coin"]).unwrap();
+        assert!(matches!(cli.command, Command::Tools { command: ToolsCommand::Call { ref args, .. } } if args == "{}"));
+
+        assert!(Cli::try_parse_from(["rust-agent", "memory", "export", "s1", "--format", "csv"]).is_err());
+        assert!(Cli::try_parse_from(["rust-agent", "memory", "show"]).is_err());
+    }
+
+    #[test]
+    fn test_validate_session_id() {
+        assert!(validate_session_id(&generate_session_id()).is_ok());
+        assert!(validate_session_id("shop_session-2").is_ok());
+        for session_id in ["", "../secret", "a/b", "..", "s1.jsonl"] {
+            assert!(validate_session_id(session_id).is_err(), "{} was accepted", session_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_show_and_export_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = spec_with_data_dir(dir.path());
+        seed_session(&spec, "shop").await;
+
+        let shown = show_session(&spec, "shop", 1).await.unwrap();
+        assert!(shown.contains("assistant: It costs 5 USDT"));
+        assert!(!shown.contains("red mug"));
+        assert!(!shown.contains("Summary:"));
+
+        let memory = open_existing_session(&spec, "shop").await.unwrap();
+        let markdown = memory.export_session_to_string(MemoryExportFormat::Markdown).await.unwrap();
+        assert!(markdown.contains("How much is the red mug?"));
+        let json: Value = serde_json::from_str(&memory.export_session_to_string(MemoryExportFormat::Json).await.unwrap()).unwrap();
+        assert_eq!(json["session_id"], "shop");
+
+        assert!(show_session(&spec, "missing", 10).await.unwrap_err().to_string().contains("not found"));
+        assert!(show_session(&spec, "../shop", 10).await.unwrap_err().to_string().contains("Invalid session id"));
+
+        let mut simple = spec.clone();
+        simple.memory.backend = MemoryBackend::Simple;
+        assert!(show_session(&simple, "shop", 10).await.unwrap_err().to_string().contains("does not persist sessions"));
+    }
+}
@@ -1,5 +1,7 @@
 // Callback handler interface definition
-use crate::agents::{AgentAction, AgentFinish};
+use crate::agents::{AgentAction, AgentFinish, Plan, PlanStep};
+use crate::models::CacheHit;
+use crate::tools::ToolProgress;
 
 // Minimal callback system (aligned with langchain-core)
 pub trait CallbackHandler: Send + Sync {
@@ -12,6 +14,9 @@ pub trait CallbackHandler: Send + Sync {
     
     fn on_llm_error(&self, _model_name: &str, _error: &str) {}
     
+    // The response came from a CachedChatModel instead of the model, called before on_llm_end
+    fn on_llm_cache_hit(&self, _model_name: &str, _hit: &CacheHit) {}
+    
     // Tool related callbacks (core)
     fn on_tool_start(&self, _tool_name: &str, _input: &str) {}
     
@@ -19,6 +24,8 @@ pub trait CallbackHandler: Send + Sync {
     
     fn on_tool_error(&self, _tool_name: &str, _error: &str) {}
     
+    fn on_tool_progress(&self, _tool_name: &str, _progress: &ToolProgress) {}
+    
     // Chain related callbacks (core)
     fn on_chain_start(&self, _chain_name: &str) {}
     
@@ -30,4 +37,11 @@ pub trait CallbackHandler: Send + Sync {
     fn on_agent_action(&self, _action: &AgentAction) {}
     
     fn on_agent_finish(&self, _finish: &AgentFinish) {}
+    
+    // Plan related callbacks (plan-and-execute agents), `revision` is 0 for the initial plan
+    fn on_plan(&self, _plan: &Plan, _revision: usize) {}
+    
+    fn on_plan_step_start(&self, _index: usize, _step: &PlanStep) {}
+    
+    fn on_plan_step_end(&self, _index: usize, _step: &PlanStep) {}
 }
\ No newline at end of file
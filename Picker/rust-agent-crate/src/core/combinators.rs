@@ -0,0 +1,386 @@
+// Runnable combinators: lambda, parallel fan-out, conditional branching, retry and fallbacks
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use log::warn;
+
+use super::config::{RunnableConfig, RunnableInterrupt};
+use super::runnable::Runnable;
+
+// Boxed future returned by runnables
+type RunnableFuture<O> = Pin<Box<dyn Future<Output = Result<O, Error>> + Send>>;
+
+// Shared runnable handle used by combinators
+type SharedRunnable<I, O> = Arc<dyn Runnable<I, O> + Send + Sync>;
+
+// Routing condition used by RunnableBranch
+type BranchCondition<I> = Arc<dyn Fn(&I) -> bool + Send + Sync>;
+
+/// Wrap an async closure as a Runnable
+pub struct RunnableLambda<I, O> {
+    func: Arc<dyn Fn(I) -> RunnableFuture<O> + Send + Sync>,
+}
+
+impl<I: Send + 'static, O: Send + 'static> RunnableLambda<I, O> {
+    pub fn new<F, Fut>(func: F) -> Self
+    where
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<O, Error>> + Send + 'static,
+    {
+        Self {
+            func: Arc::new(move |input| Box::pin(func(input))),
+        }
+    }
+}
+
+impl<I: Send + 'static, O: Send + 'static> Runnable<I, O> for RunnableLambda<I, O> {
+    fn invoke(&self, input: I) -> RunnableFuture<O> {
+        (self.func)(input)
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
+        Box::new(RunnableLambda { func: self.func.clone() })
+    }
+}
+
+/// Run named branches concurrently on the same input and collect their outputs into a map
+pub struct RunnableParallel<I, O> {
+    branches: Vec<(String, SharedRunnable<I, O>)>,
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> RunnableParallel<I, O> {
+    pub fn new() -> Self {
+        Self { branches: Vec::new() }
+    }
+
+    /// Add a named branch
+    pub fn with_branch(mut self, name: &str, runnable: impl Runnable<I, O> + 'static) -> Self {
+        self.branches.push((name.to_string(), Arc::new(runnable)));
+        self
+    }
+
+    /// Get the branch names in insertion order
+    pub fn branch_names(&self) -> Vec<String> {
+        self.branches.iter().map(|(name, _)| name.clone()).collect()
+    }
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> Default for RunnableParallel<I, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> Runnable<I, HashMap<String, O>> for RunnableParallel<I, O> {
+    fn invoke(&self, input: I) -> RunnableFuture<HashMap<String, O>> {
+        self.invoke_with_config(input, None)
+    }
+
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> RunnableFuture<HashMap<String, O>> {
+        let branches = self.branches.clone();
+        let config = config.unwrap_or_default();
+
+        Box::pin(async move {
+            let child = config.child();
+            let results = config.run(async move {
+                Ok(futures::future::join_all(branches.iter().map(|(name, runnable)| {
+                    let future = runnable.invoke_with_config(input.clone(), Some(child.clone()));
+                    async move { (name.clone(), future.await) }
+                })).await)
+            }).await?;
+
+            let mut outputs = HashMap::new();
+            for (name, result) in results {
+                let output = result.map_err(|e| Error::msg(format!("Parallel branch '{}' failed: {}", name, e)))?;
+                outputs.insert(name, output);
+            }
+            Ok(outputs)
+        })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<I, HashMap<String, O>> + Send + Sync> {
+        Box::new(RunnableParallel { branches: self.branches.clone() })
+    }
+}
+
+/// Route the input to the first branch whose condition matches, or to the default branch
+pub struct RunnableBranch<I, O> {
+    branches: Vec<(BranchCondition<I>, SharedRunnable<I, O>)>,
+    default: SharedRunnable<I, O>,
+}
+
+impl<I: Send + 'static, O: Send + 'static> RunnableBranch<I, O> {
+    pub fn new(default: impl Runnable<I, O> + 'static) -> Self {
+        Self {
+            branches: Vec::new(),
+            default: Arc::new(default),
+        }
+    }
+
+    /// Add a conditional branch, conditions are checked in insertion order
+    pub fn with_branch<F>(mut self, condition: F, runnable: impl Runnable<I, O> + 'static) -> Self
+    where
+        F: Fn(&I) -> bool + Send + Sync + 'static,
+    {
+        self.branches.push((Arc::new(condition), Arc::new(runnable)));
+        self
+    }
+}
+
+impl<I: Send + 'static, O: Send + 'static> Runnable<I, O> for RunnableBranch<I, O> {
+    fn invoke(&self, input: I) -> RunnableFuture<O> {
+        let selected = self.branches.iter()
+            .find(|(condition, _)| condition(&input))
+            .map(|(_, runnable)| runnable)
+            .unwrap_or(&self.default);
+        selected.invoke(input)
+    }
+
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> RunnableFuture<O> {
+        let selected = self.branches.iter()
+            .find(|(condition, _)| condition(&input))
+            .map(|(_, runnable)| runnable)
+            .unwrap_or(&self.default);
+        selected.invoke_with_config(input, config)
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
+        Box::new(RunnableBranch {
+            branches: self.branches.clone(),
+            default: self.default.clone(),
+        })
+    }
+}
+
+/// Retry a runnable with exponential backoff
+pub struct RunnableRetry<I, O> {
+    inner: SharedRunnable<I, O>,
+    max_attempts: usize,
+    initial_backoff: Duration,
+    backoff_multiplier: u32,
+    max_backoff: Duration,
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> RunnableRetry<I, O> {
+    /// Create a retry wrapper, `max_attempts` includes the first call
+    pub fn new(inner: impl Runnable<I, O> + 'static, max_attempts: usize) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            backoff_multiplier: 2,
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    /// Set the delay before the first retry and the multiplier applied after each retry
+    pub fn with_backoff(mut self, initial_backoff: Duration, multiplier: u32) -> Self {
+        self.initial_backoff = initial_backoff;
+        self.backoff_multiplier = multiplier.max(1);
+        self
+    }
+
+    /// Set the upper bound of the delay between retries, 30 seconds by default
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> Runnable<I, O> for RunnableRetry<I, O> {
+    fn invoke(&self, input: I) -> RunnableFuture<O> {
+        self.invoke_with_config(input, None)
+    }
+
+    // Interrupted runs are not retried, the cancellation token also stops the backoff sleep
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> RunnableFuture<O> {
+        let inner = self.inner.clone();
+        let max_attempts = self.max_attempts;
+        let max_backoff = self.max_backoff;
+        let mut backoff = self.initial_backoff.min(max_backoff);
+        let multiplier = self.backoff_multiplier;
+        let config = config.unwrap_or_default();
+
+        Box::pin(async move {
+            let child = config.child();
+            config.run(async move {
+                let mut attempt = 1;
+                loop {
+                    match inner.invoke_with_config(input.clone(), Some(child.clone())).await {
+                        Ok(output) => return Ok(output),
+                        Err(e) if e.is::<RunnableInterrupt>() => return Err(e),
+                        Err(e) if attempt >= max_attempts => {
+                            return Err(Error::msg(format!("Failed after {} attempts: {}", attempt, e)));
+                        }
+                        Err(e) => {
+                            warn!("Attempt {}/{} failed: {}, retrying in {:?}", attempt, max_attempts, e, backoff);
+                            tokio::time::sleep(backoff).await;
+                            backoff = backoff.checked_mul(multiplier).unwrap_or(Duration::MAX).min(max_backoff);
+                            attempt += 1;
+                        }
+                    }
+                }
+            }).await
+        })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
+        Box::new(RunnableRetry {
+            inner: self.inner.clone(),
+            max_attempts: self.max_attempts,
+            initial_backoff: self.initial_backoff,
+            backoff_multiplier: self.backoff_multiplier,
+            max_backoff: self.max_backoff,
+        })
+    }
+}
+
+/// Try a primary runnable, then each fallback in order until one succeeds
+pub struct RunnableWithFallbacks<I, O> {
+    primary: SharedRunnable<I, O>,
+    fallbacks: Vec<SharedRunnable<I, O>>,
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> RunnableWithFallbacks<I, O> {
+    pub fn new(primary: impl Runnable<I, O> + 'static, fallbacks: Vec<Box<dyn Runnable<I, O> + Send + Sync>>) -> Self {
+        Self {
+            primary: Arc::new(primary),
+            fallbacks: fallbacks.into_iter().map(Arc::from).collect(),
+        }
+    }
+}
+
+impl<I: Clone + Send + 'static, O: Send + 'static> Runnable<I, O> for RunnableWithFallbacks<I, O> {
+    fn invoke(&self, input: I) -> RunnableFuture<O> {
+        self.invoke_with_config(input, None)
+    }
+
+    // Interrupted runs do not fall through to the next candidate
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> RunnableFuture<O> {
+        let candidates: Vec<SharedRunnable<I, O>> = std::iter::once(self.primary.clone())
+            .chain(self.fallbacks.iter().cloned())
+            .collect();
+        let config = config.unwrap_or_default();
+
+        Box::pin(async move {
+            let child = config.child();
+            config.run(async move {
+                let mut errors = Vec::new();
+                for (index, runnable) in candidates.iter().enumerate() {
+                    match runnable.invoke_with_config(input.clone(), Some(child.clone())).await {
+                        Ok(output) => return Ok(output),
+                        Err(e) if e.is::<RunnableInterrupt>() => return Err(e),
+                        Err(e) => {
+                            warn!("Runnable {} of {} failed: {}", index + 1, candidates.len(), e);
+                            errors.push(e.to_string());
+                        }
+                    }
+                }
+                Err(Error::msg(format!("All runnables failed: [{}]", errors.join("; "))))
+            }).await
+        })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
+        Box::new(RunnableWithFallbacks {
+            primary: self.primary.clone(),
+            fallbacks: self.fallbacks.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::RunnableExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn add(n: i64) -> RunnableLambda<i64, i64> {
+        RunnableLambda::new(move |x: i64| async move { Ok(x + n) })
+    }
+
+    fn failing() -> RunnableLambda<i64, i64> {
+        RunnableLambda::new(|_x: i64| async move { Err(Error::msg("boom")) })
+    }
+
+    #[tokio::test]
+    async fn test_parallel_and_branch() {
+        let parallel = RunnableParallel::new()
+            .with_branch("plus_one", add(1))
+            .with_branch("plus_ten", add(10));
+        let outputs = parallel.invoke(5).await.unwrap();
+        assert_eq!(outputs["plus_one"], 6);
+        assert_eq!(outputs["plus_ten"], 15);
+
+        let failing_parallel = RunnableParallel::new().with_branch("bad", failing());
+        assert!(failing_parallel.invoke(1).await.unwrap_err().to_string().contains("bad"));
+
+        let branch = RunnableBranch::new(add(0))
+            .with_branch(|x: &i64| *x < 0, add(100))
+            .with_branch(|x: &i64| *x > 10, add(-10));
+        assert_eq!(branch.invoke(-1).await.unwrap(), 99);
+        assert_eq!(branch.invoke(20).await.unwrap(), 10);
+        assert_eq!(branch.invoke(3).await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_and_fallbacks() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let flaky = RunnableLambda::new(move |x: i64| {
+            let calls = calls_clone.clone();
+            async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(Error::msg("transient"))
+                } else {
+                    Ok(x)
+                }
+            }
+        });
+
+        let retry = flaky.with_retry(3).with_backoff(Duration::from_millis(1), 2);
+        assert_eq!(retry.invoke(7).await.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let exhausted = failing().with_retry(2).with_backoff(Duration::from_millis(1), 1);
+        assert!(exhausted.invoke(1).await.is_err());
+
+        // A huge multiplier saturates at the max backoff instead of overflowing
+        let saturated = failing()
+            .with_retry(6)
+            .with_backoff(Duration::from_millis(1), u32::MAX)
+            .with_max_backoff(Duration::from_millis(2));
+        assert!(saturated.invoke(1).await.is_err());
+
+        let with_fallbacks = failing().with_fallbacks(vec![Box::new(failing()), Box::new(add(2))]);
+        assert_eq!(with_fallbacks.invoke(1).await.unwrap(), 3);
+        assert!(failing().with_fallbacks(vec![]).invoke(1).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_with_max_concurrency() {
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (running_clone, peak_clone) = (running.clone(), peak.clone());
+        let tracked = RunnableLambda::new(move |x: i64| {
+            let (running, peak) = (running_clone.clone(), peak_clone.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(x * 2)
+            }
+        });
+
+        let config = RunnableConfig::new().with_max_concurrency(2);
+        let results = tracked.batch_with_config((0..6).collect(), Some(config)).await;
+
+        let outputs: Vec<i64> = results.into_iter().map(|r| r.unwrap()).collect();
+        assert_eq!(outputs, vec![0, 2, 4, 6, 8, 10]);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+}
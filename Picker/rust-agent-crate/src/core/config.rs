@@ -0,0 +1,273 @@
+// Typed per-invocation configuration for Runnables: tracing info, callbacks, limits and cancellation
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Error;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::callbacks::CallbackHandler;
+
+/// Reason a configured run was stopped before completing
+///
+/// Returned wrapped in `anyhow::Error`, callers can `downcast_ref::<RunnableInterrupt>()` to tell it apart from ordinary failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunnableInterrupt {
+    /// The cancellation token was triggered
+    Cancelled,
+    /// The run exceeded the configured timeout
+    TimedOut(Duration),
+}
+
+impl fmt::Display for RunnableInterrupt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunnableInterrupt::Cancelled => write!(f, "Run was cancelled"),
+            RunnableInterrupt::TimedOut(timeout) => write!(f, "Run timed out after {:?}", timeout),
+        }
+    }
+}
+
+impl std::error::Error for RunnableInterrupt {}
+
+/// Configuration passed to `invoke_with_config` and `batch_with_config`
+///
+/// Cloning a config shares its cancellation token, so cancelling any clone stops every run started with it.
+#[derive(Clone, Default)]
+pub struct RunnableConfig {
+    /// Name reported to chain callbacks, chain callbacks only fire for named runs
+    pub run_name: Option<String>,
+    /// Free-form tags, inherited by nested runs
+    pub tags: Vec<String>,
+    /// Free-form metadata, inherited by nested runs
+    pub metadata: HashMap<String, Value>,
+    /// Callback handlers notified during the run
+    pub callbacks: Vec<Arc<dyn CallbackHandler>>,
+    /// Maximum number of inputs processed at once by `batch_with_config`
+    pub max_concurrency: Option<usize>,
+    /// Maximum duration of the whole run
+    pub timeout: Option<Duration>,
+    /// Token used to cancel the run from outside
+    pub cancellation_token: CancellationToken,
+}
+
+impl fmt::Debug for RunnableConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunnableConfig")
+            .field("run_name", &self.run_name)
+            .field("tags", &self.tags)
+            .field("metadata", &self.metadata)
+            .field("callbacks", &self.callbacks.len())
+            .field("max_concurrency", &self.max_concurrency)
+            .field("timeout", &self.timeout)
+            .field("cancelled", &self.cancellation_token.is_cancelled())
+            .finish()
+    }
+}
+
+impl RunnableConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_run_name(mut self, run_name: &str) -> Self {
+        self.run_name = Some(run_name.to_string());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: Value) -> Self {
+        self.metadata.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn with_callback(mut self, callback: Arc<dyn CallbackHandler>) -> Self {
+        self.callbacks.push(callback);
+        self
+    }
+
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency).filter(|n| *n > 0);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Use an externally owned token, e.g. one stored per chat session by the host application
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = token;
+        self
+    }
+
+    /// Cancel every run using this config or one of its children
+    pub fn cancel(&self) {
+        self.cancellation_token.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    /// Config for a nested run: keeps tags, metadata and callbacks, drops the run name and timeout
+    /// (the parent enforces it) and uses a child token so cancelling the parent also cancels the child
+    pub fn child(&self) -> Self {
+        Self {
+            run_name: None,
+            tags: self.tags.clone(),
+            metadata: self.metadata.clone(),
+            callbacks: self.callbacks.clone(),
+            max_concurrency: self.max_concurrency,
+            timeout: None,
+            cancellation_token: self.cancellation_token.child_token(),
+        }
+    }
+
+    /// Return an error if the run has been cancelled
+    pub fn ensure_not_cancelled(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            return Err(Error::new(RunnableInterrupt::Cancelled));
+        }
+        Ok(())
+    }
+
+    /// Drive a future under this config: stops it on cancellation or timeout and notifies chain callbacks
+    ///
+    /// The future is dropped when the run is interrupted, which aborts any in-flight HTTP request it owns.
+    pub async fn run<O, F>(&self, future: F) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>> + Send,
+    {
+        if let Some(run_name) = &self.run_name {
+            for callback in &self.callbacks {
+                callback.on_chain_start(run_name);
+            }
+        }
+
+        let result = if self.is_cancelled() {
+            Err(Error::new(RunnableInterrupt::Cancelled))
+        } else {
+            let guarded = async {
+                match self.timeout {
+                    Some(timeout) => tokio::time::timeout(timeout, future).await
+                        .unwrap_or_else(|_| Err(Error::new(RunnableInterrupt::TimedOut(timeout)))),
+                    None => future.await,
+                }
+            };
+            tokio::select! {
+                _ = self.cancellation_token.cancelled() => Err(Error::new(RunnableInterrupt::Cancelled)),
+                result = guarded => result,
+            }
+        };
+
+        if let Some(run_name) = &self.run_name {
+            for callback in &self.callbacks {
+                match &result {
+                    Ok(_) => callback.on_chain_end(run_name),
+                    Err(e) => callback.on_chain_error(run_name, &e.to_string()),
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{pipe, Runnable, RunnableLambda};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingHandler {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl CallbackHandler for RecordingHandler {
+        fn on_chain_start(&self, chain_name: &str) {
+            self.events.lock().unwrap().push(format!("start:{}", chain_name));
+        }
+
+        fn on_chain_error(&self, chain_name: &str, _error: &str) {
+            self.events.lock().unwrap().push(format!("error:{}", chain_name));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_timeout() {
+        let handler = Arc::new(RecordingHandler::default());
+        let config = RunnableConfig::new()
+            .with_run_name("slow")
+            .with_callback(handler.clone());
+
+        let canceller = config.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+        let result: Result<(), Error> = config.run(async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await;
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<RunnableInterrupt>(), Some(&RunnableInterrupt::Cancelled));
+        assert_eq!(*handler.events.lock().unwrap(), vec!["start:slow", "error:slow"]);
+
+        // Child tokens follow the parent
+        assert!(config.child().is_cancelled());
+
+        let timeout = RunnableConfig::new().with_timeout(Duration::from_millis(5));
+        let result: Result<(), Error> = timeout.run(async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        }).await;
+        assert!(matches!(result.unwrap_err().downcast_ref::<RunnableInterrupt>(), Some(RunnableInterrupt::TimedOut(_))));
+    }
+
+    #[tokio::test]
+    async fn test_config_propagates_through_pipe_and_batch() {
+        let second_calls = Arc::new(AtomicUsize::new(0));
+        let calls = second_calls.clone();
+        let slow = RunnableLambda::new(|x: i64| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(x)
+        });
+        let counted = RunnableLambda::new(move |x: i64| {
+            let calls = calls.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(x)
+            }
+        });
+        let pipeline = pipe(slow, counted);
+
+        let config = RunnableConfig::new();
+        let canceller = config.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            canceller.cancel();
+        });
+        assert!(pipeline.invoke_with_config(1, Some(config.clone())).await.is_err());
+        assert_eq!(second_calls.load(Ordering::SeqCst), 0);
+
+        let results = pipeline.batch_with_config(vec![1, 2, 3], Some(config)).await;
+        assert!(results.iter().all(|r| matches!(
+            r.as_ref().unwrap_err().downcast_ref::<RunnableInterrupt>(),
+            Some(RunnableInterrupt::Cancelled)
+        )));
+
+        let results = pipeline.batch_with_config(vec![1, 2], Some(RunnableConfig::new().with_max_concurrency(1))).await;
+        assert_eq!(results.into_iter().map(|r| r.unwrap()).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(second_calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -1,5 +1,9 @@
 // Core module definition
 mod runnable;
+mod combinators;
+mod config;
 
 // Re-export module content
-pub use runnable::{Runnable, RunnableExt, RunnableSequence, pipe};
\ No newline at end of file
+pub use runnable::{Runnable, RunnableExt, RunnableSequence, pipe};
+pub use combinators::{RunnableLambda, RunnableParallel, RunnableBranch, RunnableRetry, RunnableWithFallbacks};
+pub use config::{RunnableConfig, RunnableInterrupt};
\ No newline at end of file
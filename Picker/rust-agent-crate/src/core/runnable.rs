@@ -1,23 +1,32 @@
 // Runnable interface definition - core concept of the framework
-use std::collections::HashMap;
 use std::pin::Pin;
 use futures::stream::Stream;
-use serde_json::Value;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
+use futures::StreamExt;
+
+use super::combinators::{RunnableRetry, RunnableWithFallbacks};
+use super::config::RunnableConfig;
 
 // Runnable interface definition
 pub trait Runnable<I: Send + 'static, O: Send + 'static>: Send + Sync {
     // Core async call method (main entry point)
     fn invoke(&self, input: I) -> Pin<Box<dyn std::future::Future<Output = Result<O, anyhow::Error>> + Send>>;
     
-    // Call variant with configuration - optional implementation
+    // Call variant with configuration
+    // Default implementation applies the timeout, cancellation token and chain callbacks around invoke,
+    // components that call other runnables should override it to pass `config.child()` down
     fn invoke_with_config(
         &self, 
         input: I, 
-        _config: Option<HashMap<String, Value>>
+        config: Option<RunnableConfig>
     ) -> Pin<Box<dyn std::future::Future<Output = Result<O, anyhow::Error>> + Send>> {
-        self.invoke(input)
+        let Some(config) = config else {
+            return self.invoke(input);
+        };
+        
+        let future = self.invoke(input);
+        Box::pin(async move { config.run(future).await })
     }
     
     // Async batch processing for multiple inputs
@@ -34,15 +43,27 @@ pub trait Runnable<I: Send + 'static, O: Send + 'static>: Send + Sync {
         })
     }
     
-    // Variant of batch processing - optional implementation
-    // Temporarily simplified implementation to avoid complex async composition issues
+    // Variant of batch processing with configuration
+    // Each input runs with the same config (sharing its cancellation token), at most
+    // `max_concurrency` at once when set; results keep the input order
     fn batch_with_config(
         &self, 
         inputs: Vec<I>, 
-        _config: Option<HashMap<String, Value>>
+        config: Option<RunnableConfig>
     ) -> Pin<Box<dyn std::future::Future<Output = Vec<Result<O, anyhow::Error>>> + Send>> {
-        // Directly call the batch method
-        self.batch(inputs)
+        let Some(config) = config else {
+            // No config, directly call the batch method
+            return self.batch(inputs);
+        };
+        
+        let self_clone = self.clone_to_owned();
+        let max_concurrency = config.max_concurrency.unwrap_or(inputs.len()).max(1);
+        Box::pin(async move {
+            futures::stream::iter(inputs.into_iter().map(|input| self_clone.invoke_with_config(input, Some(config.clone()))))
+                .buffered(max_concurrency)
+                .collect()
+                .await
+        })
     }
     
     // Stream processing interface - synchronous implementation
@@ -89,6 +110,18 @@ pub trait RunnableExt<I: Send + 'static, O: Send + 'static> {
     ) -> impl Runnable<I, NextO> + Send + Sync
     where
         Self: Sized + 'static + Send + Sync;
+    
+    // Retry failed invocations, max_attempts includes the first call
+    fn with_retry(self, max_attempts: usize) -> RunnableRetry<I, O>
+    where
+        Self: Sized + 'static + Send + Sync,
+        I: Clone;
+    
+    // Try fallbacks in order when this runnable fails
+    fn with_fallbacks(self, fallbacks: Vec<Box<dyn Runnable<I, O> + Send + Sync>>) -> RunnableWithFallbacks<I, O>
+    where
+        Self: Sized + 'static + Send + Sync,
+        I: Clone;
 }
 
 // Provide extension methods for Runnable
@@ -103,6 +136,22 @@ impl<T: Runnable<I, O> + ?Sized, I: Send + 'static, O: Send + 'static> RunnableE
         // Call the pipe function to combine two Runnables
         pipe(*self, next)
     }
+    
+    fn with_retry(self, max_attempts: usize) -> RunnableRetry<I, O>
+    where
+        Self: Sized + 'static + Send + Sync,
+        I: Clone,
+    {
+        RunnableRetry::new(self, max_attempts)
+    }
+    
+    fn with_fallbacks(self, fallbacks: Vec<Box<dyn Runnable<I, O> + Send + Sync>>) -> RunnableWithFallbacks<I, O>
+    where
+        Self: Sized + 'static + Send + Sync,
+        I: Clone,
+    {
+        RunnableWithFallbacks::new(self, fallbacks)
+    }
 }
 
 // Utility function: create a pipeline connecting two Runnables
@@ -128,6 +177,24 @@ pub fn pipe<I: Send + 'static, O1: Send + 'static, O2: Send + 'static>(
             })
         }
         
+        fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> Pin<Box<dyn std::future::Future<Output = Result<O2, anyhow::Error>> + Send>> {
+            let Some(config) = config else {
+                return self.invoke(input);
+            };
+            
+            let first_clone = self.first.clone_to_owned();
+            let second_clone = self.second.clone_to_owned();
+            
+            Box::pin(async move {
+                // Both stages share the pipeline's cancellation token, the timeout covers the whole pipeline
+                let child = config.child();
+                config.run(async move {
+                    let intermediate = first_clone.invoke_with_config(input, Some(child.clone())).await?;
+                    second_clone.invoke_with_config(intermediate, Some(child)).await
+                }).await
+            })
+        }
+        
         fn clone_to_owned(&self) -> Box<dyn Runnable<I, O2> + Send + Sync> {
             // Note: this implementation assumes components can be cloned, actual implementation may need adjustment
             Box::new(PipeImpl {
@@ -173,6 +240,10 @@ impl<I: 'static + Send, O: 'static + Send> Runnable<I, O> for RunnableSequence<I
         inner.invoke(input)
     }
     
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> Pin<Box<dyn std::future::Future<Output = Result<O, anyhow::Error>> + Send>> {
+        self.inner.invoke_with_config(input, config)
+    }
+    
     fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
         Box::new(RunnableSequence {
             inner: self.inner.clone_to_owned(),
@@ -183,10 +254,16 @@ impl<I: 'static + Send, O: 'static + Send> Runnable<I, O> for RunnableSequence<I
 // Example implementation of clone_to_owned method for Box<dyn Runnable>
 impl<I: Send + 'static, O: Send + 'static> Runnable<I, O> for Box<dyn Runnable<I, O> + Send + Sync> {
     fn invoke(&self, input: I) -> Pin<Box<dyn std::future::Future<Output = Result<O, anyhow::Error>> + Send>> {
-        let self_clone = self.clone_to_owned();
-        Box::pin(async move {
-            self_clone.invoke(input).await
-        })
+        // Dispatch to the boxed runnable, calling invoke on the Box itself would recurse
+        (**self).invoke(input)
+    }
+    
+    fn invoke_with_config(&self, input: I, config: Option<RunnableConfig>) -> Pin<Box<dyn std::future::Future<Output = Result<O, anyhow::Error>> + Send>> {
+        (**self).invoke_with_config(input, config)
+    }
+    
+    fn batch_with_config(&self, inputs: Vec<I>, config: Option<RunnableConfig>) -> Pin<Box<dyn std::future::Future<Output = Vec<Result<O, anyhow::Error>>> + Send>> {
+        (**self).batch_with_config(inputs, config)
     }
     
     fn clone_to_owned(&self) -> Box<dyn Runnable<I, O> + Send + Sync> {
@@ -0,0 +1,331 @@
+// Document loaders for plain text, Markdown, JSON and CSV files
+use anyhow::{Error, Result};
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+
+use crate::core::Runnable;
+use super::Document;
+
+/// Turns file content into documents, every document gets a `source` metadata entry
+pub trait DocumentLoader: Send + Sync {
+    /// Parse content that was already read
+    fn parse(&self, content: &str, source: &str) -> Result<Vec<Document>>;
+
+    /// Read a file and parse it
+    fn load<'a>(&'a self, path: &'a Path) -> Pin<Box<dyn Future<Output = Result<Vec<Document>>> + Send + 'a>> {
+        Box::pin(async move {
+            let content = tokio::fs::read_to_string(path).await
+                .map_err(|e| Error::msg(format!("Failed to read {}: {}", path.display(), e)))?;
+            self.parse(&content, &path.display().to_string())
+        })
+    }
+}
+
+// Loaders are runnables taking a file path, so they can be piped into splitters
+macro_rules! impl_loader_runnable {
+    ($($loader:ty),*) => {
+        $(
+            impl Runnable<String, Vec<Document>> for $loader {
+                fn invoke(&self, path: String) -> Pin<Box<dyn Future<Output = Result<Vec<Document>>> + Send>> {
+                    let loader = self.clone();
+                    Box::pin(async move { loader.load(Path::new(&path)).await })
+                }
+
+                fn clone_to_owned(&self) -> Box<dyn Runnable<String, Vec<Document>> + Send + Sync> {
+                    Box::new(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_loader_runnable!(TextLoader, MarkdownLoader, JsonLoader, CsvLoader);
+
+/// Loads the whole file as one document
+#[derive(Clone, Default)]
+pub struct TextLoader;
+
+impl DocumentLoader for TextLoader {
+    fn parse(&self, content: &str, source: &str) -> Result<Vec<Document>> {
+        Ok(vec![Document::new(content).with_metadata("source", source)])
+    }
+}
+
+/// Loads a Markdown file as one document with its first heading as `title`, or one document per heading section
+#[derive(Clone, Default)]
+pub struct MarkdownLoader {
+    split_by_headings: bool,
+}
+
+impl MarkdownLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// One document per section, with the section heading path (e.g. `Install > Linux`) as `heading`
+    pub fn with_split_by_headings(mut self, split_by_headings: bool) -> Self {
+        self.split_by_headings = split_by_headings;
+        self
+    }
+
+    // Heading level and text of a Markdown ATX heading line
+    fn heading(line: &str) -> Option<(usize, &str)> {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            Some((level, line[level..].trim()))
+        } else {
+            None
+        }
+    }
+}
+
+impl DocumentLoader for MarkdownLoader {
+    fn parse(&self, content: &str, source: &str) -> Result<Vec<Document>> {
+        let mut in_code_block = false;
+        let mut headings: Vec<(usize, String)> = Vec::new();
+        let mut sections: Vec<(Option<String>, String)> = vec![(None, String::new())];
+        let mut title = None;
+
+        for line in content.lines() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            if let Some((level, text)) = Self::heading(line).filter(|_| !in_code_block) {
+                title.get_or_insert_with(|| text.to_string());
+                if self.split_by_headings {
+                    headings.retain(|(l, _)| *l < level);
+                    headings.push((level, text.to_string()));
+                    let path = headings.iter().map(|(_, h)| h.as_str()).collect::<Vec<_>>().join(" > ");
+                    sections.push((Some(path), String::new()));
+                }
+            }
+            let (_, text) = sections.last_mut().expect("sections is never empty");
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        let mut documents = Vec::new();
+        for (heading, text) in sections {
+            if text.trim().is_empty() {
+                continue;
+            }
+            let mut document = Document::new(text.trim()).with_metadata("source", source);
+            if let Some(title) = &title {
+                document = document.with_metadata("title", title.as_str());
+            }
+            if let Some(heading) = heading {
+                document = document.with_metadata("heading", heading);
+            }
+            documents.push(document);
+        }
+        Ok(documents)
+    }
+}
+
+/// Loads a JSON file; an array (optionally selected with a JSON pointer) becomes one document per element
+#[derive(Clone, Default)]
+pub struct JsonLoader {
+    pointer: Option<String>,
+    content_key: Option<String>,
+}
+
+impl JsonLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSON pointer (RFC 6901) to the value to load, e.g. `/tasks`
+    pub fn with_pointer(mut self, pointer: &str) -> Self {
+        self.pointer = Some(pointer.to_string());
+        self
+    }
+
+    /// Use this field as the document content, the other scalar fields become metadata
+    pub fn with_content_key(mut self, key: &str) -> Self {
+        self.content_key = Some(key.to_string());
+        self
+    }
+
+    fn to_document(&self, value: &Value, source: &str, index: Option<usize>) -> Document {
+        let mut document = match (&self.content_key, value) {
+            (Some(key), Value::Object(object)) => {
+                let content = match object.get(key) {
+                    Some(Value::String(text)) => text.clone(),
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                let mut document = Document::new(content);
+                for (field, value) in object.iter().filter(|(field, value)| *field != key && !value.is_object() && !value.is_array()) {
+                    document.metadata.insert(field.clone(), value.clone());
+                }
+                document
+            }
+            (_, Value::String(text)) => Document::new(text.as_str()),
+            (_, value) => Document::new(serde_json::to_string_pretty(value).unwrap_or_default()),
+        };
+        document = document.with_metadata("source", source);
+        if let Some(index) = index {
+            document = document.with_metadata("index", index);
+        }
+        document
+    }
+}
+
+impl DocumentLoader for JsonLoader {
+    fn parse(&self, content: &str, source: &str) -> Result<Vec<Document>> {
+        let root: Value = serde_json::from_str(content)
+            .map_err(|e| Error::msg(format!("Invalid JSON in {}: {}", source, e)))?;
+        let value = match &self.pointer {
+            Some(pointer) => root.pointer(pointer)
+                .ok_or_else(|| Error::msg(format!("JSON pointer {} not found in {}", pointer, source)))?,
+            None => &root,
+        };
+        Ok(match value {
+            Value::Array(items) => items.iter().enumerate()
+                .map(|(index, item)| self.to_document(item, source, Some(index)))
+                .collect(),
+            value => vec![self.to_document(value, source, None)],
+        })
+    }
+}
+
+/// Loads a CSV file as one document per row, formatted as `column: value` lines
+#[derive(Clone)]
+pub struct CsvLoader {
+    delimiter: char,
+    metadata_columns: Vec<String>,
+}
+
+impl Default for CsvLoader {
+    fn default() -> Self {
+        Self { delimiter: ',', metadata_columns: Vec::new() }
+    }
+}
+
+impl CsvLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Columns stored as metadata instead of content
+    pub fn with_metadata_columns(mut self, columns: Vec<String>) -> Self {
+        self.metadata_columns = columns;
+        self
+    }
+
+    // Split CSV records, handling quoted fields with delimiters, escaped quotes and newlines
+    fn records(&self, content: &str) -> Result<Vec<Vec<String>>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut chars = content.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if in_quotes && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' if in_quotes => in_quotes = false,
+                '"' if field.is_empty() => in_quotes = true,
+                c if c == self.delimiter && !in_quotes => record.push(std::mem::take(&mut field)),
+                '\r' if !in_quotes => {}
+                '\n' if !in_quotes => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                c => field.push(c),
+            }
+        }
+        if in_quotes {
+            return Err(Error::msg("Unterminated quoted field"));
+        }
+        if !field.is_empty() || !record.is_empty() {
+            record.push(field);
+            records.push(record);
+        }
+        records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+        Ok(records)
+    }
+}
+
+impl DocumentLoader for CsvLoader {
+    fn parse(&self, content: &str, source: &str) -> Result<Vec<Document>> {
+        let mut records = self.records(content)
+            .map_err(|e| Error::msg(format!("Invalid CSV in {}: {}", source, e)))?
+            .into_iter();
+        let Some(header) = records.next() else {
+            return Ok(Vec::new());
+        };
+
+        let mut documents = Vec::new();
+        for (row, record) in records.enumerate() {
+            let mut lines = Vec::new();
+            let mut metadata = Map::new();
+            for (column, value) in header.iter().zip(record.iter()) {
+                if self.metadata_columns.contains(column) {
+                    metadata.insert(column.clone(), Value::String(value.clone()));
+                } else {
+                    lines.push(format!("{}: {}", column, value));
+                }
+            }
+            let mut document = Document::new(lines.join("\n"))
+                .with_metadata("source", source)
+                .with_metadata("row", row);
+            document.metadata.extend(metadata);
+            documents.push(document);
+        }
+        Ok(documents)
+    }
+}
+
+/// Load a file with the loader matching its extension (`md`, `json`, `csv`, anything else as text)
+pub async fn load_file(path: impl AsRef<Path>) -> Result<Vec<Document>> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_lowercase();
+    match extension.as_str() {
+        "md" | "markdown" => MarkdownLoader::new().load(path).await,
+        "json" => JsonLoader::new().load(path).await,
+        "csv" => CsvLoader::new().load(path).await,
+        _ => TextLoader.load(path).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_markdown_json_and_csv_loaders() {
+        let markdown = "# Picker\nIntro\n## Install\n```sh\n# not a heading\n```\n### Linux\napt install picker\n";
+        let sections = MarkdownLoader::new().with_split_by_headings(true).parse(markdown, "README.md").unwrap();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[1].metadata["heading"], "Picker > Install");
+        assert!(sections[1].page_content.contains("# not a heading"));
+        assert_eq!(sections[2].metadata["heading"], "Picker > Install > Linux");
+        assert_eq!(sections[2].metadata["title"], "Picker");
+
+        let tasks = r#"{"tasks": [{"name": "sync", "description": "Sync wallets", "enabled": true}, {"name": "backup", "description": "Backup"}]}"#;
+        let documents = JsonLoader::new().with_pointer("/tasks").with_content_key("description").parse(tasks, "tasks.json").unwrap();
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0].page_content, "Sync wallets");
+        assert_eq!(documents[0].metadata["enabled"], json!(true));
+        assert_eq!(documents[1].metadata["index"], json!(1));
+
+        let csv = "id,name,notes\n1,\"Widget, large\",\"says \"\"hi\"\"\nsecond line\"\n2,Gadget,\n";
+        let rows = CsvLoader::new().with_metadata_columns(vec!["id".to_string()]).parse(csv, "products.csv").unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].page_content, "name: Widget, large\nnotes: says \"hi\"\nsecond line");
+        assert_eq!(rows[0].metadata["id"], "1");
+        assert_eq!(rows[1].source(), Some("products.csv"));
+    }
+}
@@ -0,0 +1,34 @@
+// Document ingestion: loaders turn files into documents, splitters cut them into chunks for embeddings and retrieval
+pub mod loaders;
+pub mod splitters;
+
+pub use loaders::{DocumentLoader, TextLoader, MarkdownLoader, JsonLoader, CsvLoader, load_file};
+pub use splitters::{TextSplitter, RecursiveCharacterTextSplitter, LengthUnit};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A piece of text with metadata such as its source file, heading or row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Document {
+    pub page_content: String,
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+}
+
+impl Document {
+    pub fn new(page_content: impl Into<String>) -> Self {
+        Self { page_content: page_content.into(), metadata: HashMap::new() }
+    }
+
+    pub fn with_metadata(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// The `source` metadata set by the loaders
+    pub fn source(&self) -> Option<&str> {
+        self.metadata.get("source").and_then(|v| v.as_str())
+    }
+}
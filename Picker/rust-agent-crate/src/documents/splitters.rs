@@ -0,0 +1,212 @@
+// Text splitters cutting documents into overlapping chunks
+use anyhow::Result;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::core::Runnable;
+use crate::memory::utils::estimate_text_tokens;
+use super::Document;
+
+/// Unit used to measure chunk sizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LengthUnit {
+    #[default]
+    Characters,
+    /// Estimated model tokens, see `estimate_text_tokens`
+    Tokens,
+}
+
+impl LengthUnit {
+    fn measure(&self, text: &str) -> usize {
+        match self {
+            LengthUnit::Characters => text.chars().count(),
+            LengthUnit::Tokens => estimate_text_tokens(text),
+        }
+    }
+}
+
+/// Splits text into chunks
+pub trait TextSplitter: Send + Sync {
+    fn split_text(&self, text: &str) -> Vec<String>;
+
+    /// Split every document, chunks keep the document metadata plus a `chunk` index
+    fn split_documents(&self, documents: &[Document]) -> Vec<Document> {
+        documents.iter()
+            .flat_map(|document| {
+                self.split_text(&document.page_content).into_iter().enumerate().map(|(index, chunk)| {
+                    let mut chunk = Document { page_content: chunk, metadata: document.metadata.clone() };
+                    chunk.metadata.insert("chunk".to_string(), Value::from(index));
+                    chunk
+                })
+            })
+            .collect()
+    }
+}
+
+/// Splits on the first separator that occurs (paragraphs, then lines, then words, then characters),
+/// recursing into pieces that are still too long, and merges pieces into chunks with overlap
+#[derive(Debug, Clone)]
+pub struct RecursiveCharacterTextSplitter {
+    chunk_size: usize,
+    chunk_overlap: usize,
+    separators: Vec<String>,
+    length_unit: LengthUnit,
+}
+
+impl RecursiveCharacterTextSplitter {
+    pub fn new(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            chunk_overlap: chunk_overlap.min(chunk_size.saturating_sub(1)),
+            separators: ["\n\n", "\n", " ", ""].iter().map(|s| s.to_string()).collect(),
+            length_unit: LengthUnit::Characters,
+        }
+    }
+
+    /// Splitter preferring Markdown heading and code block boundaries
+    pub fn markdown(chunk_size: usize, chunk_overlap: usize) -> Self {
+        Self::new(chunk_size, chunk_overlap).with_separators(
+            ["\n## ", "\n### ", "\n#### ", "\n```", "\n\n", "\n", " ", ""].iter().map(|s| s.to_string()).collect(),
+        )
+    }
+
+    /// Separators tried in order, an empty separator splits into characters
+    pub fn with_separators(mut self, separators: Vec<String>) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    pub fn with_length_unit(mut self, length_unit: LengthUnit) -> Self {
+        self.length_unit = length_unit;
+        self
+    }
+
+    fn split_recursive(&self, text: &str, separators: &[String]) -> Vec<String> {
+        let position = separators.iter().position(|s| s.is_empty() || text.contains(s.as_str()));
+        let (pieces, rest): (Vec<&str>, &[String]) = match position {
+            Some(i) if separators[i].is_empty() => (split_chars(text), &[]),
+            // Keep the separator at the start of the next piece so joining pieces restores the text
+            Some(i) => (split_keep_separator(text, &separators[i]), &separators[i + 1..]),
+            None => (vec![text], &[]),
+        };
+
+        let mut chunks = Vec::new();
+        let mut small = Vec::new();
+        for piece in pieces {
+            if self.length_unit.measure(piece) <= self.chunk_size {
+                small.push(piece);
+                continue;
+            }
+            chunks.extend(self.merge(&small));
+            small.clear();
+            if rest.is_empty() {
+                chunks.push(piece.trim().to_string());
+            } else {
+                chunks.extend(self.split_recursive(piece, rest));
+            }
+        }
+        chunks.extend(self.merge(&small));
+        chunks
+    }
+
+    // Merge small pieces into chunks up to chunk_size, starting each chunk with up to chunk_overlap of the previous one
+    // Lengths are measured on the joined text since token estimates are not additive
+    fn merge(&self, pieces: &[&str]) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut current: Vec<&str> = Vec::new();
+        let measure = |pieces: &[&str]| self.length_unit.measure(&pieces.concat());
+        for piece in pieces {
+            let with_piece = |current: &[&str]| measure(&[current, &[*piece]].concat());
+            if !current.is_empty() && with_piece(&current) > self.chunk_size {
+                chunks.push(current.concat());
+                while !current.is_empty() && (measure(&current) > self.chunk_overlap || with_piece(&current) > self.chunk_size) {
+                    current.remove(0);
+                }
+            }
+            current.push(piece);
+        }
+        if !current.is_empty() {
+            chunks.push(current.concat());
+        }
+        chunks.into_iter()
+            .map(|chunk| chunk.trim().to_string())
+            .filter(|chunk| !chunk.is_empty())
+            .collect()
+    }
+}
+
+fn split_chars(text: &str) -> Vec<&str> {
+    text.char_indices()
+        .map(|(i, c)| &text[i..i + c.len_utf8()])
+        .collect()
+}
+
+fn split_keep_separator<'a>(text: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (index, _) in text.match_indices(separator) {
+        if index > start {
+            pieces.push(&text[start..index]);
+        }
+        start = index;
+    }
+    pieces.push(&text[start..]);
+    pieces
+}
+
+impl TextSplitter for RecursiveCharacterTextSplitter {
+    fn split_text(&self, text: &str) -> Vec<String> {
+        self.split_recursive(text, &self.separators)
+            .into_iter()
+            .filter(|chunk| !chunk.is_empty())
+            .collect()
+    }
+}
+
+impl Runnable<Vec<Document>, Vec<Document>> for RecursiveCharacterTextSplitter {
+    fn invoke(&self, documents: Vec<Document>) -> Pin<Box<dyn Future<Output = Result<Vec<Document>>> + Send>> {
+        let chunks = self.split_documents(&documents);
+        Box::pin(async move { Ok(chunks) })
+    }
+
+    fn clone_to_owned(&self) -> Box<dyn Runnable<Vec<Document>, Vec<Document>> + Send + Sync> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::pipe;
+    use crate::documents::MarkdownLoader;
+
+    #[test]
+    fn test_recursive_split_with_overlap() {
+        let text = "Picker runs tasks.\n\nEach task has a config file with a schedule and a command to run on the desktop.";
+        let splitter = RecursiveCharacterTextSplitter::new(40, 10);
+        let chunks = splitter.split_text(text);
+        assert_eq!(chunks[0], "Picker runs tasks.");
+        assert!(chunks.iter().all(|c| c.chars().count() <= 40));
+        // Consecutive chunks of the long paragraph share up to 10 characters
+        assert_eq!(chunks[1], "Each task has a config file with a");
+        assert!(chunks[2].starts_with("with a schedule"));
+
+        let tokens = RecursiveCharacterTextSplitter::new(5, 0).with_length_unit(LengthUnit::Tokens);
+        assert!(tokens.split_text(text).iter().all(|c| estimate_text_tokens(c) <= 5));
+    }
+
+    #[tokio::test]
+    async fn test_loader_piped_into_splitter() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("README.md");
+        tokio::fs::write(&path, "# Picker\n\nA desktop task runner.\n\n## Usage\n\nCreate a task and run it.").await.unwrap();
+
+        let ingest = pipe(MarkdownLoader::new(), RecursiveCharacterTextSplitter::markdown(40, 0));
+        let chunks = ingest.invoke(path.display().to_string()).await.unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].page_content, "## Usage\n\nCreate a task and run it.");
+        assert_eq!(chunks[1].metadata["chunk"], Value::from(1));
+        assert_eq!(chunks[1].metadata["title"], "Picker");
+    }
+}
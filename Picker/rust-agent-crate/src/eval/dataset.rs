@@ -0,0 +1,107 @@
+// Evaluation datasets: one JSON case per line
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One evaluation case
+///
+/// Every `expected_*` field is optional, scorers skip the cases that do not define what they check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalCase {
+    /// Stable identifier used to compare reports across runs
+    pub id: String,
+    /// User input sent to the agent
+    pub input: String,
+    /// Tool the agent is expected to call
+    #[serde(default)]
+    pub expected_tool: Option<String>,
+    /// Arguments the tool call must contain (extra arguments are allowed)
+    #[serde(default)]
+    pub expected_args: Option<Value>,
+    /// Reference answer, used by the LLM judge
+    #[serde(default)]
+    pub expected_answer: Option<String>,
+    /// Regex the answer must match
+    #[serde(default)]
+    pub answer_pattern: Option<String>,
+    /// Free-form metadata, e.g. tags used to slice reports
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+}
+
+impl EvalCase {
+    pub fn new(id: &str, input: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            input: input.to_string(),
+            expected_tool: None,
+            expected_args: None,
+            expected_answer: None,
+            answer_pattern: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_expected_tool(mut self, tool: &str, args: Option<Value>) -> Self {
+        self.expected_tool = Some(tool.to_string());
+        self.expected_args = args;
+        self
+    }
+
+    pub fn with_expected_answer(mut self, answer: &str) -> Self {
+        self.expected_answer = Some(answer.to_string());
+        self
+    }
+
+    pub fn with_answer_pattern(mut self, pattern: &str) -> Self {
+        self.answer_pattern = Some(pattern.to_string());
+        self
+    }
+}
+
+/// An ordered list of evaluation cases
+#[derive(Debug, Clone, Default)]
+pub struct EvalDataset {
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalDataset {
+    pub fn new(cases: Vec<EvalCase>) -> Self {
+        Self { cases }
+    }
+
+    /// Parse a JSONL dataset, blank lines and lines starting with `//` are ignored
+    pub fn from_jsonl_str(content: &str) -> Result<Self> {
+        let mut cases: Vec<EvalCase> = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let case: EvalCase = serde_json::from_str(line)
+                .map_err(|e| Error::msg(format!("Invalid eval case on line {}: {}", index + 1, e)))?;
+            if cases.iter().any(|c| c.id == case.id) {
+                return Err(Error::msg(format!("Duplicate eval case id '{}' on line {}", case.id, index + 1)));
+            }
+            cases.push(case);
+        }
+        Ok(Self { cases })
+    }
+
+    pub async fn from_jsonl_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await
+            .map_err(|e| Error::msg(format!("Failed to read eval dataset {}: {}", path.display(), e)))?;
+        Self::from_jsonl_str(&content)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+}
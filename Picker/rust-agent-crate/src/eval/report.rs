@@ -0,0 +1,156 @@
+// Evaluation reports: pass rates per scorer and regressions against a baseline report
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::scorer::Score;
+
+/// Outcome of one case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub id: String,
+    pub input: String,
+    /// True when the agent did not fail and every applicable scorer passed
+    pub passed: bool,
+    /// Short description of what the agent produced (tool calls and answer)
+    pub output: Option<String>,
+    /// Agent or scorer error, the case counts as failed
+    pub error: Option<String>,
+    pub scores: Vec<Score>,
+}
+
+/// Pass rate of one scorer over the cases it applied to
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScorerSummary {
+    pub cases: usize,
+    pub passed: usize,
+    pub pass_rate: f64,
+}
+
+/// A case that passed in the baseline and fails now
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Regression {
+    pub case_id: String,
+    /// Scorers that passed in the baseline and fail now (empty when the agent errored)
+    pub scorers: Vec<String>,
+}
+
+/// Report of one evaluation run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub created_at: String,
+    pub total: usize,
+    pub passed: usize,
+    pub pass_rate: f64,
+    pub scorers: BTreeMap<String, ScorerSummary>,
+    pub cases: Vec<CaseResult>,
+}
+
+fn rate(passed: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { passed as f64 / total as f64 }
+}
+
+impl EvalReport {
+    pub fn from_results(cases: Vec<CaseResult>) -> Self {
+        let mut scorers: BTreeMap<String, ScorerSummary> = BTreeMap::new();
+        for score in cases.iter().flat_map(|c| &c.scores) {
+            let summary = scorers.entry(score.scorer.clone())
+                .or_insert(ScorerSummary { cases: 0, passed: 0, pass_rate: 0.0 });
+            summary.cases += 1;
+            if score.passed {
+                summary.passed += 1;
+            }
+        }
+        for summary in scorers.values_mut() {
+            summary.pass_rate = rate(summary.passed, summary.cases);
+        }
+
+        let passed = cases.iter().filter(|c| c.passed).count();
+        Self {
+            created_at: chrono::Utc::now().to_rfc3339(),
+            total: cases.len(),
+            passed,
+            pass_rate: rate(passed, cases.len()),
+            scorers,
+            cases,
+        }
+    }
+
+    /// Cases that passed in `baseline` and fail in this report, matched by case id
+    pub fn regressions(&self, baseline: &EvalReport) -> Vec<Regression> {
+        self.cases.iter()
+            .filter(|case| !case.passed)
+            .filter_map(|case| {
+                let before = baseline.cases.iter().find(|b| b.id == case.id && b.passed)?;
+                let scorers = case.scores.iter()
+                    .filter(|score| !score.passed)
+                    .filter(|score| before.scores.iter().any(|b| b.scorer == score.scorer && b.passed))
+                    .map(|score| score.scorer.clone())
+                    .collect();
+                Some(Regression { case_id: case.id.clone(), scorers })
+            })
+            .collect()
+    }
+
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_string_pretty(self)?).await?;
+        Ok(())
+    }
+
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read_to_string(path).await
+            .map_err(|e| Error::msg(format!("Failed to read eval report {}: {}", path.display(), e)))?;
+        serde_json::from_str(&content).map_err(|e| Error::msg(format!("Invalid eval report {}: {}", path.display(), e)))
+    }
+
+    /// Render a Markdown summary, including regressions when a baseline is given
+    pub fn to_markdown(&self, baseline: Option<&EvalReport>) -> String {
+        let mut markdown = String::from("# Agent evaluation\n\n");
+        markdown.push_str(&format!("- Cases: {}\n- Passed: {} ({:.1}%)\n", self.total, self.passed, self.pass_rate * 100.0));
+        if let Some(baseline) = baseline {
+            markdown.push_str(&format!(
+                "- Baseline: {:.1}% ({:+.1} points)\n",
+                baseline.pass_rate * 100.0,
+                (self.pass_rate - baseline.pass_rate) * 100.0
+            ));
+        }
+
+        markdown.push_str("\n| Scorer | Cases | Passed | Pass rate |\n|---|---|---|---|\n");
+        for (name, summary) in &self.scorers {
+            markdown.push_str(&format!("| {} | {} | {} | {:.1}% |\n", name, summary.cases, summary.passed, summary.pass_rate * 100.0));
+        }
+
+        if let Some(baseline) = baseline {
+            let regressions = self.regressions(baseline);
+            markdown.push_str(&format!("\n## Regressions ({})\n\n", regressions.len()));
+            for regression in regressions {
+                markdown.push_str(&format!("- `{}`: {}\n", regression.case_id, if regression.scorers.is_empty() {
+                    "agent error".to_string()
+                } else {
+                    regression.scorers.join(", ")
+                }));
+            }
+        }
+
+        let failures: Vec<&CaseResult> = self.cases.iter().filter(|c| !c.passed).collect();
+        if !failures.is_empty() {
+            markdown.push_str("\n## Failures\n\n");
+            for case in failures {
+                let reasons: Vec<String> = case.error.iter().cloned()
+                    .chain(case.scores.iter().filter(|s| !s.passed).filter_map(|s| {
+                        s.reason.as_ref().map(|r| format!("{}: {}", s.scorer, r))
+                    }))
+                    .collect();
+                markdown.push_str(&format!("- `{}`: {}\n", case.id, reasons.join("; ")));
+            }
+        }
+
+        markdown
+    }
+}
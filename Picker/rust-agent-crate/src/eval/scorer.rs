@@ -0,0 +1,235 @@
+// Pluggable scorers comparing an agent output with an eval case
+use anyhow::{Error, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::agents::AgentAction;
+use crate::models::{ChatMessage, ChatMessageContent, ChatModel};
+use super::dataset::EvalCase;
+
+/// Result of one scorer on one case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Score {
+    pub scorer: String,
+    pub passed: bool,
+    /// Score between 0.0 and 1.0
+    pub value: f64,
+    pub reason: Option<String>,
+}
+
+impl Score {
+    pub fn pass(scorer: &str) -> Self {
+        Self { scorer: scorer.to_string(), passed: true, value: 1.0, reason: None }
+    }
+
+    pub fn fail(scorer: &str, reason: String) -> Self {
+        Self { scorer: scorer.to_string(), passed: false, value: 0.0, reason: Some(reason) }
+    }
+}
+
+/// What the agent did for one case: every tool call in order and the final answer
+#[derive(Debug, Clone, Default)]
+pub struct Trajectory {
+    /// Tool calls, named as resolved against the agent's tools
+    pub actions: Vec<AgentAction>,
+    pub answer: Option<String>,
+}
+
+/// Scorer interface, returns `None` when the case does not define what the scorer checks
+pub trait Scorer: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn score<'a>(&'a self, case: &'a EvalCase, trajectory: &'a Trajectory) -> Pin<Box<dyn Future<Output = Result<Option<Score>, Error>> + Send + 'a>>;
+}
+
+/// Checks that the agent called the expected tool at any step (case insensitive)
+pub struct ExactToolMatch;
+
+impl Scorer for ExactToolMatch {
+    fn name(&self) -> &str {
+        "exact_tool_match"
+    }
+
+    fn score<'a>(&'a self, case: &'a EvalCase, trajectory: &'a Trajectory) -> Pin<Box<dyn Future<Output = Result<Option<Score>, Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let Some(expected) = &case.expected_tool else {
+                return Ok(None);
+            };
+            if trajectory.actions.iter().any(|action| action.tool.eq_ignore_ascii_case(expected)) {
+                return Ok(Some(Score::pass(self.name())));
+            }
+            Ok(Some(if trajectory.actions.is_empty() {
+                Score::fail(self.name(), format!("expected tool '{}', agent answered directly", expected))
+            } else {
+                let called: Vec<&str> = trajectory.actions.iter().map(|action| action.tool.as_str()).collect();
+                Score::fail(self.name(), format!("expected tool '{}', got '{}'", expected, called.join("', '")))
+            }))
+        })
+    }
+}
+
+/// Checks that a tool call (to the expected tool, when the case names one) has every expected argument with the same value
+///
+/// Numbers are compared by value and strings holding numbers match numbers, since models often quote them.
+pub struct ArgumentMatch;
+
+fn values_match(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => expected.iter()
+            .all(|(key, value)| actual.get(key).is_some_and(|a| values_match(value, a))),
+        (Value::Number(e), Value::Number(a)) => e.as_f64() == a.as_f64(),
+        (Value::Number(e), Value::String(a)) | (Value::String(a), Value::Number(e)) => {
+            a.trim().parse::<f64>().ok() == e.as_f64()
+        }
+        _ => expected == actual,
+    }
+}
+
+impl Scorer for ArgumentMatch {
+    fn name(&self) -> &str {
+        "argument_match"
+    }
+
+    fn score<'a>(&'a self, case: &'a EvalCase, trajectory: &'a Trajectory) -> Pin<Box<dyn Future<Output = Result<Option<Score>, Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let Some(expected) = &case.expected_args else {
+                return Ok(None);
+            };
+            let calls: Vec<&AgentAction> = trajectory.actions.iter()
+                .filter(|action| case.expected_tool.as_ref().is_none_or(|tool| action.tool.eq_ignore_ascii_case(tool)))
+                .collect();
+            if calls.is_empty() {
+                return Ok(Some(Score::fail(self.name(), "no tool call to compare arguments with".to_string())));
+            }
+            let mut mismatches = Vec::new();
+            for action in calls {
+                match serde_json::from_str::<Value>(&action.tool_input) {
+                    Ok(actual) if values_match(expected, &actual) => return Ok(Some(Score::pass(self.name()))),
+                    Ok(actual) => mismatches.push(actual.to_string()),
+                    Err(e) => mismatches.push(format!("tool input that is not JSON ({})", e)),
+                }
+            }
+            Ok(Some(Score::fail(self.name(), format!("expected arguments {} to be included in {}", expected, mismatches.join(", ")))))
+        })
+    }
+}
+
+/// Checks the final answer against the case's `answer_pattern`, or a fixed pattern for every case
+pub struct RegexScorer {
+    pattern: Option<Regex>,
+}
+
+impl RegexScorer {
+    /// Use each case's `answer_pattern`
+    pub fn from_cases() -> Self {
+        Self { pattern: None }
+    }
+
+    /// Apply the same pattern to every case
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = Regex::new(pattern).map_err(|e| Error::msg(format!("Invalid scorer pattern: {}", e)))?;
+        Ok(Self { pattern: Some(pattern) })
+    }
+}
+
+impl Scorer for RegexScorer {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    fn score<'a>(&'a self, case: &'a EvalCase, trajectory: &'a Trajectory) -> Pin<Box<dyn Future<Output = Result<Option<Score>, Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let pattern = match (&self.pattern, &case.answer_pattern) {
+                (Some(pattern), _) => pattern.clone(),
+                (None, Some(pattern)) => Regex::new(pattern)
+                    .map_err(|e| Error::msg(format!("Invalid answer_pattern in case '{}': {}", case.id, e)))?,
+                (None, None) => return Ok(None),
+            };
+            let Some(answer) = &trajectory.answer else {
+                return Ok(Some(Score::fail(self.name(), "agent did not produce an answer".to_string())));
+            };
+            Ok(Some(if pattern.is_match(answer) {
+                Score::pass(self.name())
+            } else {
+                Score::fail(self.name(), format!("answer does not match /{}/", pattern.as_str()))
+            }))
+        })
+    }
+}
+
+/// Asks a chat model to grade the answer against the case's `expected_answer`
+///
+/// The judge must reply with `{"score": <0..1>, "reason": "..."}`, a case passes at or above the threshold.
+pub struct LlmJudgeScorer {
+    judge: Arc<dyn ChatModel>,
+    threshold: f64,
+}
+
+const JUDGE_PROMPT: &str = "You are grading an AI assistant's answer against a reference answer. \
+Reply only with JSON in the form {\"score\": <number between 0 and 1>, \"reason\": \"<short explanation>\"}. \
+Score 1 when the answer is correct and complete, 0 when it is wrong.";
+
+impl LlmJudgeScorer {
+    pub fn new(judge: Arc<dyn ChatModel>) -> Self {
+        Self { judge, threshold: 0.5 }
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    fn message(content: String) -> ChatMessageContent {
+        ChatMessageContent { content, name: None, additional_kwargs: HashMap::new(), parts: Vec::new() }
+    }
+}
+
+impl Scorer for LlmJudgeScorer {
+    fn name(&self) -> &str {
+        "llm_judge"
+    }
+
+    fn score<'a>(&'a self, case: &'a EvalCase, trajectory: &'a Trajectory) -> Pin<Box<dyn Future<Output = Result<Option<Score>, Error>> + Send + 'a>> {
+        Box::pin(async move {
+            let Some(expected) = &case.expected_answer else {
+                return Ok(None);
+            };
+            let Some(answer) = &trajectory.answer else {
+                return Ok(Some(Score::fail(self.name(), "agent did not produce an answer".to_string())));
+            };
+
+            let messages = vec![
+                ChatMessage::System(Self::message(JUDGE_PROMPT.to_string())),
+                ChatMessage::Human(Self::message(format!(
+                    "Question: {}\nReference answer: {}\nAssistant answer: {}",
+                    case.input, expected, answer
+                ))),
+            ];
+            let completion = self.judge.invoke(messages).await?;
+            let content = match completion.message {
+                ChatMessage::AIMessage(content) => content.content,
+                other => return Err(Error::msg(format!("Unexpected judge message: {:?}", other))),
+            };
+
+            let start = content.find('{').unwrap_or(0);
+            let end = content.rfind('}').map(|i| i + 1).unwrap_or(content.len());
+            let verdict: Value = serde_json::from_str(&content[start..end])
+                .map_err(|e| Error::msg(format!("Judge reply is not valid JSON ({}): {}", e, content)))?;
+            let value = verdict.get("score").and_then(|v| v.as_f64())
+                .ok_or_else(|| Error::msg(format!("Judge reply has no numeric score: {}", content)))?
+                .clamp(0.0, 1.0);
+
+            Ok(Some(Score {
+                scorer: self.name().to_string(),
+                passed: value >= self.threshold,
+                value,
+                reason: verdict.get("reason").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            }))
+        })
+    }
+}
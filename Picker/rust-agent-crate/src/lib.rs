@@ -7,15 +7,34 @@ pub mod memory;
 mod agents;
 mod callbacks;
 mod mcp;
+mod telemetry;
+pub mod eval;
+pub mod guardrails;
+pub mod documents;
+pub mod spec;
+#[cfg(test)]
+mod test_utils;
 
 // Re-export main components for external use
-pub use core::{Runnable, RunnableExt, RunnableSequence};
-pub use models::{ChatModel, ChatMessage as ModelChatMessage, ChatMessageContent, ChatCompletion, TokenUsage, OpenAIChatModel};
-pub use tools::{Tool, Toolkit, ExampleTool, ExampleToolkit, find_matching_tool_index, parse_model_output};
-pub use memory::{BaseMemory, SimpleMemory, MessageHistoryMemory, SummaryMemory, CompositeMemory, CompositeMemoryConfig, ChatMessageRecord, ChatMessage};
-pub use agents::{Agent, McpAgent, AgentAction, AgentFinish, AgentOutput, AgentRunner, SimpleAgent, SimpleAgentRunner};
+pub use core::{Runnable, RunnableExt, RunnableSequence, RunnableLambda, RunnableParallel, RunnableBranch, RunnableRetry, RunnableWithFallbacks, RunnableConfig, RunnableInterrupt};
+pub use models::{ChatModel, ChatMessage as ModelChatMessage, ChatMessageContent, ContentPart, ChatCompletion, TokenUsage, OpenAIChatModel, FakeChatModel, FakeResponse, RecordReplayChatModel, ReplayMode, ReplayEntry, ReplayMessage, CachedChatModel, ResponseCache, InMemoryResponseCache, CachedResponse, CacheHit, CacheHitKind, CacheStats, Embeddings, EmbeddingsFuture, OpenAIEmbeddings, cosine_similarity};
+#[cfg(feature = "sqlite-cache")]
+pub use models::SqliteResponseCache;
+pub use tools::{Tool, Toolkit, ExampleTool, ExampleToolkit, ToolContext, ToolProgress, ProgressReporter, find_matching_tool_index, parse_model_output};
+#[cfg(feature = "builtin-tools")]
+pub use tools::{HttpRequestTool, FileSystemTool, JsonQueryTool, ShellTool};
+pub use memory::{BaseMemory, SimpleMemory, MessageHistoryMemory, SummaryMemory, CompositeMemory, CompositeMemoryConfig, ChatMessageRecord, ChatMessage, MemoryExportBundle, MemoryExportFormat, Redactor, RegexRedactor, MemoryCipher, MemoryCipherError};
+pub use agents::{Agent, McpAgent, AgentAction, AgentFinish, AgentOutput, AgentRunner, SimpleAgent, SimpleAgentRunner, AgentTool, SupervisorAgent, SubAgentMemory, PlanAndExecuteAgent, Plan, PlanStep, PlanStepStatus};
 pub use callbacks::CallbackHandler;
-pub use mcp::{McpClient, SimpleMcpClient, McpTool, McpToolAdapter, McpServer, SimpleMcpServer};
+pub use mcp::{McpClient, SimpleMcpClient, McpTool, McpConnectionState, ReconnectPolicy, McpToolAdapter, McpServer, SimpleMcpServer, McpClientGroup};
+pub use documents::{Document, DocumentLoader, TextLoader, MarkdownLoader, JsonLoader, CsvLoader, TextSplitter, RecursiveCharacterTextSplitter, LengthUnit, load_file};
+pub use guardrails::{Guardrails, GuardrailConfig, Guardrail, GuardrailDecision, GuardrailViolation, ToolPolicy};
+pub use spec::{AgentSpec, SpecAgent, SpecFormat};
+pub use telemetry::TRACER_NAME;
+#[cfg(feature = "otel")]
+pub use telemetry::init_otlp_tracing;
+use telemetry::traced;
+use serde_json::Value;
 use anyhow::Error;
 use std::collections::HashMap;
 
@@ -24,11 +43,40 @@ pub use core::pipe;
 // Export anyhow error handling library to ensure consistent error handling for third-party users
 pub use anyhow;
 
-// Main function to run Agent
-pub async fn run_agent(agent: &McpAgent, input: String) -> Result<String, Error> {
+// Main function to run Agent (McpAgent, SupervisorAgent or any agent that is also a Runnable)
+pub async fn run_agent<A>(agent: &A, input: String) -> Result<String, Error>
+where
+    A: Agent + Runnable<HashMap<String, String>, AgentOutput> + ?Sized,
+{
+    run_agent_with_config(agent, input, RunnableConfig::default()).await
+}
+
+// Run Agent under a config, cancelling the config stops the run including any pending tool call
+pub async fn run_agent_with_config<A>(agent: &A, input: String, config: RunnableConfig) -> Result<String, Error>
+where
+    A: Agent + Runnable<HashMap<String, String>, AgentOutput> + ?Sized,
+{
+    let attributes = config.run_name.iter().map(|name| ("agent.run_name", Value::String(name.clone()))).collect();
+    traced("agent.run", attributes, config.run(run_agent_steps(agent, input, config.child(), None)), |_| Vec::new()).await
+}
+
+// Run Agent behind guardrails: the input is checked, tool calls must pass the argument policies and tool output is sanitized and quoted
+pub async fn run_agent_with_guardrails<A>(agent: &A, input: String, config: RunnableConfig, guardrails: &Guardrails) -> Result<String, Error>
+where
+    A: Agent + Runnable<HashMap<String, String>, AgentOutput> + ?Sized,
+{
+    let input = guardrails.check_input(&input)?;
+    let attributes = config.run_name.iter().map(|name| ("agent.run_name", Value::String(name.clone()))).collect();
+    traced("agent.run", attributes, config.run(run_agent_steps(agent, input, config.child(), Some(guardrails))), |_| Vec::new()).await
+}
+
+async fn run_agent_steps<A>(agent: &A, input: String, config: RunnableConfig, guardrails: Option<&Guardrails>) -> Result<String, Error>
+where
+    A: Agent + Runnable<HashMap<String, String>, AgentOutput> + ?Sized,
+{
     let mut inputs = HashMap::new();
     inputs.insert("input".to_string(), input);
-    let output = agent.invoke(inputs).await?;
+    let output = agent.invoke_with_config(inputs, Some(config.clone())).await?;
     
     match output {
         AgentOutput::Action(action) => {
@@ -36,23 +84,64 @@ pub async fn run_agent(agent: &McpAgent, input: String) -> Result<String, Error>
             let tools = agent.tools();
             match find_matching_tool_index(&tools, &action.tool) {
                 Some(matched_name) => {
+                    // Guardrails see the resolved tool name, so fuzzy or namespaced names cannot slip past a policy,
+                    // they may veto the call or rewrite its arguments
+                    let action = match guardrails {
+                        Some(guardrails) => guardrails.check_action(&AgentAction { tool: matched_name.clone(), ..action })?,
+                        None => action,
+                    };
                     // After finding a matching tool name, search for the specific tool again
                     if let Some(tool) = tools.iter().find(|t| t.name() == matched_name) {
                         // Invoke the tool
-                        let tool_result = tool.invoke(&action.tool_input).await?;
+                        for callback in &config.callbacks {
+                            callback.on_tool_start(&matched_name, &action.tool_input);
+                        }
+                        // Progress goes to the callbacks, cancelling the config also cancels the tool call
+                        let callbacks = config.callbacks.clone();
+                        let tool_name = matched_name.clone();
+                        let context = ToolContext::new()
+                            .with_cancellation_token(config.cancellation_token.clone())
+                            .with_config(config.child())
+                            .with_progress(ProgressReporter::new(move |progress| {
+                                for callback in &callbacks {
+                                    callback.on_tool_progress(&tool_name, &progress);
+                                }
+                            }));
+                        let tool_result = match traced(
+                            "tool.call",
+                            vec![("tool.name", Value::String(matched_name.clone()))],
+                            config.run(tool.invoke_with_context(&action.tool_input, context)),
+                            |output: &String| vec![("tool.output_chars", Value::from(output.len()))],
+                        ).await {
+                            Ok(result) => result,
+                            Err(e) => {
+                                for callback in &config.callbacks {
+                                    callback.on_tool_error(&matched_name, &e.to_string());
+                                }
+                                return Err(e);
+                            }
+                        };
+                        for callback in &config.callbacks {
+                            callback.on_tool_end(&matched_name, &tool_result);
+                        }
                         
                         // Feed the tool execution result back to Agent for further processing
                         let mut new_inputs = HashMap::new();
-                        new_inputs.insert("input".to_string(), format!("[CUSTOMIZE_TOOL_RESULT] {{\"tool\": \"{}\", \"result\": {}}}", matched_name, tool_result));
-                        let new_output = agent.invoke(new_inputs).await?;
+                        let fed_back = match guardrails {
+                            Some(guardrails) => guardrails.sanitize_tool_output(&matched_name, &tool_result),
+                            None => format!("{{\"tool\": \"{}\", \"result\": {}}}", matched_name, tool_result),
+                        };
+                        new_inputs.insert("input".to_string(), format!("[CUSTOMIZE_TOOL_RESULT] {}", fed_back));
+                        let new_output = agent.invoke_with_config(new_inputs, Some(config.clone())).await?;
                         
                         match new_output {
                             AgentOutput::Finish(finish) => {
                                 Ok(finish.return_values.get("answer").map(|s| s.clone()).unwrap_or_else(|| "".to_string()))
                             },
                             _ => {
-                                // If still Action, simply return the tool result for now
-                                Ok(format!("Tool {} executed successfully, result: {}", matched_name, tool_result))
+                                // If still Action, simply return the tool result for now, sanitized when guardrails are set
+                                let result = if guardrails.is_some() { &fed_back } else { &tool_result };
+                                Ok(format!("Tool {} executed successfully, result: {}", matched_name, result))
                             }
                         }
                     } else {
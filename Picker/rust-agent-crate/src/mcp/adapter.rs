@@ -3,7 +3,7 @@ use anyhow::Error;
 use std::collections::HashMap;
 use std::sync::Arc;
 use serde_json::Value;
-use crate::tools::Tool;
+use crate::tools::{Tool, ToolContext};
 use super::client::{McpClient, McpTool};
 use log::info;
 // MCP tool adapter
@@ -49,6 +49,11 @@ impl Tool for McpToolAdapter {
     }
     
     fn invoke(&self, input: &str) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, Error>> + Send + '_>> {
+        self.invoke_with_context(input, ToolContext::new())
+    }
+    
+    // Progress and cancellation are forwarded to the MCP server
+    fn invoke_with_context(&self, input: &str, context: ToolContext) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, Error>> + Send + '_>> {
         let client = self.mcp_client.clone();
         let tool_name = self.mcp_tool.name.clone();
         let input_str = input.to_string();
@@ -66,8 +71,7 @@ impl Tool for McpToolAdapter {
             };
             
             // Call the tool on the MCP server
-            let result_future = client.call_tool(&tool_name, parameters);
-            let result = result_future.await?;
+            let result = client.call_tool_with_context(&tool_name, parameters, context).await?;
             
             // Convert result to string
             Ok(serde_json::to_string_pretty(&result)?)
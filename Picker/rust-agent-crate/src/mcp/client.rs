@@ -5,11 +5,17 @@ use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use crate::core::RunnableInterrupt;
 use crate::mcp::JSONRPCRequest;
 use crate::mcp::JSONRPCResponse;
+use crate::mcp::SESSION_ID_HEADER;
+use crate::tools::{ToolContext, ToolProgress};
 
 // MCP tool structure
 #[derive(Debug,Clone)]
@@ -19,7 +25,81 @@ pub struct McpTool {
     // Other tool-related fields
 }
 
+// Connection state of an MCP client, broadcast to subscribers on every change
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McpConnectionState {
+    Disconnected,
+    Connected,
+    // Requests are failing, retrying with exponential backoff; `attempt` counts failures starting at 1
+    Reconnecting { attempt: u32 },
+}
+
+/// Health check and reconnect timing used by `SimpleMcpClient::spawn_health_check`
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Delay between health checks while connected
+    pub health_check_interval: Duration,
+    /// First retry delay after a failed check, doubled on each failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            health_check_interval: Duration::from_secs(15),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+// Remote tools/list result with the time it was fetched
+struct CachedTools {
+    tools: Vec<McpTool>,
+    fetched_at: Instant,
+}
+
+// Every client shares one connection pool
+fn shared_http_client() -> reqwest::Client {
+    static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    HTTP_CLIENT.get_or_init(reqwest::Client::new).clone()
+}
+
+// Result of a JSON-RPC response, error responses become errors
+fn rpc_result(response: JSONRPCResponse) -> Result<Value, Error> {
+    if let Some(error) = response.error {
+        return Err(match error.data {
+            Some(data) => Error::msg(format!("JSON-RPC error: {} (code: {}, data: {})", error.message, error.code, data)),
+            None => Error::msg(format!("JSON-RPC error: {} (code: {})", error.message, error.code)),
+        });
+    }
+    Ok(response.result.unwrap_or(Value::Null))
+}
+
+// Sends notifications/cancelled for a tools/call that was abandoned before its result arrived
+struct CancelOnDrop {
+    client: SimpleMcpClient,
+    request_id: Option<Value>,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        let (Some(request_id), Ok(runtime)) = (self.request_id.take(), tokio::runtime::Handle::try_current()) else {
+            return;
+        };
+        let client = Clone::clone(&self.client);
+        runtime.spawn(async move {
+            info!("Cancelling MCP tool call {}", request_id);
+            if let Err(e) = client.notify("notifications/cancelled", json!({ "requestId": request_id, "reason": "Cancelled by client" })).await {
+                warn!("{}", e);
+            }
+        });
+    }
+}
+
 // Simple MCP client implementation, modify SimpleMcpClient structure, add tool handler field
+// Clones share the connection state, tools cache and HTTP connection pool
 #[derive(Clone)]
 pub struct SimpleMcpClient {
     pub url: String,
@@ -28,6 +108,15 @@ pub struct SimpleMcpClient {
     pub tool_handlers: HashMap<String, Arc<dyn Fn(HashMap<String, Value>) -> Pin<Box<dyn Future<Output = Result<Value, Error>> + Send>> + Send + Sync>>,
     // Connection status flag, indicates whether successfully connected to MCP server
     pub is_mcp_server_connected: Arc<Mutex<bool>>,
+    http_client: reqwest::Client,
+    connection_state: Arc<Mutex<McpConnectionState>>,
+    connection_events: broadcast::Sender<McpConnectionState>,
+    tools_cache: Arc<Mutex<Option<CachedTools>>>,
+    tools_cache_ttl: Duration,
+    // Cancelled by disconnect to stop health checks
+    shutdown: Arc<Mutex<CancellationToken>>,
+    // Sent with every request, the server scopes request ids to it
+    session_id: String,
 }
 
 // Implement methods for SimpleMcpClient structure
@@ -38,9 +127,22 @@ impl SimpleMcpClient {
             available_tools: Vec::new(),
             tool_handlers: HashMap::new(),
             is_mcp_server_connected: Arc::new(Mutex::new(false)), // Initial state is disconnected
+            http_client: shared_http_client(),
+            connection_state: Arc::new(Mutex::new(McpConnectionState::Disconnected)),
+            connection_events: broadcast::channel(16).0,
+            tools_cache: Arc::new(Mutex::new(None)),
+            tools_cache_ttl: Duration::from_secs(60),
+            shutdown: Arc::new(Mutex::new(CancellationToken::new())),
+            session_id: Uuid::new_v4().to_string(),
         }
     }
     
+    /// How long a remote tools/list result is reused, zero disables caching
+    pub fn with_tools_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.tools_cache_ttl = ttl;
+        self
+    }
+    
     // Add custom tool method
     pub fn add_tool(&mut self, tool: McpTool) {
         self.available_tools.push(tool);
@@ -70,233 +172,336 @@ impl SimpleMcpClient {
     
     // Set server connection status
     pub fn set_server_connected(&self, connected: bool) {
-        if let Ok(mut conn_status) = self.is_mcp_server_connected.lock() {
-            *conn_status = connected;
-            if connected {
-                info!("MCP server connection status set to connected");
-            } else {
-                info!("MCP server connection status set to disconnected");
-            }
-        }
+        self.set_connection_state(if connected { McpConnectionState::Connected } else { McpConnectionState::Disconnected });
     }
     
     // Get server connection status
     pub fn is_server_connected(&self) -> bool {
         *self.is_mcp_server_connected.lock().unwrap_or_else(|e| e.into_inner())
     }
+    
+    /// Current connection state
+    pub fn connection_state(&self) -> McpConnectionState {
+        *self.connection_state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
+    /// Receive every connection state change, e.g. to refresh a tool list in the UI
+    pub fn subscribe_connection_events(&self) -> broadcast::Receiver<McpConnectionState> {
+        self.connection_events.subscribe()
+    }
+    
+    /// Drop the cached tools/list result, the next get_tools asks the server again
+    pub fn invalidate_tools_cache(&self) {
+        *self.tools_cache.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+    
+    // Update the state, a change invalidates the tools cache (the server may have restarted with other tools) and notifies subscribers
+    fn set_connection_state(&self, state: McpConnectionState) {
+        let previous = std::mem::replace(&mut *self.connection_state.lock().unwrap_or_else(|e| e.into_inner()), state);
+        *self.is_mcp_server_connected.lock().unwrap_or_else(|e| e.into_inner()) = state == McpConnectionState::Connected;
+        if previous == state {
+            return;
+        }
+        info!("MCP server {} connection state changed: {:?} -> {:?}", self.url, previous, state);
+        self.invalidate_tools_cache();
+        // No subscribers is fine
+        let _ = self.connection_events.send(state);
+    }
+    
+    /// Ping the server periodically in a background task, marking the client connected or reconnecting
+    /// with exponential backoff; stops when `disconnect` is called
+    pub fn spawn_health_check(&self, policy: ReconnectPolicy) -> tokio::task::JoinHandle<()> {
+        let client = Clone::clone(self);
+        let shutdown = self.shutdown.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        tokio::spawn(async move {
+            let mut attempt = 0;
+            let mut backoff = policy.initial_backoff;
+            loop {
+                // A ping in flight when `disconnect` is called must not mark the client connected again
+                let result = tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    result = client.ping() => result,
+                };
+                if shutdown.is_cancelled() {
+                    break;
+                }
+                let delay = match result {
+                    Ok(()) => {
+                        attempt = 0;
+                        backoff = policy.initial_backoff;
+                        client.set_connection_state(McpConnectionState::Connected);
+                        policy.health_check_interval
+                    }
+                    Err(e) => {
+                        attempt += 1;
+                        warn!("MCP health check failed (attempt {}): {}", attempt, e);
+                        client.set_connection_state(McpConnectionState::Reconnecting { attempt });
+                        let delay = backoff;
+                        backoff = (backoff * 2).min(policy.max_backoff);
+                        delay
+                    }
+                };
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+        })
+    }
+    
+    // Send a JSON-RPC request to the server, transport failures mark the client as reconnecting
+    // and the next request reaching the server marks it connected again
+    async fn rpc(&self, method: &str, params: Option<Value>) -> Result<Value, Error> {
+        if self.url.is_empty() {
+            return Err(Error::msg("No URL set for MCP client"));
+        }
+        let request = JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(Value::String(Uuid::new_v4().to_string())),
+            method: method.to_string(),
+            params,
+        };
+        let response = match self.http_client.post(format!("{}/rpc", self.url))
+            .header(SESSION_ID_HEADER, &self.session_id)
+            .json(&request)
+            .send().await {
+            Ok(response) => response,
+            Err(e) => {
+                if self.is_server_connected() {
+                    self.set_connection_state(McpConnectionState::Reconnecting { attempt: 1 });
+                }
+                return Err(Error::msg(format!("Failed to send request to MCP server: {}", e)));
+            }
+        };
+        if matches!(self.connection_state(), McpConnectionState::Reconnecting { .. }) {
+            self.set_connection_state(McpConnectionState::Connected);
+        }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_else(|_| "Unable to read response body".to_string());
+            return Err(Error::msg(format!("MCP server returned HTTP error {}: {}", status.as_u16(), body)));
+        }
+        let response_text = response.text().await
+            .map_err(|e| Error::msg(format!("Failed to read response body: {}", e)))?;
+        let rpc_response: JSONRPCResponse = serde_json::from_str(&response_text)
+            .map_err(|e| Error::msg(format!("Failed to parse response as JSON: {}. Response content: {}", e, response_text)))?;
+        rpc_result(rpc_response)
+    }
+    
+    // Send a JSON-RPC notification, the server sends no result back
+    async fn notify(&self, method: &str, params: Value) -> Result<(), Error> {
+        self.http_client.post(format!("{}/rpc", self.url))
+            .header(SESSION_ID_HEADER, &self.session_id)
+            .json(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+            .send().await
+            .map_err(|e| Error::msg(format!("Failed to send {} to MCP server: {}", method, e)))?;
+        Ok(())
+    }
+    
+    // tools/call with a progress token, progress notifications arrive as server-sent events before the result;
+    // a server answering with plain JSON is handled too
+    async fn call_tool_streaming(&self, tool_name: String, params: HashMap<String, Value>, context: ToolContext) -> Result<Value, Error> {
+        let request_id = Value::String(Uuid::new_v4().to_string());
+        let request = JSONRPCRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(request_id.clone()),
+            method: "tools/call".to_string(),
+            params: Some(json!({
+                "name": tool_name,
+                "arguments": params,
+                "_meta": { "progressToken": request_id }
+            })),
+        };
+        // Tells the server to stop the tool if this call is cancelled or dropped before the result arrives
+        let mut cancel_guard = CancelOnDrop { client: Clone::clone(self), request_id: Some(request_id) };
+        
+        let call = async {
+            let mut response = self.http_client.post(format!("{}/rpc", self.url))
+                .header(reqwest::header::ACCEPT, "application/json, text/event-stream")
+                .header(SESSION_ID_HEADER, &self.session_id)
+                .json(&request)
+                .send().await
+                .map_err(|e| Error::msg(format!("Failed to send request to MCP server: {}", e)))?;
+            if !response.status().is_success() {
+                return Err(Error::msg(format!("MCP server returned HTTP error {}", response.status().as_u16())));
+            }
+            let is_stream = response.headers().get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("text/event-stream"));
+            if !is_stream {
+                return rpc_result(response.json().await?);
+            }
+            
+            let mut buffer = Vec::new();
+            while let Some(chunk) = response.chunk().await? {
+                buffer.extend_from_slice(&chunk);
+                while let Some(end) = buffer.windows(2).position(|window| window == b"\n\n") {
+                    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                    let data = String::from_utf8_lossy(&event).lines()
+                        .filter_map(|line| line.strip_prefix("data:"))
+                        .map(str::trim_start)
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if data.is_empty() {
+                        continue;
+                    }
+                    let message: Value = serde_json::from_str(&data)
+                        .map_err(|e| Error::msg(format!("Invalid event from MCP server: {}", e)))?;
+                    if message["method"] == "notifications/progress" {
+                        match (&context.progress, serde_json::from_value::<ToolProgress>(message["params"].clone())) {
+                            (Some(reporter), Ok(progress)) => reporter.report(progress),
+                            (_, Err(e)) => warn!("Ignoring invalid progress notification: {}", e),
+                            _ => {}
+                        }
+                    } else if message.get("id").is_some() {
+                        return rpc_result(serde_json::from_value(message)?);
+                    }
+                }
+            }
+            Err(Error::msg("MCP server closed the stream without a result"))
+        };
+        
+        let result = tokio::select! {
+            _ = context.cancellation_token.cancelled() => return Err(Error::new(RunnableInterrupt::Cancelled)),
+            result = call => result,
+        };
+        cancel_guard.request_id = None;
+        result
+    }
+    
+    // Remote tools, from the cache while it is fresh
+    async fn remote_tools(&self) -> Result<Vec<McpTool>, Error> {
+        if let Some(cached) = self.tools_cache.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            if cached.fetched_at.elapsed() < self.tools_cache_ttl {
+                debug!("Using cached MCP tool list");
+                return Ok(cached.tools.clone());
+            }
+        }
+        
+        let result = self.rpc("tools/list", None).await?;
+        debug!("Server response result: {:?}", result);
+        let tools_value = result.get("tools")
+            .ok_or_else(|| Error::msg(format!("No 'tools' field in server response result: {:?}", result)))?;
+        let tools_array = tools_value.as_array()
+            .ok_or_else(|| Error::msg(format!("Failed to parse tools array from server response: {:?}", tools_value)))?;
+        let mut tools = Vec::new();
+        for tool_value in tools_array {
+            match (tool_value["name"].as_str(), tool_value["description"].as_str()) {
+                (Some(name), Some(description)) => tools.push(McpTool {
+                    name: name.to_string(),
+                    description: description.to_string(),
+                }),
+                _ => warn!("Failed to parse tool from server response: {:?}", tool_value),
+            }
+        }
+        
+        *self.tools_cache.lock().unwrap_or_else(|e| e.into_inner()) = Some(CachedTools { tools: tools.clone(), fetched_at: Instant::now() });
+        Ok(tools)
+    }
 }
 
 // Implement McpClient trait for SimpleMcpClient
 impl McpClient for SimpleMcpClient {
-    // Connect to MCP server
+    // Connect to MCP server, the client is marked connected when the server answers a ping
     fn connect(&mut self, url: &str) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + Send + '_>> {
         let url = url.to_string();
         Box::pin(async move {
+            if url != self.url {
+                self.invalidate_tools_cache();
+            }
             self.url = url;
-            Ok(())
+            {
+                let mut shutdown = self.shutdown.lock().unwrap_or_else(|e| e.into_inner());
+                if shutdown.is_cancelled() {
+                    *shutdown = CancellationToken::new();
+                }
+            }
+            match self.ping().await {
+                Ok(()) => {
+                    self.set_connection_state(McpConnectionState::Connected);
+                    Ok(())
+                }
+                Err(e) => {
+                    self.set_connection_state(McpConnectionState::Disconnected);
+                    Err(e)
+                }
+            }
         })
     }
     
-    // Get available tool list
+    // Get available tool list, local tools first; only local tools when no server was ever connected,
+    // a connected or reconnecting server that cannot list its tools is an error
     fn get_tools(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<McpTool>, Error>> + Send + '_>> {
-        let url = self.url.clone();
-        let local_tools = self.available_tools.clone();
-        let is_connected = self.is_mcp_server_connected.clone();
         Box::pin(async move {
-            // First check connection status flag, return local tool list directly if not connected
-            let connected = if let Ok(conn) = is_connected.lock() {
-                *conn
-            } else {
-                false
-            };
-
-            if !connected {
-                warn!("MCP server is not connected, returning local tools only");
-                return Ok(local_tools);
-            }
-            
-            if !url.is_empty() {
-                // Construct JSON-RPC request
-                let request = JSONRPCRequest {
-                    jsonrpc: "2.0".to_string(),
-                    id: Some(Value::String(Uuid::new_v4().to_string())),
-                    method: "tools/list".to_string(),
-                    params: None,
-                };
-
-                // Send HTTP POST request
-                let client = reqwest::Client::new();
-                let response = client
-                    .post(&format!("{}/rpc", url))
-                    .json(&request)
-                    .send()
-                    .await;
-
-                // Check if request was sent successfully
-                match response {
-                    Ok(response) => {
-                        // Check HTTP status code
-                        if !response.status().is_success() {
-                            let status = response.status();
-                            let body = response.text().await.unwrap_or_else(|_| "Unable to read response body".to_string());
-                            warn!("MCP server returned HTTP error {}: {}. Response body: {}", status.as_u16(), status.canonical_reason().unwrap_or("Unknown error"), body);
-                            // Return local tool list when server returns error
-                            return Ok(local_tools);
-                        }
-
-                        // Get response text for debugging
-                        let response_text = response.text().await
-                            .map_err(|e| Error::msg(format!("Failed to read response body: {}", e)))?;
-                        
-                        // Check if response is empty
-                        if response_text.trim().is_empty() {
-                            warn!("MCP server returned empty response");
-                            // Return local tool list when server returns empty response
-                            return Ok(local_tools);
-                        }
-
-                        // Try to parse JSON
-                        let rpc_response: JSONRPCResponse = serde_json::from_str(&response_text)
-                            .map_err(|e| {
-                                warn!("Failed to parse response as JSON: {}. Response content: {}", e, response_text);
-                                // Return local tool list when JSON parsing fails
-                                Error::msg(format!("Failed to parse response as JSON: {}. Response content: {}", e, response_text))
-                            })?;
-                        
-                        // Check for errors
-                        if let Some(error) = rpc_response.error {
-                            warn!("JSON-RPC error: {} (code: {})", error.message, error.code);
-                            // Return local tool list when JSON-RPC returns error
-                            return Ok(local_tools);
-                        }
-                        
-                        // Parse tool list
-                        if let Some(result) = rpc_response.result {
-                            debug!("Server response result: {:?}", result);
-                            if let Some(tools_value) = result.get("tools") {
-                                debug!("Tools value: {:?}", tools_value);
-                                if let Ok(tools_array) = serde_json::from_value::<Vec<serde_json::Value>>(tools_value.clone()) {
-                                    let mut tools = Vec::new();
-                                    // First add local tools to tools
-                                    tools.extend(local_tools);
-                                    for tool_value in tools_array {
-                                        debug!("Processing tool value: {:?}", tool_value);
-                                        if let (Ok(name), Ok(description)) = (
-                                            serde_json::from_value::<String>(tool_value["name"].clone()),
-                                            serde_json::from_value::<String>(tool_value["description"].clone())
-                                        ) {
-                                            tools.push(McpTool {
-                                                name,
-                                                description,
-                                            });
-                                        } else {
-                                            warn!("Failed to parse tool from server response: {:?}", tool_value);
-                                        }
-                                    }
-                                    return Ok(tools);
-                                } else {
-                                    warn!("Failed to parse tools array from server response: {:?}", tools_value);
-                                }
-                            } else {
-                                warn!("No 'tools' field in server response result: {:?}", result);
-                            }
-                        } else {
-                            warn!("No result in JSON-RPC response");
-                        }
-                        
-                        // Return local tool list if parsing fails
-                        warn!("Failed to parse tools from server response");
-                        Ok(local_tools)
-                    }
-                    Err(e) => {
-                        // Return local tool list when unable to connect to server
-                        warn!("Failed to send request to MCP server: {}", e);
-                        Ok(local_tools)
-                    }
-                }
-            } else {
-                // Return local tool list if no URL is set
-                Ok(local_tools)
+            let mut tools = self.available_tools.clone();
+            if self.connection_state() == McpConnectionState::Disconnected {
+                debug!("MCP server is not connected, returning local tools only");
+                return Ok(tools);
             }
+            let remote = self.remote_tools().await
+                .map_err(|e| Error::msg(format!("Failed to get tools from MCP server {}: {}", self.url, e)))?;
+            tools.extend(remote);
+            Ok(tools)
         })
     }
     
     // Call specified tool
     fn call_tool(&self, tool_name: &str, params: HashMap<String, Value>) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Value, Error>> + Send + '_>> {
-        let url = self.url.clone();
         let tool_name = tool_name.to_string();
-        let params = params.clone();
         let handler_opt = self.tool_handlers.get(&tool_name).cloned();
         Box::pin(async move {
             // Check if there is a custom tool handler
             if let Some(handler) = handler_opt {
                 // If there is a custom handler, call it
                 info!("Calling tool {} with params {:?}", tool_name, params);
-                handler(params.clone()).await
-            } else {
+                handler(params).await
+            } else if !self.url.is_empty() {
                 // Otherwise send JSON-RPC request via HTTP
-                if !url.is_empty() {
-                    // Construct JSON-RPC request
-                    let request = JSONRPCRequest {
-                        jsonrpc: "2.0".to_string(),
-                        id: Some(Value::String(Uuid::new_v4().to_string())),
-                        method: "tools/call".to_string(),
-                        params: Some(json!({
-                            "name": tool_name,
-                            "arguments": params
-                        })),
-                    };
-
-                    // Send HTTP POST request
-                    let client = reqwest::Client::new();
-                    let response = client
-                        .post(&format!("{}/rpc", url))
-                        .json(&request)
-                        .send()
-                        .await?;
-
-                    // Parse response
-                    let rpc_response: JSONRPCResponse = response.json().await?;
-                    
-                    // Check for errors
-                    if let Some(error) = rpc_response.error {
-                        return Err(Error::msg(format!("JSON-RPC error: {} (code: {})", error.message, error.code)));
-                    }
-                    
-                    // Return result
-                    Ok(rpc_response.result.unwrap_or(Value::Null))
-                } else {
-                    // If no URL is set and no custom handler, use default processing logic
-                    match tool_name.as_str() {
-                        "get_weather" => {
-                            // Bind default values to variables to extend lifetime
-                            let default_city = Value::String("Beijing".to_string());
-                            let city_value = params.get("city").unwrap_or(&default_city);
-                            let city = city_value.as_str().unwrap_or("Beijing");
-                            Ok(json!({
-                                "city": city,
-                                "temperature": "25°C",
-                                "weather": "cloudy",
-                                "humidity": "60%"
-                            }))
-                        },
-                        _ => Err(Error::msg(format!("Unknown tool: {}", tool_name)))
-                    }
+                self.rpc("tools/call", Some(json!({
+                    "name": tool_name,
+                    "arguments": params
+                }))).await
+            } else {
+                // If no URL is set and no custom handler, use default processing logic
+                match tool_name.as_str() {
+                    "get_weather" => {
+                        // Bind default values to variables to extend lifetime
+                        let default_city = Value::String("Beijing".to_string());
+                        let city_value = params.get("city").unwrap_or(&default_city);
+                        let city = city_value.as_str().unwrap_or("Beijing");
+                        Ok(json!({
+                            "city": city,
+                            "temperature": "25°C",
+                            "weather": "cloudy",
+                            "humidity": "60%"
+                        }))
+                    },
+                    _ => Err(Error::msg(format!("Unknown tool: {}", tool_name)))
                 }
             }
         })
     }
     
-    // Disconnect
+    // Remote calls stream progress and forward cancellation to the server
+    fn call_tool_with_context(&self, tool_name: &str, params: HashMap<String, Value>, context: ToolContext) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Value, Error>> + Send + '_>> {
+        if self.tool_handlers.contains_key(tool_name) || self.url.is_empty() {
+            let call = self.call_tool(tool_name, params);
+            return Box::pin(async move {
+                tokio::select! {
+                    _ = context.cancellation_token.cancelled() => Err(Error::new(RunnableInterrupt::Cancelled)),
+                    result = call => result,
+                }
+            });
+        }
+        Box::pin(self.call_tool_streaming(tool_name.to_string(), params, context))
+    }
+    
+    // Disconnect, stopping health checks
     fn disconnect(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + Send + '_>> {
-        let url = self.url.clone();
-        let is_connected = self.is_mcp_server_connected.clone();
         Box::pin(async move {
-            // Simple implementation: simulate successful disconnection
-            if let Ok(mut conn) = is_connected.lock() {
-                *conn = false;
-            }
-            info!("Disconnected from MCP server at {}", url);
+            self.shutdown.lock().unwrap_or_else(|e| e.into_inner()).cancel();
+            self.set_connection_state(McpConnectionState::Disconnected);
+            info!("Disconnected from MCP server at {}", self.url);
             Ok(())
         })
     }
@@ -316,84 +521,17 @@ impl McpClient for SimpleMcpClient {
         })
     }
     
-    // Clone method
+    // Clone method, the copy shares the connection state and tools cache
     fn clone(&self) -> Box<dyn McpClient> {
-        // Manually create deep copy of available_tools
-        let tools = self.available_tools.iter().map(|t| McpTool {
-            name: t.name.clone(),
-            description: t.description.clone()
-        }).collect();
-        
-        // Copy tool handlers
-        let tool_handlers = self.tool_handlers.clone();
-
-        // Clone connection status
-        let is_connected = if let Ok(conn) = self.is_mcp_server_connected.lock() {
-            Arc::new(Mutex::new(*conn))
-        } else {
-            Arc::new(Mutex::new(false))
-        };
-        
-        Box::new(SimpleMcpClient {
-            url: self.url.clone(),
-            available_tools: tools,
-            tool_handlers,
-            is_mcp_server_connected: is_connected,
-        })
+        Box::new(Clone::clone(self))
     }
     
     // Ping服务器
     fn ping(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + Send + '_>> {
-        let url = self.url.clone();
         Box::pin(async move {
-            if !url.is_empty() {
-                // 创建 ping 请求
-                let request = JSONRPCRequest {
-                    jsonrpc: "2.0".to_string(),
-                    id: Some(Value::Number(serde_json::Number::from(1))),
-                    method: "ping".to_string(),
-                    params: None,
-                };
-            
-                // 发送请求到服务器 - 使用正确的路径 /rpc
-                let url = format!("{}/rpc", url);
-                let client = reqwest::Client::new();
-                let response = client
-                    .post(&url)
-                    .header("Content-Type", "application/json")
-                    .json(&request)
-                    .send()
-                    .await
-                    .map_err(|e| Error::msg(format!("Failed to send ping request: {}", e)))?;
-            
-                // 检查响应状态
-                if !response.status().is_success() {
-                    return Err(Error::msg(format!("Ping request failed with status: {}", response.status())));
-                }
-            
-                // 解析响应
-                let response_text = response.text().await
-                    .map_err(|e| Error::msg(format!("Failed to read response: {}", e)))?;
-                let response_value: Value = serde_json::from_str(&response_text)
-                    .map_err(|e| Error::msg(format!("Failed to parse response: {}", e)))?;
-            
-                // 检查响应中是否有错误
-                if let Some(error) = response_value.get("error") {
-                    if !error.is_null() {
-                        return Err(Error::msg(format!("Ping request returned error: {}", error)));
-                    }
-                }
-                
-                // 检查是否有结果字段
-                if let Some(_result) = response_value.get("result") {
-                    // Ping 成功，返回空结果
-                    Ok(())
-                } else {
-                    Err(Error::msg("No result in ping response"))
-                }
-            } else {
-                Err(Error::msg("No URL set for MCP client"))
-            }
+            self.rpc("ping", None).await
+                .map(|_| ())
+                .map_err(|e| Error::msg(format!("Ping request failed: {}", e)))
         })
     }
 }
@@ -429,6 +567,17 @@ pub trait McpClient: Send + Sync {
         })
     }
     
+    // Call specified tool with progress reporting and cancellation, the default ignores progress and stops waiting when cancelled
+    fn call_tool_with_context(&self, tool_name: &str, params: HashMap<String, Value>, context: ToolContext) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Value, Error>> + Send + '_>> {
+        let call = self.call_tool(tool_name, params);
+        Box::pin(async move {
+            tokio::select! {
+                _ = context.cancellation_token.cancelled() => Err(Error::new(RunnableInterrupt::Cancelled)),
+                result = call => result,
+            }
+        })
+    }
+    
     // Disconnect
     fn disconnect(&self) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), Error>> + Send + '_>> {
         Box::pin(async move {
@@ -463,3 +612,97 @@ pub trait McpClient: Send + Sync {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{McpServer, SimpleMcpServer};
+    use crate::tools::ExampleTool;
+
+    #[tokio::test]
+    async fn test_reconnect_events_and_tools_cache() {
+        // Reserve a free port, the server binds it once the client is already retrying
+        let address = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap().to_string();
+        let client = SimpleMcpClient::new(format!("http://{}", address));
+        let mut events = client.subscribe_connection_events();
+        let health = client.spawn_health_check(ReconnectPolicy {
+            health_check_interval: Duration::from_millis(50),
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(50),
+        });
+
+        // Nothing listens yet, so the client backs off and retries
+        let event = tokio::time::timeout(Duration::from_secs(2), events.recv()).await.unwrap().unwrap();
+        assert_eq!(event, McpConnectionState::Reconnecting { attempt: 1 });
+
+        let server = SimpleMcpServer::new();
+        server.register_tool(Arc::new(ExampleTool::new("lookup".to_string(), "Look up a task".to_string()))).unwrap();
+        server.start(&address).await.unwrap();
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(2), events.recv()).await.unwrap().unwrap();
+            if event == McpConnectionState::Connected {
+                break;
+            }
+        }
+        assert_eq!(client.get_tools().await.unwrap().len(), 1);
+
+        // The tool list is served from the cache until it is invalidated
+        server.register_tool(Arc::new(ExampleTool::new("schedule".to_string(), "Schedule a task".to_string()))).unwrap();
+        assert_eq!(client.get_tools().await.unwrap().len(), 1);
+        client.invalidate_tools_cache();
+        assert_eq!(client.get_tools().await.unwrap().len(), 2);
+
+        // The next request reaching the server after a failed one marks the client connected again
+        client.set_connection_state(McpConnectionState::Reconnecting { attempt: 1 });
+        assert_eq!(client.get_tools().await.unwrap().len(), 2);
+        assert_eq!(client.connection_state(), McpConnectionState::Connected);
+
+        client.disconnect().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), health).await.unwrap().unwrap();
+        assert_eq!(client.connection_state(), McpConnectionState::Disconnected);
+        server.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_stops_health_check_during_ping() {
+        // Accept connections but never answer, so the ping stays in flight
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut open = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                open.push(stream);
+            }
+        });
+
+        let client = SimpleMcpClient::new(format!("http://{}", address));
+        client.set_server_connected(true);
+        let health = client.spawn_health_check(ReconnectPolicy {
+            health_check_interval: Duration::from_millis(50),
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(50),
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        client.disconnect().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(1), health).await.unwrap().unwrap();
+        assert_eq!(client.connection_state(), McpConnectionState::Disconnected);
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_get_tools_fails_when_server_is_unreachable() {
+        let address = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let mut client = SimpleMcpClient::new(format!("http://{}", address));
+        client.add_tool(McpTool { name: "local".to_string(), description: "Local tool".to_string() });
+
+        // Never connected: local tools only
+        assert_eq!(client.get_tools().await.unwrap().len(), 1);
+
+        // Connected server that stopped answering: an error, not a silently shorter list
+        client.set_server_connected(true);
+        assert!(client.get_tools().await.is_err());
+        assert_eq!(client.connection_state(), McpConnectionState::Reconnecting { attempt: 1 });
+        assert!(client.get_tools().await.is_err());
+    }
+}
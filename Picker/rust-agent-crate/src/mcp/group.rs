@@ -0,0 +1,242 @@
+// Client aggregating several MCP servers behind one McpClient
+use anyhow::Error;
+use futures::future::join_all;
+use log::{info, warn};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use super::client::{McpClient, McpTool};
+use crate::tools::ToolContext;
+
+const NAMESPACE_SEPARATOR: char = '.';
+
+struct McpServerEntry {
+    namespace: String,
+    url: String,
+    client: Box<dyn McpClient>,
+}
+
+/// Several MCP servers exposed as one client
+///
+/// Tool names are prefixed with the server namespace (`chain.transfer_coin`) and `call_tool` is routed
+/// to the server owning the namespace. Servers that are down are skipped when listing tools, so an
+/// `McpAgent` built on a group keeps working with the servers that are up.
+pub struct McpClientGroup {
+    servers: Vec<McpServerEntry>,
+}
+
+impl McpClientGroup {
+    pub fn new() -> Self {
+        Self { servers: Vec::new() }
+    }
+
+    /// Add a server, `connect` connects it to `url`
+    pub fn with_server(mut self, namespace: &str, url: &str, client: impl McpClient + 'static) -> Result<Self, Error> {
+        self.add_server(namespace, url, Box::new(client))?;
+        Ok(self)
+    }
+
+    // Add server method, the namespace must be non-empty, contain no '.' and not be registered yet
+    pub fn add_server(&mut self, namespace: &str, url: &str, client: Box<dyn McpClient>) -> Result<(), Error> {
+        if namespace.is_empty() || namespace.contains(NAMESPACE_SEPARATOR) {
+            return Err(Error::msg(format!(
+                "Invalid MCP server namespace {:?}: must be non-empty and contain no '{}'", namespace, NAMESPACE_SEPARATOR
+            )));
+        }
+        if self.server(namespace).is_some() {
+            return Err(Error::msg(format!("MCP server namespace {} is already registered", namespace)));
+        }
+        self.servers.push(McpServerEntry {
+            namespace: namespace.to_string(),
+            url: url.to_string(),
+            client,
+        });
+        Ok(())
+    }
+
+    pub fn namespaces(&self) -> Vec<&str> {
+        self.servers.iter().map(|server| server.namespace.as_str()).collect()
+    }
+
+    /// Client of a server
+    pub fn server(&self, namespace: &str) -> Option<&dyn McpClient> {
+        self.servers.iter().find(|server| server.namespace == namespace).map(|server| server.client.as_ref())
+    }
+
+    // Split `namespace.tool` and find the owning server
+    fn route(&self, tool_name: &str) -> Result<(&dyn McpClient, String), Error> {
+        let (namespace, name) = tool_name.split_once(NAMESPACE_SEPARATOR)
+            .ok_or_else(|| Error::msg(format!("Tool name {} has no server namespace", tool_name)))?;
+        let client = self.server(namespace)
+            .ok_or_else(|| Error::msg(format!("Unknown MCP server {} for tool {}", namespace, tool_name)))?;
+        Ok((client, name.to_string()))
+    }
+}
+
+impl Default for McpClientGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl McpClient for McpClientGroup {
+    // Connect every server to its own URL, succeeds when at least one server is reachable
+    fn connect(&mut self, _url: &str) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + '_>> {
+        Box::pin(async move {
+            let mut failures = Vec::new();
+            for server in &mut self.servers {
+                match server.client.connect(&server.url).await {
+                    Ok(()) => info!("Connected to MCP server {} at {}", server.namespace, server.url),
+                    Err(e) => {
+                        warn!("Failed to connect to MCP server {} at {}: {}", server.namespace, server.url, e);
+                        failures.push(format!("{}: {}", server.namespace, e));
+                    }
+                }
+            }
+            if !self.servers.is_empty() && failures.len() == self.servers.len() {
+                return Err(Error::msg(format!("Failed to connect to any MCP server: {}", failures.join("; "))));
+            }
+            Ok(())
+        })
+    }
+
+    // Namespaced tools of all servers, servers failing to answer are skipped
+    fn get_tools(&self) -> Pin<Box<dyn Future<Output = Result<Vec<McpTool>, Error>> + Send + '_>> {
+        Box::pin(async move {
+            let results = join_all(self.servers.iter().map(|server| server.client.get_tools())).await;
+            let mut tools = Vec::new();
+            for (server, result) in self.servers.iter().zip(results) {
+                match result {
+                    Ok(server_tools) => tools.extend(server_tools.into_iter().map(|tool| McpTool {
+                        name: format!("{}{}{}", server.namespace, NAMESPACE_SEPARATOR, tool.name),
+                        description: tool.description,
+                    })),
+                    Err(e) => warn!("Skipping tools of MCP server {}: {}", server.namespace, e),
+                }
+            }
+            Ok(tools)
+        })
+    }
+
+    fn call_tool(&self, tool_name: &str, params: HashMap<String, Value>) -> Pin<Box<dyn Future<Output = Result<Value, Error>> + Send + '_>> {
+        let route = self.route(tool_name);
+        Box::pin(async move {
+            let (client, name) = route?;
+            client.call_tool(&name, params).await
+        })
+    }
+
+    fn call_tool_with_context(&self, tool_name: &str, params: HashMap<String, Value>, context: ToolContext) -> Pin<Box<dyn Future<Output = Result<Value, Error>> + Send + '_>> {
+        let route = self.route(tool_name);
+        Box::pin(async move {
+            let (client, name) = route?;
+            client.call_tool_with_context(&name, params, context).await
+        })
+    }
+
+    fn disconnect(&self) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + '_>> {
+        Box::pin(async move {
+            for (server, result) in self.servers.iter().zip(join_all(self.servers.iter().map(|server| server.client.disconnect())).await) {
+                if let Err(e) = result {
+                    warn!("Failed to disconnect from MCP server {}: {}", server.namespace, e);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn clone(&self) -> Box<dyn McpClient> {
+        // Namespaces were validated when the servers were added
+        Box::new(McpClientGroup {
+            servers: self.servers.iter().map(|server| McpServerEntry {
+                namespace: server.namespace.clone(),
+                url: server.url.clone(),
+                client: server.client.clone(),
+            }).collect(),
+        })
+    }
+
+    // Reachable when any server answers
+    fn ping(&self) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + '_>> {
+        Box::pin(async move {
+            let results = join_all(self.servers.iter().map(|server| server.client.ping())).await;
+            if results.iter().any(|result| result.is_ok()) {
+                Ok(())
+            } else {
+                Err(Error::msg("No MCP server in the group is reachable"))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{Agent, McpAgent};
+    use crate::models::{FakeChatModel, FakeResponse};
+    use crate::SimpleMcpClient;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    // A server that is down
+    struct DownClient;
+
+    impl McpClient for DownClient {
+        fn get_tools(&self) -> Pin<Box<dyn Future<Output = Result<Vec<McpTool>, Error>> + Send + '_>> {
+            Box::pin(async { Err(Error::msg("connection refused")) })
+        }
+
+        fn clone(&self) -> Box<dyn McpClient> {
+            Box::new(DownClient)
+        }
+    }
+
+    fn local_server(tool: &str, result: Value) -> SimpleMcpClient {
+        let mut client = SimpleMcpClient::new(String::new());
+        client.add_tool(McpTool { name: tool.to_string(), description: format!("{} tool", tool) });
+        client.register_tool_handler(tool.to_string(), move |_| {
+            let result = result.clone();
+            async move { Ok(result) }
+        });
+        client
+    }
+
+    #[tokio::test]
+    async fn test_group_namespaces_routes_and_skips_down_servers() {
+        let group = McpClientGroup::new()
+            .with_server("chain", "http://localhost:6201", local_server("transfer_coin", json!({"tx": "0x1"})))
+            .unwrap()
+            .with_server("files", "http://localhost:6202", local_server("read_file", json!("hello")))
+            .unwrap()
+            .with_server("offline", "http://localhost:6203", DownClient)
+            .unwrap();
+
+        let names: Vec<String> = group.get_tools().await.unwrap().into_iter().map(|tool| tool.name).collect();
+        assert_eq!(names, vec!["chain.transfer_coin", "files.read_file"]);
+        assert_eq!(group.call_tool("files.read_file", HashMap::new()).await.unwrap(), json!("hello"));
+        assert!(group.call_tool("wallet.balance", HashMap::new()).await.is_err());
+        assert!(group.call_tool("transfer_coin", HashMap::new()).await.is_err());
+
+        let mut agent = McpAgent::with_chat_model(
+            Arc::new(group),
+            "Assistant".to_string(),
+            Arc::new(FakeChatModel::new(vec![FakeResponse::answer("done")])),
+        );
+        agent.auto_add_tools().await.unwrap();
+        let tools = agent.tools();
+        let transfer = tools.iter().find(|tool| tool.name() == "chain.transfer_coin").unwrap();
+        assert!(transfer.invoke("{}").await.unwrap().contains("0x1"));
+    }
+
+    #[test]
+    fn test_add_server_rejects_invalid_and_duplicate_namespaces() {
+        let mut group = McpClientGroup::new();
+        assert!(group.add_server("", "http://localhost:6201", Box::new(DownClient)).is_err());
+        assert!(group.add_server("chain.eth", "http://localhost:6201", Box::new(DownClient)).is_err());
+        group.add_server("chain", "http://localhost:6201", Box::new(DownClient)).unwrap();
+        assert!(group.add_server("chain", "http://localhost:6202", Box::new(DownClient)).is_err());
+        assert_eq!(group.namespaces(), vec!["chain"]);
+    }
+}
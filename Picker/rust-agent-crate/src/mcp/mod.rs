@@ -2,42 +2,97 @@
 mod client;
 mod adapter;
 mod server;
+mod group;
 
 // Re-export module content
-pub use client::{McpClient, SimpleMcpClient, McpTool};
+pub use client::{McpClient, SimpleMcpClient, McpTool, McpConnectionState, ReconnectPolicy};
 pub use adapter::McpToolAdapter;
 pub use server::{McpServer, SimpleMcpServer};
+pub use group::McpClientGroup;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+// Header identifying the client session, request ids (and their cancellation) are scoped to it
+pub(crate) const SESSION_ID_HEADER: &str = "mcp-session-id";
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JSONRPCRequest {
     pub jsonrpc: String,
+    // None makes the request a notification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<Value>,
     pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub params: Option<Value>,
 }
 
+// Exactly one of result and error is sent; the id is null only when the request id could not be read
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JSONRPCResponse {
     jsonrpc: String,
-    id: Option<Value>,
+    // Always serialized, JSON-RPC requires the member even when it is null
+    #[serde(default)]
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     error: Option<JSONRPCError>,
 }
 
+impl JSONRPCResponse {
+    pub fn success(id: Value, result: Value) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id, result: Some(result), error: None }
+    }
+
+    pub fn failure(id: Value, error: JSONRPCError) -> Self {
+        Self { jsonrpc: "2.0".to_string(), id, result: None, error: Some(error) }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct JSONRPCError {
     code: i32,
     message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl JSONRPCError {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INTERNAL_ERROR: i32 = -32603;
+    // Not part of JSON-RPC, the code LSP uses for cancelled requests
+    pub const REQUEST_CANCELLED: i32 = -32800;
+
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+
+    /// Structured details, e.g. the schema violations of invalid params
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
 }
 
 // MCP Ping interface test
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::RunnableInterrupt;
+    use crate::tools::{ProgressReporter, Tool, ToolContext};
+    use anyhow::Error;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
     use tokio::time::{timeout, Duration};
+    use tokio_util::sync::CancellationToken;
     
     #[tokio::test]
     async fn test_mcp_ping() {
@@ -45,10 +100,10 @@ mod tests {
         let server = SimpleMcpServer::new();
         
         // start MCP server
-        let server_address = "127.0.0.1:6000";
-        if let Err(e) = server.start(server_address).await {
+        if let Err(e) = server.start("127.0.0.1:0").await {
             panic!("Failed to start MCP server: {}", e);
         }
+        let server_address = server.local_addr().unwrap();
         
         // wait for server to start
         tokio::time::sleep(Duration::from_millis(100)).await;
@@ -79,4 +134,155 @@ mod tests {
             panic!("Failed to stop MCP server: {}", e);
         }
     }
+    
+    // Waits for chain confirmations, reporting progress and stopping when cancelled
+    struct ConfirmationTool {
+        stopped: Arc<AtomicBool>,
+    }
+    
+    impl Tool for ConfirmationTool {
+        fn name(&self) -> &str {
+            "cross_chain_pay"
+        }
+        
+        fn description(&self) -> &str {
+            "Pay on another chain"
+        }
+        
+        fn args_schema(&self) -> Option<Value> {
+            Some(json!({ "type": "object", "properties": { "confirmations": { "type": "integer" } } }))
+        }
+        
+        fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+            self.invoke_with_context(input, ToolContext::new())
+        }
+        
+        fn invoke_with_context(&self, input: &str, context: ToolContext) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+            let confirmations: u64 = serde_json::from_str::<Value>(input).ok()
+                .and_then(|args| args["confirmations"].as_u64())
+                .unwrap_or(3);
+            Box::pin(async move {
+                for confirmation in 1..=confirmations {
+                    tokio::select! {
+                        _ = context.cancellation_token.cancelled() => {
+                            self.stopped.store(true, Ordering::SeqCst);
+                            return Err(Error::msg("payment aborted"));
+                        }
+                        _ = tokio::time::sleep(Duration::from_millis(20)) => {}
+                    }
+                    context.report_progress(confirmation as f64 * 100.0 / confirmations as f64, &format!("{} confirmations", confirmation));
+                }
+                Ok("paid".to_string())
+            })
+        }
+        
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_tool_progress_and_cancellation() {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = SimpleMcpServer::new();
+        server.register_tool(Arc::new(ConfirmationTool { stopped: stopped.clone() })).unwrap();
+        server.start("127.0.0.1:0").await.unwrap();
+        let client = SimpleMcpClient::new(format!("http://{}", server.local_addr().unwrap()));
+        
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = updates.clone();
+        let context = ToolContext::new().with_progress(ProgressReporter::new(move |progress| sink.lock().unwrap().push(progress)));
+        let result = client.call_tool_with_context("cross_chain_pay", HashMap::new(), context).await.unwrap();
+        assert_eq!(result, "paid");
+        let updates = updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 3);
+        assert_eq!(updates[2].percentage(), Some(100.0));
+        assert_eq!(updates[0].message.as_deref(), Some("1 confirmations"));
+        
+        // Cancelling on the first progress update aborts the call on the server
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let context = ToolContext::new()
+            .with_cancellation_token(token)
+            .with_progress(ProgressReporter::new(move |_| cancel.cancel()));
+        let params = HashMap::from([("confirmations".to_string(), json!(100))]);
+        let error = client.call_tool_with_context("cross_chain_pay", params, context).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<RunnableInterrupt>(), Some(&RunnableInterrupt::Cancelled));
+        timeout(Duration::from_secs(2), async {
+            while !stopped.load(Ordering::SeqCst) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.expect("tool was not cancelled on the server");
+        
+        server.stop().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_jsonrpc_batches_notifications_and_error_codes() {
+        let server = SimpleMcpServer::new();
+        server.register_tool(Arc::new(ConfirmationTool { stopped: Arc::new(AtomicBool::new(false)) })).unwrap();
+        server.start("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/rpc", server.local_addr().unwrap());
+        let http = reqwest::Client::new();
+        let post = |body: String| http.post(&url).header("content-type", "application/json").body(body).send();
+        
+        let parse_error: Value = post("{\"jsonrpc\": ".to_string()).await.unwrap().json().await.unwrap();
+        assert_eq!(parse_error["error"]["code"], -32700);
+        assert_eq!(parse_error.get("id"), Some(&Value::Null));
+        
+        let notification = post(json!({ "jsonrpc": "2.0", "method": "ping" }).to_string()).await.unwrap();
+        assert_eq!(notification.status(), 202);
+        assert!(notification.text().await.unwrap().is_empty());
+        
+        let batch: Value = post(json!([
+            { "jsonrpc": "2.0", "id": 1, "method": "ping" },
+            { "jsonrpc": "2.0", "method": "notifications/initialized" },
+            { "jsonrpc": "2.0", "id": "two", "method": "resources/list" },
+            { "jsonrpc": "2.0", "id": 3, "method": "tools/call", "params": { "name": "cross_chain_pay", "arguments": { "confirmations": "many" } } },
+            { "jsonrpc": "1.0", "id": 4, "method": "ping" }
+        ]).to_string()).await.unwrap().json().await.unwrap();
+        let responses = batch.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0], json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+        assert_eq!(responses[1]["error"]["code"], -32601);
+        assert_eq!(responses[1]["id"], "two");
+        assert_eq!(responses[2]["error"]["code"], -32602);
+        assert_eq!(responses[2]["error"]["data"]["errors"][0], "arguments.confirmations must be of type integer");
+        assert_eq!(responses[3]["error"]["code"], -32600);
+        
+        let empty: Value = post("[]".to_string()).await.unwrap().json().await.unwrap();
+        assert_eq!(empty["error"]["code"], -32600);
+        
+        server.stop().await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_request_ids_are_scoped_to_sessions() {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let server = SimpleMcpServer::new();
+        server.register_tool(Arc::new(ConfirmationTool { stopped: stopped.clone() })).unwrap();
+        server.start("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/rpc", server.local_addr().unwrap());
+        let http = reqwest::Client::new();
+        let post = |session: &str, body: Value| http.post(&url)
+            .header(SESSION_ID_HEADER, session)
+            .json(&body)
+            .send();
+        let call = json!({ "jsonrpc": "2.0", "id": 7, "method": "tools/call", "params": { "name": "cross_chain_pay", "arguments": { "confirmations": 25 } } });
+        
+        let running = tokio::spawn(post("alice", call.clone()));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        
+        // Cancelling id 7 from another session does not stop the call, reusing the in-flight id in the same session is refused
+        post("bob", json!({ "jsonrpc": "2.0", "method": "notifications/cancelled", "params": { "requestId": 7 } })).await.unwrap();
+        let duplicate: Value = post("alice", call.clone()).await.unwrap().json().await.unwrap();
+        assert_eq!(duplicate["error"]["code"], -32600);
+        assert_eq!(duplicate["id"], 7);
+        
+        let result: Value = running.await.unwrap().unwrap().json().await.unwrap();
+        assert_eq!(result["result"], "paid");
+        assert!(!stopped.load(Ordering::SeqCst));
+        
+        server.stop().await.unwrap();
+    }
 }
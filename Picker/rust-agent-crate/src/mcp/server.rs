@@ -2,22 +2,31 @@
 use anyhow::Error;
 use std::sync::{Arc, Mutex};
 use std::collections::HashMap;
-use crate::tools::Tool;
+use std::convert::Infallible;
+use crate::core::RunnableInterrupt;
+use crate::tools::{ProgressReporter, Tool, ToolContext};
 use serde::{Deserialize, Serialize};
 use axum::{
+    body::Bytes,
     extract::State,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::{Event, Sse}, IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
+use futures::future::join_all;
+use std::net::SocketAddr;
 use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, StreamExt};
+use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
-use serde_json::Value;
+use serde_json::{json, Value};
 use log::{info, error};
 
-use crate::mcp::JSONRPCRequest;
 use crate::mcp::JSONRPCResponse;
 use crate::mcp::JSONRPCError;
+use crate::mcp::SESSION_ID_HEADER;
 
 #[derive(Debug, Deserialize, Serialize)]
 struct CallToolParams {
@@ -31,6 +40,7 @@ pub struct SimpleMcpServer {
     tools: Arc<Mutex<HashMap<String, Arc<dyn Tool>>>>,
     is_running: Arc<Mutex<bool>>,
     server_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    local_addr: Arc<Mutex<Option<SocketAddr>>>,
 }
 
 impl SimpleMcpServer {
@@ -40,9 +50,15 @@ impl SimpleMcpServer {
             tools: Arc::new(Mutex::new(HashMap::new())),
             is_running: Arc::new(Mutex::new(false)),
             server_handle: Arc::new(Mutex::new(None)),
+            local_addr: Arc::new(Mutex::new(None)),
         }
     }
     
+    /// Address the server listens on once started, e.g. the port assigned when starting on port 0
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.lock().unwrap_or_else(|e| e.into_inner())
+    }
+    
     pub fn with_address(mut self, address: String) -> Self {
         self.address = address;
         self
@@ -55,148 +71,329 @@ async fn test_handler() -> &'static str {
     "Hello, Rust-Agent!"
 }
 
-// Handle JSON-RPC request
+// Handle JSON-RPC request: a single request or a batch array, notifications get no response
 #[axum::debug_handler]
 async fn handle_jsonrpc_request(
     State(state): State<Arc<SimpleMcpServerState>>,
-    Json(payload): Json<JSONRPCRequest>,
-) -> Json<JSONRPCResponse> {
-    let response = match payload.method.as_str() {
-        "tools/call" => {
-            // Handle tool call request
-            match handle_tool_call(state, payload.params).await {
-                Ok(result) => {
-                    JSONRPCResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: Some(payload.id.unwrap_or(Value::Null)),
-                        result: Some(result),
-                        error: None,
-                    }
-                }
-                Err(e) => {
-                    JSONRPCResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: Some(payload.id.unwrap_or(Value::Null)),
-                        result: None,
-                        error: Some(JSONRPCError {
-                            code: -32603,
-                            message: e.to_string(),
-                        }),
-                    }
-                }
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // Request ids are only unique per client, clients without a session id share one anonymous session
+    let session = headers.get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let message: Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            let error = JSONRPCError::new(JSONRPCError::PARSE_ERROR, "Parse error").with_data(json!(e.to_string()));
+            return Json(JSONRPCResponse::failure(Value::Null, error)).into_response();
+        }
+    };
+    
+    match message {
+        Value::Array(batch) => {
+            if batch.is_empty() {
+                let error = JSONRPCError::new(JSONRPCError::INVALID_REQUEST, "Invalid Request").with_data(json!("Empty batch"));
+                return Json(JSONRPCResponse::failure(Value::Null, error)).into_response();
+            }
+            // Batch entries run concurrently, tool calls in a batch are not streamed
+            let responses: Vec<JSONRPCResponse> = join_all(batch.into_iter().map(|message| handle_message(state.clone(), &session, message)))
+                .await
+                .into_iter()
+                .flatten()
+                .collect();
+            if responses.is_empty() {
+                StatusCode::ACCEPTED.into_response()
+            } else {
+                Json(responses).into_response()
             }
         }
-        "ping" => {
-            // Handle ping request
-            JSONRPCResponse {
-                jsonrpc: "2.0".to_string(),
-                id: Some(payload.id.unwrap_or(Value::Null)),
-                result: Some(Value::Object(serde_json::Map::new())),
-                error: None,
+        message => {
+            let request = match parse_request(message) {
+                Ok(request) => request,
+                Err(response) => return Json(*response).into_response(),
+            };
+            let accepts_stream = headers.get(header::ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.contains("text/event-stream"));
+            let progress_token = request.params.as_ref()
+                .and_then(|params| params.pointer("/_meta/progressToken"))
+                .cloned();
+            match (&request.id, progress_token) {
+                (Some(_), Some(progress_token)) if accepts_stream && request.method == "tools/call" => {
+                    stream_tool_call(state, session, request, progress_token)
+                }
+                _ => match dispatch(state, &session, request, None).await {
+                    Some(response) => Json(response).into_response(),
+                    None => StatusCode::ACCEPTED.into_response(),
+                },
             }
         }
-        "tools/list" => {
-            // Handle tool list request
-            match handle_list_tools(state).await {
-                Ok(result) => {
-                    JSONRPCResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: Some(payload.id.unwrap_or(Value::Null)),
-                        result: Some(result),
-                        error: None,
+    }
+}
+
+// A request without an id member is a notification
+struct IncomingRequest {
+    id: Option<Value>,
+    method: String,
+    params: Option<Value>,
+}
+
+fn parse_request(message: Value) -> Result<IncomingRequest, Box<JSONRPCResponse>> {
+    let id = message.get("id").cloned();
+    let invalid = |reason: &str| Box::new(JSONRPCResponse::failure(
+        id.clone().unwrap_or(Value::Null),
+        JSONRPCError::new(JSONRPCError::INVALID_REQUEST, "Invalid Request").with_data(json!(reason)),
+    ));
+    let Value::Object(mut object) = message else {
+        return Err(invalid("Request must be an object"));
+    };
+    if object.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(invalid("jsonrpc must be \"2.0\""));
+    }
+    if !matches!(&id, None | Some(Value::String(_)) | Some(Value::Number(_)) | Some(Value::Null)) {
+        return Err(invalid("id must be a string, number or null"));
+    }
+    let Some(Value::String(method)) = object.remove("method") else {
+        return Err(invalid("method must be a string"));
+    };
+    // Null params are read as omitted, older clients send them
+    let params = object.remove("params").filter(|params| !params.is_null());
+    if !matches!(params, None | Some(Value::Object(_)) | Some(Value::Array(_))) {
+        return Err(invalid("params must be an object or an array"));
+    }
+    Ok(IncomingRequest { id, method, params })
+}
+
+// Run one request of a single or batch message, returns None for notifications
+async fn handle_message(state: Arc<SimpleMcpServerState>, session: &str, message: Value) -> Option<JSONRPCResponse> {
+    match parse_request(message) {
+        Ok(request) => dispatch(state, session, request, None).await,
+        Err(response) => Some(*response),
+    }
+}
+
+async fn dispatch(
+    state: Arc<SimpleMcpServerState>,
+    session: &str,
+    request: IncomingRequest,
+    progress: Option<ProgressReporter>,
+) -> Option<JSONRPCResponse> {
+    let result = match request.method.as_str() {
+        "ping" => Ok(json!({})),
+        "tools/list" => handle_list_tools(&state),
+        "tools/call" => {
+            // Registered so notifications/cancelled of the same session can stop the call
+            let cancellation_token = CancellationToken::new();
+            let request_key = request.id.as_ref().map(|id| (session.to_string(), id.to_string()));
+            let registered = match &request_key {
+                Some(key) => {
+                    let mut in_flight = state.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+                    if in_flight.contains_key(key) {
+                        false
+                    } else {
+                        in_flight.insert(key.clone(), cancellation_token.clone());
+                        true
                     }
                 }
-                Err(e) => {
-                    JSONRPCResponse {
-                        jsonrpc: "2.0".to_string(),
-                        id: Some(payload.id.unwrap_or(Value::Null)),
-                        result: None,
-                        error: Some(JSONRPCError {
-                            code: -32603,
-                            message: e.to_string(),
-                        }),
-                    }
+                None => true,
+            };
+            if registered {
+                let mut context = ToolContext::new().with_cancellation_token(cancellation_token);
+                if let Some(progress) = progress {
+                    context = context.with_progress(progress);
                 }
+                let result = handle_tool_call(&state, request.params, context).await;
+                if let Some(key) = &request_key {
+                    state.in_flight.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+                }
+                result
+            } else {
+                // Answering would make the two calls indistinguishable to the client
+                Err(JSONRPCError::new(JSONRPCError::INVALID_REQUEST, "Invalid Request")
+                    .with_data(json!("A request with this id is already in flight")))
             }
         }
-        _ => {
-            // Unsupported method
-            JSONRPCResponse {
-                jsonrpc: "2.0".to_string(),
-                id: Some(payload.id.unwrap_or(Value::Null)),
-                result: None,
-                error: Some(JSONRPCError {
-                    code: -32601,
-                    message: "Method not found".to_string(),
-                }),
-            }
+        "notifications/cancelled" => {
+            // Cancel an in-flight tool call
+            handle_cancelled(&state, session, request.params.as_ref());
+            Ok(Value::Null)
         }
+        // Other notifications (e.g. notifications/initialized) need no handling
+        method if method.starts_with("notifications/") => Ok(Value::Null),
+        method => Err(JSONRPCError::new(JSONRPCError::METHOD_NOT_FOUND, "Method not found").with_data(json!(method))),
     };
     
-    Json(response)
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => JSONRPCResponse::success(id, result),
+        Err(error) => JSONRPCResponse::failure(id, error),
+    })
 }
 
-async fn handle_list_tools(
-    state: Arc<SimpleMcpServerState>,
-) -> Result<serde_json::Value, Error> {
+// tools/call with a progress token from a client accepting server-sent events: progress notifications
+// are streamed before the result, and the tool is cancelled if the client disconnects
+fn stream_tool_call(state: Arc<SimpleMcpServerState>, session: String, request: IncomingRequest, progress_token: Value) -> Response {
+    let (sender, receiver) = mpsc::unbounded_channel::<Event>();
+    let progress_sender = sender.clone();
+    let progress = ProgressReporter::new(move |progress| {
+        let mut params = serde_json::to_value(&progress).unwrap_or_else(|_| json!({}));
+        params["progressToken"] = progress_token.clone();
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": params });
+        let _ = progress_sender.send(Event::default().data(notification.to_string()));
+    });
+    let request_id = request.id.clone();
+    tokio::spawn(async move {
+        let call = dispatch(state.clone(), &session, request, Some(progress));
+        tokio::pin!(call);
+        let finished = tokio::select! {
+            response = &mut call => Some(response),
+            _ = sender.closed() => None,
+        };
+        match finished {
+            Some(response) => {
+                let _ = sender.send(Event::default().data(serde_json::to_string(&response).unwrap_or_default()));
+            }
+            // The client went away, let the tool stop cooperatively
+            None => {
+                handle_cancelled(&state, &session, Some(&json!({ "requestId": request_id })));
+                let _ = call.await;
+            }
+        }
+    });
+    Sse::new(UnboundedReceiverStream::new(receiver).map(Ok::<_, Infallible>)).into_response()
+}
+
+fn handle_cancelled(state: &SimpleMcpServerState, session: &str, params: Option<&Value>) {
+    let Some(request_id) = params.and_then(|params| params.get("requestId")) else {
+        return;
+    };
+    let key = (session.to_string(), request_id.to_string());
+    if let Some(token) = state.in_flight.lock().unwrap_or_else(|e| e.into_inner()).remove(&key) {
+        info!("Cancelling tool call {}", request_id);
+        token.cancel();
+    }
+}
+
+fn handle_list_tools(state: &SimpleMcpServerState) -> Result<Value, JSONRPCError> {
     // Get all registered tools
-    let tools_map = state.tools.lock().map_err(|e| Error::msg(format!("Failed to acquire lock: {}", e)))?;
+    let tools_map = state.tools.lock().map_err(|e| JSONRPCError::new(JSONRPCError::INTERNAL_ERROR, format!("Failed to acquire lock: {}", e)))?;
     
-    // Convert to tool format required by MCP protocol
+    // Convert to tool format required by MCP protocol, tools without a schema accept any object
     let mut tools_list = Vec::new();
     for (_, tool) in tools_map.iter() {
-        let mcp_tool = serde_json::json!({
+        let mcp_tool = json!({
             "name": tool.name(),
             "description": tool.description(),
-            "inputSchema": {
+            "inputSchema": tool.args_schema().unwrap_or_else(|| json!({
                 "type": "object",
                 "properties": {},
                 "required": []
-            }
+            }))
         });
         tools_list.push(mcp_tool);
     }
     
     // Construct response
-    let result = serde_json::json!({
+    Ok(json!({
         "tools": tools_list
-    });
-    
-    Ok(result)
+    }))
 }
 
 async fn handle_tool_call(
-    state: Arc<SimpleMcpServerState>,
-    params: Option<serde_json::Value>,
-) -> Result<serde_json::Value, Error> {
+    state: &SimpleMcpServerState,
+    params: Option<Value>,
+    context: ToolContext,
+) -> Result<Value, JSONRPCError> {
     // Parse parameters
-    let call_params: CallToolParams = serde_json::from_value(params.unwrap_or(serde_json::Value::Null))
-        .map_err(|e| Error::msg(format!("Invalid parameters: {}", e)))?;
+    let call_params: CallToolParams = serde_json::from_value(params.unwrap_or(Value::Null))
+        .map_err(|e| JSONRPCError::new(JSONRPCError::INVALID_PARAMS, "Invalid params").with_data(json!({ "errors": [e.to_string()] })))?;
     
     // Find tool and get its Arc reference
     let tool = {
-        let tools = state.tools.lock().map_err(|e| Error::msg(format!("Failed to acquire lock: {}", e)))?;
+        let tools = state.tools.lock().map_err(|e| JSONRPCError::new(JSONRPCError::INTERNAL_ERROR, format!("Failed to acquire lock: {}", e)))?;
         tools.get(&call_params.name)
-            .ok_or_else(|| Error::msg(format!("Tool '{}' not found", call_params.name)))?
+            .ok_or_else(|| JSONRPCError::new(JSONRPCError::INVALID_PARAMS, format!("Unknown tool: {}", call_params.name)))?
             .clone()
     };
     
-    // Prepare tool input parameters
-    let input_str = if let Some(args) = call_params.arguments {
-        serde_json::to_string(&args)?
-    } else {
-        "{}".to_string()
-    };
+    // Arguments must match the schema the tool declares
+    let arguments = Value::Object(call_params.arguments.unwrap_or_default().into_iter().collect());
+    if let Some(schema) = tool.args_schema() {
+        let errors = schema_errors(&schema, &arguments, "arguments");
+        if !errors.is_empty() {
+            return Err(JSONRPCError::new(JSONRPCError::INVALID_PARAMS, "Invalid params").with_data(json!({ "tool": tool.name(), "errors": errors })));
+        }
+    }
     
     // Call tool (now can be called without holding the lock)
-    let result = tool.invoke(&input_str).await?;
-    Ok(serde_json::Value::String(result))
+    match tool.invoke_with_context(&arguments.to_string(), context).await {
+        Ok(result) => Ok(Value::String(result)),
+        Err(e) if matches!(e.downcast_ref::<RunnableInterrupt>(), Some(RunnableInterrupt::Cancelled)) => {
+            Err(JSONRPCError::new(JSONRPCError::REQUEST_CANCELLED, e.to_string()))
+        }
+        Err(e) => Err(JSONRPCError::new(JSONRPCError::INTERNAL_ERROR, e.to_string()).with_data(json!({ "tool": tool.name() }))),
+    }
+}
+
+// Check a value against the JSON schema keywords tools use (type, required, properties, items, enum)
+fn schema_errors(schema: &Value, value: &Value, path: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let matches = |name: &str| match name {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            _ => true,
+        };
+        if !types.is_empty() && !types.iter().any(|name| matches(name)) {
+            errors.push(format!("{} must be of type {}", path, types.join(" or ")));
+            return errors;
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            errors.push(format!("{} must be one of {}", path, Value::Array(allowed.clone())));
+        }
+    }
+    if let Value::Object(object) = value {
+        for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                errors.push(format!("{}.{} is required", path, name));
+            }
+        }
+        if let Some(Value::Object(properties)) = schema.get("properties") {
+            for (name, property) in properties {
+                if let Some(field) = object.get(name) {
+                    errors.extend(schema_errors(property, field, &format!("{}.{}", path, name)));
+                }
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            errors.extend(schema_errors(item_schema, item, &format!("{}[{}]", path, index)));
+        }
+    }
+    errors
 }
 
 // Server state structure
 #[derive(Clone)]
 struct SimpleMcpServerState {
     tools: Arc<Mutex<HashMap<String, Arc<dyn Tool>>>>,
+    // Cancellation tokens of running tool calls by session id and JSON-RPC request id
+    in_flight: Arc<Mutex<HashMap<(String, String), CancellationToken>>>,
 }
 
 // MCP server abstraction
@@ -221,6 +418,7 @@ impl McpServer for SimpleMcpServer {
         // Create server state
         let state = Arc::new(SimpleMcpServerState {
             tools: self.tools.clone(),
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
         });
         
         // Create routes
@@ -234,7 +432,11 @@ impl McpServer for SimpleMcpServer {
         let listener = TcpListener::bind(address).await
             .map_err(|e| Error::msg(format!("Failed to bind to address {}: {}", address, e)))?;
         
-        info!("MCP server listening on http://{}", address);
+        let local_addr = listener.local_addr()
+            .map_err(|e| Error::msg(format!("Failed to get local address: {}", e)))?;
+        *self.local_addr.lock().map_err(|e| Error::msg(format!("Failed to acquire lock: {}", e)))? = Some(local_addr);
+        
+        info!("MCP server listening on http://{}", local_addr);
         
         // Run server in background task
         let handle = tokio::spawn(async move {
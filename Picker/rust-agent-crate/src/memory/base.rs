@@ -140,10 +140,14 @@ impl BaseMemory for SimpleMemory {
             
             // Add input as human message or tool message to chat history
             if let Some(input_value) = input_clone.get("input") {
-                let user_message = serde_json::json!({
+                let mut user_message = serde_json::json!({
                         "role": "human",
                         "content": input_value
                     });
+                // Keep image and file parts of multimodal messages
+                if let Some(parts) = input_clone.get("parts") {
+                    user_message["parts"] = parts.clone();
+                }
                 
                 if let Value::Array(ref mut arr) = chat_history {
                     info!("Adding to chat history: {:?}", user_message);
@@ -1,6 +1,6 @@
 // Composite memory module
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::pin::Pin;
 use anyhow::{Error, Result};
@@ -13,10 +13,13 @@ use std::future::Future;
 
 use crate::memory::base::{BaseMemory, MemoryVariables};
 use crate::memory::message_history::{MessageHistoryMemory, ChatMessage};
-use crate::memory::summary::SummaryMemory;
+use crate::memory::summary::{SummaryMemory, SummaryData};
+use crate::memory::export::{MemoryExportBundle, MemoryExportFormat, MEMORY_EXPORT_VERSION};
+use crate::memory::redaction::Redactor;
+use crate::memory::encryption::{MemoryCipher, rotate_memory_encryption};
 use crate::memory::utils::{
     ensure_data_dir_exists, get_data_dir_from_env, get_summary_threshold_from_env,
-    get_recent_messages_count_from_env, generate_session_id
+    get_recent_messages_count_from_env, generate_session_id, atomic_write_file, read_file_content
 };
 
 /// Composite memory configuration
@@ -60,6 +63,10 @@ pub struct CompositeMemory {
     summary_memory: Option<Arc<SummaryMemory>>,
     /// In-memory memory variables
     memory_variables: Arc<RwLock<MemoryVariables>>,
+    /// Redactor applied before persisting or summarizing messages (optional)
+    redactor: Option<Arc<dyn Redactor>>,
+    /// Cipher used to encrypt memory files at rest (optional)
+    cipher: Option<Arc<MemoryCipher>>,
 }
 
 impl CompositeMemory {
@@ -88,6 +95,17 @@ impl CompositeMemory {
 
     /// Create a composite memory instance with configuration
     pub async fn with_config(config: CompositeMemoryConfig) -> Result<Self> {
+        Self::with_config_and_cipher(config, None).await
+    }
+
+    /// Create a composite memory instance whose history and summary files are encrypted at rest
+    ///
+    /// Existing plaintext files are rejected with `MemoryCipherError::Unencrypted` unless the cipher enables
+    /// `with_plaintext_migration`, in which case they are read as-is and encrypted on the next save.
+    /// Opening encrypted files with a wrong key fails with `MemoryCipherError::WrongKey`.
+    pub async fn with_config_and_cipher(config: CompositeMemoryConfig, cipher: Option<MemoryCipher>) -> Result<Self> {
+        let cipher = cipher.map(Arc::new);
+
         // Ensure data directory exists
         ensure_data_dir_exists(&config.data_dir).await?;
 
@@ -96,10 +114,11 @@ impl CompositeMemory {
             .unwrap_or_else(|| generate_session_id());
 
         // Always create message history memory
-        let history = MessageHistoryMemory::new_with_recent_count(
+        let history = MessageHistoryMemory::new_with_cipher(
             session_id.clone(),
             config.data_dir.clone(),
-            config.recent_messages_count
+            config.recent_messages_count,
+            cipher.clone()
         ).await?;
         let message_history = Some(Arc::new(history));
 
@@ -109,7 +128,7 @@ impl CompositeMemory {
             config.data_dir.clone(),
             config.summary_threshold,
             message_history.clone().unwrap() // We just created it, so it's safe to unwrap
-        ).await?;
+        ).await?.with_cipher(cipher.clone());
         let summary_memory = Some(Arc::new(summary));
 
         Ok(Self {
@@ -117,9 +136,46 @@ impl CompositeMemory {
             message_history,
             summary_memory,
             memory_variables: Arc::new(RwLock::new(HashMap::new())),
+            redactor: None,
+            cipher,
         })
     }
 
+    /// Set the redactor applied to messages before they are persisted or summarized
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.summary_memory = self.summary_memory
+            .map(|summary| Arc::new((*summary).clone().with_redactor(redactor.clone())));
+        self.redactor = Some(redactor);
+        self
+    }
+
+    /// Re-encrypt all memory files in the data directory with a new key and reopen this session
+    ///
+    /// Pass `None` to decrypt the files. Every session in the data directory must use the current key;
+    /// other open instances on the same directory have to be reopened afterwards.
+    pub async fn rotate_encryption_key(self, new_cipher: Option<MemoryCipher>) -> Result<Self> {
+        rotate_memory_encryption(&self.config.data_dir, self.cipher.as_deref(), new_cipher.as_ref()).await?;
+
+        let mut config = self.config.clone();
+        if let Some(ref history) = self.message_history {
+            config.session_id = Some(history.get_session_id().to_string());
+        }
+
+        let mut rotated = Self::with_config_and_cipher(config, new_cipher).await?;
+        if let Some(redactor) = self.redactor {
+            rotated = rotated.with_redactor(redactor);
+        }
+        Ok(rotated)
+    }
+
+    /// Apply the configured redactor (if any) to text
+    fn redact_text(&self, text: &str) -> String {
+        match self.redactor {
+            Some(ref redactor) => redactor.redact(text),
+            None => text.to_string(),
+        }
+    }
+
     /// Create a composite memory instance with session ID
     pub async fn with_session_id(session_id: String) -> Result<Self> {
         let mut config = CompositeMemoryConfig::default();
@@ -128,7 +184,9 @@ impl CompositeMemory {
     }
 
     /// Add message to memory
-    pub async fn add_message(&self, message: ChatMessage) -> Result<()> {
+    pub async fn add_message(&self, mut message: ChatMessage) -> Result<()> {
+        message.content = self.redact_text(&message.content);
+
         // Add to message history (always enabled)
         if let Some(ref history) = self.message_history {
             history.add_message(&message).await?;
@@ -221,6 +279,97 @@ impl CompositeMemory {
             Ok(None)
         }
     }
+
+    /// Export the session (history, summary and metadata) as a portable bundle
+    pub async fn export_session(&self) -> Result<MemoryExportBundle> {
+        let history = self.message_history.as_ref()
+            .ok_or_else(|| Error::msg("Message history is not enabled"))?;
+        let session_history = history.get_session_history().await?;
+
+        let summary = match self.summary_memory {
+            Some(ref summary) => {
+                let summary_data = summary.load_summary().await?;
+                summary_data.summary.is_some().then_some(summary_data)
+            }
+            None => None,
+        };
+
+        Ok(MemoryExportBundle {
+            version: MEMORY_EXPORT_VERSION,
+            session_id: session_history.session_id,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            created_at: session_history.created_at,
+            updated_at: session_history.updated_at,
+            messages: session_history.messages,
+            summary,
+            metadata: session_history.metadata,
+        })
+    }
+
+    /// Export the session and render it in the given format
+    pub async fn export_session_to_string(&self, format: MemoryExportFormat) -> Result<String> {
+        self.export_session().await?.render(format)
+    }
+
+    /// Export the session to a file in the given format
+    pub async fn export_session_to_file(&self, file_path: &Path, format: MemoryExportFormat) -> Result<()> {
+        let content = self.export_session_to_string(format).await?;
+        atomic_write_file(file_path, &content).await?;
+        info!("Exported session to {}", file_path.display());
+        Ok(())
+    }
+
+    /// Import a bundle into this session, replacing its history and summary
+    ///
+    /// The configured redactor (if any) is applied to the imported content before it is persisted.
+    pub async fn import_session(&self, mut bundle: MemoryExportBundle) -> Result<()> {
+        if let Some(ref redactor) = self.redactor {
+            bundle.redact(redactor.as_ref());
+        }
+
+        let history = self.message_history.as_ref()
+            .ok_or_else(|| Error::msg("Message history is not enabled"))?;
+        history.replace_messages(bundle.messages, bundle.metadata).await?;
+
+        if let Some(ref summary) = self.summary_memory {
+            match bundle.summary {
+                Some(summary_data) => summary.restore_summary(&summary_data).await?,
+                None => summary.restore_summary(&SummaryData::default()).await?,
+            }
+        }
+
+        self.memory_variables.write().await.clear();
+        info!("Imported session {} into {}", bundle.session_id, history.get_session_id());
+        Ok(())
+    }
+
+    /// Import a JSON or Markdown export file into this session
+    pub async fn import_session_from_file(&self, file_path: &Path) -> Result<()> {
+        let content = read_file_content(file_path).await?;
+        let bundle = MemoryExportBundle::parse(&content)?;
+        self.import_session(bundle).await
+    }
+
+    /// Scrub already persisted history and summary with the given redactor
+    ///
+    /// Returns the number of messages and summaries that were changed.
+    pub async fn redact_session(&self, redactor: &dyn Redactor) -> Result<usize> {
+        let mut bundle = self.export_session().await?;
+        let changed = bundle.redact(redactor);
+
+        if changed > 0 {
+            if let Some(ref history) = self.message_history {
+                history.replace_messages(bundle.messages, bundle.metadata).await?;
+            }
+            if let (Some(ref summary), Some(summary_data)) = (&self.summary_memory, bundle.summary) {
+                summary.restore_summary(&summary_data).await?;
+            }
+            self.memory_variables.write().await.clear();
+            warn!("Redacted {} entries in session {}", changed, bundle.session_id);
+        }
+
+        Ok(changed)
+    }
 }
 
 // Implement as_any method for CompositeMemory's BaseMemory trait
@@ -292,22 +441,24 @@ impl BaseMemory for CompositeMemory {
     fn save_context<'a>(&'a self, inputs: &'a HashMap<String, Value>, outputs: &'a HashMap<String, Value>) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
             // Extract input and output
-            let input = inputs.get("input")
+            let input = self.redact_text(inputs.get("input")
                 .and_then(|v| v.as_str())
-                .unwrap_or("");
+                .unwrap_or(""));
                 
-            let output = outputs.get("output")
+            let output = self.redact_text(outputs.get("output")
                 .and_then(|v| v.as_str())
-                .unwrap_or("");
+                .unwrap_or(""));
 
             // Create user message
-            if !input.is_empty() {
+            let parts = inputs.get("parts");
+            if !input.is_empty() || parts.is_some() {
                 let user_message = ChatMessage {
                     id: uuid::Uuid::new_v4().to_string(),
                     role: "user".to_string(),
                     content: input.to_string(),
                     timestamp: chrono::Utc::now().to_rfc3339(),
-                    metadata: None,
+                    // Image and file parts of multimodal messages
+                    metadata: parts.map(|parts| json!({ "parts": parts })),
                 };
                 
                 // Add directly to message history without triggering summary generation
@@ -349,12 +500,12 @@ impl BaseMemory for CompositeMemory {
             // Update internal memory variables
             let mut memory_vars = self.memory_variables.write().await;
             
-            if let Some(input_val) = inputs.get("input") {
-                memory_vars.insert("input".to_string(), input_val.clone());
+            if inputs.contains_key("input") {
+                memory_vars.insert("input".to_string(), json!(input));
             }
             
-            if let Some(output_val) = outputs.get("output") {
-                memory_vars.insert("output".to_string(), output_val.clone());
+            if outputs.contains_key("output") {
+                memory_vars.insert("output".to_string(), json!(output));
             }
 
             Ok(())
@@ -421,6 +572,8 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
     use crate::memory::message_history::ChatMessage;
+    use crate::memory::redaction::RegexRedactor;
+    use crate::memory::encryption::MemoryCipherError;
 
     #[tokio::test]
     async fn test_composite_memory_new() {
@@ -462,6 +615,53 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn test_multimodal_parts_persisted_and_sent_back() {
+        use crate::models::{FakeChatModel, FakeResponse};
+        use crate::test_utils::fake_agent;
+        use crate::{ContentPart, ModelChatMessage, Runnable};
+        use std::sync::Arc;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = CompositeMemoryConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            auto_generate_summary: false,
+            ..Default::default()
+        };
+        let memory = CompositeMemory::with_config(config).await.unwrap();
+
+        let model = Arc::new(FakeChatModel::new(vec![
+            FakeResponse::answer("A red mug"),
+            FakeResponse::answer("It costs 5 USDT"),
+        ]));
+        let mut agent = fake_agent("Shop assistant", &model);
+        agent.set_memory(Box::new(memory.clone()));
+
+        let image = ContentPart::image_url("https://market.example/mug.png");
+        let photo = ContentPart::image_base64("image/png", "iVBORw0KGgo=");
+        let parts = serde_json::to_string(&vec![image.clone(), photo.clone()]).unwrap();
+        agent.invoke(HashMap::from([("input".to_string(), "Describe this product".to_string()), ("parts".to_string(), parts)])).await.unwrap();
+        agent.invoke(HashMap::from([("input".to_string(), "How much is it?".to_string())])).await.unwrap();
+
+        // The image URL is persisted with the user message and sent again with the history,
+        // the inline image is sent once and replaced by a placeholder in the history
+        let first_request = &model.requests()[0];
+        assert!(matches!(first_request.last(), Some(ModelChatMessage::Human(content)) if content.parts == vec![image.clone(), photo.clone()]));
+        let stored = memory.get_recent_messages(10).await.unwrap();
+        assert_eq!(stored[0].metadata.as_ref().unwrap()["parts"][0]["url"], "https://market.example/mug.png");
+        assert!(!stored[0].metadata.as_ref().unwrap().to_string().contains("iVBORw0KGgo="));
+        let second_request = &model.requests()[1];
+        let history_parts: Vec<&ContentPart> = second_request.iter()
+            .filter_map(|message| match message {
+                ModelChatMessage::Human(content) => Some(&content.parts),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        assert_eq!(history_parts, vec![&image, &photo.for_history()]);
+        assert!(matches!(photo.for_history(), ContentPart::Text { text } if text.starts_with("[image image/png sha256:")));
+    }
+
     #[tokio::test]
     async fn test_save_context() {
         let temp_dir = TempDir::new().unwrap();
@@ -514,4 +714,95 @@ mod tests {
         let count = memory.get_message_count().await.unwrap();
         assert_eq!(count, 0);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = CompositeMemoryConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            auto_generate_summary: false, // Disable auto summary for testing
+            ..Default::default()
+        };
+        
+        let source = CompositeMemory::with_config(config.clone()).await.unwrap();
+        let mut inputs = HashMap::new();
+        inputs.insert("input".to_string(), json!("Hello"));
+        let mut outputs = HashMap::new();
+        outputs.insert("output".to_string(), json!("Hi there!"));
+        source.save_context(&inputs, &outputs).await.unwrap();
+        
+        let export_path = temp_dir.path().join("export.md");
+        source.export_session_to_file(&export_path, MemoryExportFormat::Markdown).await.unwrap();
+        
+        // Import into a fresh session
+        let target = CompositeMemory::with_config(config).await.unwrap();
+        target.import_session_from_file(&export_path).await.unwrap();
+        
+        let messages = target.get_recent_messages(10).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "Hello");
+        assert_eq!(messages[1].content, "Hi there!");
+    }
+
+    #[tokio::test]
+    async fn test_redaction_before_persisting_and_after() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = CompositeMemoryConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            auto_generate_summary: false, // Disable auto summary for testing
+            ..Default::default()
+        };
+        
+        let plain = CompositeMemory::with_config(config.clone()).await.unwrap();
+        let mut inputs = HashMap::new();
+        inputs.insert("input".to_string(), json!("My email is alice@example.com"));
+        plain.save_context(&inputs, &HashMap::new()).await.unwrap();
+        
+        // Scrub what was persisted without a redactor
+        let changed = plain.redact_session(&RegexRedactor::with_default_rules()).await.unwrap();
+        assert_eq!(changed, 1);
+        let messages = plain.get_recent_messages(10).await.unwrap();
+        assert_eq!(messages[0].content, "My email is [REDACTED:email]");
+        
+        // With a redactor configured, secrets never reach the history file
+        let redacting = CompositeMemory::with_config(config).await.unwrap()
+            .with_redactor(Arc::new(RegexRedactor::with_default_rules()));
+        redacting.save_context(&inputs, &HashMap::new()).await.unwrap();
+        let exported = redacting.export_session_to_string(MemoryExportFormat::Json).await.unwrap();
+        assert!(!exported.contains("alice@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_session_and_key_rotation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = CompositeMemoryConfig {
+            data_dir: temp_dir.path().to_path_buf(),
+            session_id: Some("encrypted_session".to_string()),
+            auto_generate_summary: false, // Disable auto summary for testing
+            ..Default::default()
+        };
+        let old_key = MemoryCipher::from_passphrase("old passphrase").unwrap();
+        
+        let memory = CompositeMemory::with_config_and_cipher(config.clone(), Some(old_key.clone())).await.unwrap();
+        let mut inputs = HashMap::new();
+        inputs.insert("input".to_string(), json!("secret wallet talk"));
+        memory.save_context(&inputs, &HashMap::new()).await.unwrap();
+        
+        let history_path = temp_dir.path().join("encrypted_session_history.jsonl");
+        let on_disk = tokio::fs::read_to_string(&history_path).await.unwrap();
+        assert!(!on_disk.contains("secret wallet talk"));
+        
+        // Wrong key and missing key are reported clearly
+        let wrong = MemoryCipher::from_passphrase("wrong").unwrap();
+        let err = CompositeMemory::with_config_and_cipher(config.clone(), Some(wrong)).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<MemoryCipherError>(), Some(&MemoryCipherError::WrongKey));
+        assert!(CompositeMemory::with_config(config.clone()).await.is_err());
+        
+        // Rotate to a new key and keep reading the same session
+        let new_key = MemoryCipher::from_passphrase("new passphrase").unwrap();
+        let rotated = memory.rotate_encryption_key(Some(new_key.clone())).await.unwrap();
+        assert_eq!(rotated.get_recent_messages(10).await.unwrap()[0].content, "secret wallet talk");
+        assert!(CompositeMemory::with_config_and_cipher(config.clone(), Some(old_key)).await.is_err());
+        assert!(CompositeMemory::with_config_and_cipher(config, Some(new_key)).await.is_ok());
+    }
+}
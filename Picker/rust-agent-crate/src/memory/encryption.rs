@@ -0,0 +1,402 @@
+// Encryption-at-rest for memory files (AES-256-GCM)
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use anyhow::{Error, Result};
+use base64::{engine::general_purpose, Engine as _};
+use log::info;
+use serde::{Serialize, Deserialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::memory::utils::{file_exists, read_file_content, write_file_content};
+
+/// Marker field identifying an encrypted memory file
+const ENVELOPE_MARKER: &str = "rust_agent_encrypted";
+/// Current envelope format version
+const ENVELOPE_VERSION: u32 = 1;
+/// PBKDF2 iteration count used for passphrase derived keys
+pub const PBKDF2_ITERATIONS: u32 = 100_000;
+/// Salt length in bytes for passphrase derived keys
+const SALT_LEN: usize = 16;
+
+/// Errors surfaced by the memory cipher, can be recovered with `anyhow::Error::downcast_ref`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryCipherError {
+    /// Decryption failed: the key is wrong or the file was tampered with
+    WrongKey,
+    /// The file is encrypted but no key was configured
+    MissingKey,
+    /// The envelope could not be parsed or uses an unsupported format
+    InvalidEnvelope(String),
+    /// The file is not encrypted although a key is configured and plaintext migration is off
+    Unencrypted,
+}
+
+impl fmt::Display for MemoryCipherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryCipherError::WrongKey => write!(f, "Failed to decrypt memory data: the encryption key is wrong or the data is corrupted"),
+            MemoryCipherError::MissingKey => write!(f, "Memory data is encrypted but no encryption key is configured"),
+            MemoryCipherError::InvalidEnvelope(reason) => write!(f, "Invalid encrypted memory envelope: {}", reason),
+            MemoryCipherError::Unencrypted => write!(f, "Memory data is not encrypted although an encryption key is configured"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryCipherError {}
+
+/// Key material for memory encryption
+#[derive(Clone)]
+pub enum MemoryEncryptionKey {
+    /// User passphrase, a key is derived once per cipher with PBKDF2-HMAC-SHA256 and a random salt
+    Passphrase(String),
+    /// Raw 256-bit key, e.g. a secret supplied by the OS keyring
+    Raw([u8; 32]),
+}
+
+// Never print key material
+impl fmt::Debug for MemoryEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryEncryptionKey::Passphrase(_) => write!(f, "Passphrase(***)"),
+            MemoryEncryptionKey::Raw(_) => write!(f, "Raw(***)"),
+        }
+    }
+}
+
+/// On-disk format of an encrypted memory file
+#[derive(Debug, Serialize, Deserialize)]
+struct EncryptedEnvelope {
+    rust_agent_encrypted: u32,
+    /// Key derivation: "pbkdf2-sha256" or "raw"
+    kdf: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iterations: Option<u32>,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// AES-256-GCM cipher for memory files
+#[derive(Clone)]
+pub struct MemoryCipher {
+    key: MemoryEncryptionKey,
+    /// Salt written to every envelope sealed by this cipher, `None` for raw keys
+    salt: Option<String>,
+    /// Derived keys by envelope salt and iterations, so PBKDF2 runs once per salt instead of once per file access
+    derived: Arc<Mutex<HashMap<String, [u8; 32]>>>,
+    allow_plaintext: bool,
+}
+
+// Never print key material
+impl fmt::Debug for MemoryCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryCipher")
+            .field("key", &self.key)
+            .field("allow_plaintext", &self.allow_plaintext)
+            .finish()
+    }
+}
+
+impl MemoryCipher {
+    /// Create a cipher from key material
+    pub fn new(key: MemoryEncryptionKey) -> Self {
+        let mut derived = HashMap::new();
+        let salt = match &key {
+            MemoryEncryptionKey::Passphrase(passphrase) => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                let encoded = general_purpose::STANDARD.encode(salt);
+                derived.insert(format!("{}:{}", encoded, PBKDF2_ITERATIONS), derive_key(passphrase, &salt, PBKDF2_ITERATIONS));
+                Some(encoded)
+            }
+            MemoryEncryptionKey::Raw(_) => None,
+        };
+        Self { key, salt, derived: Arc::new(Mutex::new(derived)), allow_plaintext: false }
+    }
+
+    /// Accept unencrypted files when opening, for migrating a plaintext data directory
+    ///
+    /// Off by default: with a key configured, a plaintext file fails with `MemoryCipherError::Unencrypted`.
+    pub fn with_plaintext_migration(mut self, allow: bool) -> Self {
+        self.allow_plaintext = allow;
+        self
+    }
+
+    /// Create a cipher from a user passphrase
+    pub fn from_passphrase(passphrase: &str) -> Result<Self> {
+        if passphrase.is_empty() {
+            return Err(Error::msg("Encryption passphrase must not be empty"));
+        }
+        Ok(Self::new(MemoryEncryptionKey::Passphrase(passphrase.to_string())))
+    }
+
+    /// Create a cipher from a raw 32-byte key
+    pub fn from_raw_key(key: &[u8]) -> Result<Self> {
+        let key: [u8; 32] = key.try_into()
+            .map_err(|_| Error::msg(format!("Encryption key must be 32 bytes, got {}", key.len())))?;
+        Ok(Self::new(MemoryEncryptionKey::Raw(key)))
+    }
+
+    /// Create a cipher from a base64 encoded 32-byte key (the usual keyring storage format)
+    pub fn from_base64_key(encoded: &str) -> Result<Self> {
+        let key = general_purpose::STANDARD.decode(encoded.trim())
+            .map_err(|e| Error::msg(format!("Invalid base64 encryption key: {}", e)))?;
+        Self::from_raw_key(&key)
+    }
+
+    /// Generate a random base64 encoded key suitable for storing in a keyring
+    pub fn generate_base64_key() -> String {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        general_purpose::STANDARD.encode(key)
+    }
+
+    /// Check whether stored content is an encrypted envelope
+    pub fn is_encrypted(content: &str) -> bool {
+        serde_json::from_str::<serde_json::Value>(content)
+            .map(|v| v.get(ENVELOPE_MARKER).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Encrypt plaintext into a serialized envelope
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let (key, kdf, salt, iterations) = match (&self.key, &self.salt) {
+            (MemoryEncryptionKey::Passphrase(passphrase), Some(salt)) => {
+                let key = self.passphrase_key(passphrase, salt, PBKDF2_ITERATIONS)?;
+                (key, "pbkdf2-sha256", Some(salt.clone()), Some(PBKDF2_ITERATIONS))
+            }
+            (MemoryEncryptionKey::Raw(key), _) => (*key, "raw", None, None),
+            (MemoryEncryptionKey::Passphrase(_), None) => return Err(Error::msg("Passphrase cipher has no salt")),
+        };
+
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| Error::msg(format!("AES-256-GCM key init failed: {:?}", e)))?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| Error::msg(format!("AES-256-GCM encrypt failed: {:?}", e)))?;
+
+        let envelope = EncryptedEnvelope {
+            rust_agent_encrypted: ENVELOPE_VERSION,
+            kdf: kdf.to_string(),
+            salt,
+            iterations,
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        };
+        Ok(serde_json::to_string(&envelope)?)
+    }
+
+    /// Decrypt a serialized envelope
+    pub fn decrypt(&self, content: &str) -> Result<String> {
+        let envelope: EncryptedEnvelope = serde_json::from_str(content)
+            .map_err(|e| MemoryCipherError::InvalidEnvelope(e.to_string()))?;
+        if envelope.rust_agent_encrypted > ENVELOPE_VERSION {
+            return Err(MemoryCipherError::InvalidEnvelope(format!("unsupported version {}", envelope.rust_agent_encrypted)).into());
+        }
+
+        let key = match (&self.key, envelope.kdf.as_str()) {
+            (MemoryEncryptionKey::Passphrase(passphrase), "pbkdf2-sha256") => {
+                let salt = envelope.salt.as_deref()
+                    .ok_or_else(|| MemoryCipherError::InvalidEnvelope("missing salt".to_string()))?;
+                let iterations = envelope.iterations
+                    .ok_or_else(|| MemoryCipherError::InvalidEnvelope("missing iterations".to_string()))?;
+                self.passphrase_key(passphrase, salt, iterations)?
+            }
+            (MemoryEncryptionKey::Raw(key), "raw") => *key,
+            // A passphrase can never open a raw-key file and vice versa
+            (_, "pbkdf2-sha256") | (_, "raw") => return Err(MemoryCipherError::WrongKey.into()),
+            (_, kdf) => return Err(MemoryCipherError::InvalidEnvelope(format!("unsupported kdf '{}'", kdf)).into()),
+        };
+
+        let nonce = decode_field(Some(&envelope.nonce), "nonce")?;
+        if nonce.len() != 12 {
+            return Err(MemoryCipherError::InvalidEnvelope("nonce must be 12 bytes".to_string()).into());
+        }
+        let ciphertext = decode_field(Some(&envelope.ciphertext), "ciphertext")?;
+
+        let cipher = Aes256Gcm::new_from_slice(&key)
+            .map_err(|e| Error::msg(format!("AES-256-GCM key init failed: {:?}", e)))?;
+        let plaintext = cipher.decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| MemoryCipherError::WrongKey)?;
+
+        String::from_utf8(plaintext)
+            .map_err(|e| MemoryCipherError::InvalidEnvelope(format!("plaintext is not valid UTF-8: {}", e)).into())
+    }
+
+    /// Get the key for a salt from the cache, deriving it on first use
+    fn passphrase_key(&self, passphrase: &str, salt: &str, iterations: u32) -> Result<[u8; 32]> {
+        let cache_key = format!("{}:{}", salt, iterations);
+        if let Some(key) = self.derived.lock().map_err(|_| Error::msg("Key cache lock poisoned"))?.get(&cache_key) {
+            return Ok(*key);
+        }
+        let key = derive_key(passphrase, &decode_field(Some(salt), "salt")?, iterations);
+        self.derived.lock().map_err(|_| Error::msg("Key cache lock poisoned"))?.insert(cache_key, key);
+        Ok(key)
+    }
+}
+
+/// Encode content for storage, encrypting it when a cipher is configured
+pub fn seal_content(cipher: Option<&MemoryCipher>, plaintext: &str) -> Result<String> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(plaintext),
+        None => Ok(plaintext.to_string()),
+    }
+}
+
+/// Decode stored content, decrypting it when it is an encrypted envelope
+///
+/// With a cipher configured, plaintext content is rejected unless the cipher allows plaintext migration,
+/// in which case it is returned unchanged and encrypted on the next save.
+pub fn open_content(cipher: Option<&MemoryCipher>, content: &str) -> Result<String> {
+    if !MemoryCipher::is_encrypted(content) {
+        return match cipher {
+            Some(cipher) if !cipher.allow_plaintext && !content.trim().is_empty() => Err(MemoryCipherError::Unencrypted.into()),
+            _ => Ok(content.to_string()),
+        };
+    }
+    match cipher {
+        Some(cipher) => cipher.decrypt(content),
+        None => Err(MemoryCipherError::MissingKey.into()),
+    }
+}
+
+/// Re-encrypt the memory files of every session in a data directory
+///
+/// `old` and `new` may be `None` to encrypt a plaintext directory or to decrypt an encrypted one.
+/// All files are decrypted and re-sealed into temporary files before any is renamed over the original,
+/// so a wrong `old` key or a failed write leaves the directory untouched.
+/// Returns the number of rewritten files.
+pub async fn rotate_memory_encryption(data_dir: &Path, old: Option<&MemoryCipher>, new: Option<&MemoryCipher>) -> Result<usize> {
+    if !file_exists(data_dir).await {
+        return Ok(0);
+    }
+
+    let mut decrypted = Vec::new();
+    let mut entries = tokio::fs::read_dir(data_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !(name.ends_with("_history.jsonl") || name.ends_with("_summary.json")) {
+            continue;
+        }
+
+        let content = read_file_content(&path).await?;
+        let plaintext = open_content(old, &content)
+            .map_err(|e| Error::msg(format!("Failed to open '{}': {}", path.display(), e)))?;
+        decrypted.push((path, plaintext));
+    }
+
+    let mut staged: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (path, plaintext) in &decrypted {
+        let mut temp_name = path.as_os_str().to_owned();
+        temp_name.push(".rotate");
+        let temp_path = PathBuf::from(temp_name);
+        let written = match seal_content(new, plaintext) {
+            Ok(sealed) => write_file_content(&temp_path, &sealed).await,
+            Err(e) => Err(e),
+        };
+        staged.push((temp_path, path.clone()));
+        if let Err(e) = written {
+            for (temp_path, _) in &staged {
+                let _ = tokio::fs::remove_file(temp_path).await;
+            }
+            return Err(e);
+        }
+    }
+    for (temp_path, path) in &staged {
+        tokio::fs::rename(temp_path, path).await?;
+    }
+
+    info!("Rotated encryption of {} memory files in {}", decrypted.len(), data_dir.display());
+    Ok(decrypted.len())
+}
+
+/// Derive a 256-bit key from a passphrase
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+/// Decode a base64 envelope field
+fn decode_field(value: Option<&str>, field: &str) -> Result<Vec<u8>> {
+    let value = value.ok_or_else(|| MemoryCipherError::InvalidEnvelope(format!("missing {}", field)))?;
+    general_purpose::STANDARD.decode(value)
+        .map_err(|e| MemoryCipherError::InvalidEnvelope(format!("invalid {}: {}", field, e)).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_passphrase_round_trip_and_wrong_key() {
+        let cipher = MemoryCipher::from_passphrase("correct horse").unwrap();
+        let sealed = cipher.encrypt("{\"messages\":[]}").unwrap();
+
+        assert!(MemoryCipher::is_encrypted(&sealed));
+        assert!(!sealed.contains("messages"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "{\"messages\":[]}");
+
+        // The key is derived once per cipher and its salt is reused in every envelope
+        let second = cipher.encrypt("{}").unwrap();
+        let salt = |sealed: &str| serde_json::from_str::<EncryptedEnvelope>(sealed).unwrap().salt;
+        assert_eq!(salt(&sealed), salt(&second));
+        // Another cipher with the same passphrase opens it by deriving the key for that salt
+        let other = MemoryCipher::from_passphrase("correct horse").unwrap();
+        assert_ne!(salt(&other.encrypt("{}").unwrap()), salt(&sealed));
+        assert_eq!(other.decrypt(&second).unwrap(), "{}");
+
+        let wrong = MemoryCipher::from_passphrase("battery staple").unwrap();
+        let err = wrong.decrypt(&sealed).unwrap_err();
+        assert_eq!(err.downcast_ref::<MemoryCipherError>(), Some(&MemoryCipherError::WrongKey));
+    }
+
+    #[test]
+    fn test_raw_key_and_missing_key() {
+        let cipher = MemoryCipher::from_base64_key(&MemoryCipher::generate_base64_key()).unwrap();
+        let sealed = seal_content(Some(&cipher), "hello").unwrap();
+        assert_eq!(open_content(Some(&cipher), &sealed).unwrap(), "hello");
+
+        let err = open_content(None, &sealed).unwrap_err();
+        assert_eq!(err.downcast_ref::<MemoryCipherError>(), Some(&MemoryCipherError::MissingKey));
+
+        // Plaintext is rejected unless migrating a plaintext directory
+        let err = open_content(Some(&cipher), "{\"a\":1}").unwrap_err();
+        assert_eq!(err.downcast_ref::<MemoryCipherError>(), Some(&MemoryCipherError::Unencrypted));
+        let migrating = cipher.clone().with_plaintext_migration(true);
+        assert_eq!(open_content(Some(&migrating), "{\"a\":1}").unwrap(), "{\"a\":1}");
+        assert!(MemoryCipher::from_raw_key(&[0u8; 16]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_memory_encryption() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("s1_history.jsonl");
+        tokio::fs::write(&path, "{\"session_id\":\"s1\"}").await.unwrap();
+
+        let old = MemoryCipher::from_passphrase("old").unwrap();
+        let new = MemoryCipher::from_passphrase("new").unwrap();
+
+        assert_eq!(rotate_memory_encryption(temp_dir.path(), None, Some(&old)).await.unwrap(), 1);
+        assert!(rotate_memory_encryption(temp_dir.path(), Some(&new), Some(&new)).await.is_err());
+        assert_eq!(rotate_memory_encryption(temp_dir.path(), Some(&old), Some(&new)).await.unwrap(), 1);
+
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        assert_eq!(new.decrypt(&content).unwrap(), "{\"session_id\":\"s1\"}");
+        // Staged files are renamed over the originals, none are left behind
+        let mut entries = tokio::fs::read_dir(temp_dir.path()).await.unwrap();
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            names.push(entry.file_name().to_string_lossy().to_string());
+        }
+        assert_eq!(names, vec!["s1_history.jsonl".to_string()]);
+    }
+}
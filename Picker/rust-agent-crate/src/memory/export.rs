@@ -0,0 +1,218 @@
+// Portable session export bundle (JSON and Markdown)
+use anyhow::{Error, Result};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::memory::message_history::ChatMessageRecord;
+use crate::memory::redaction::Redactor;
+use crate::memory::summary::SummaryData;
+
+/// Current export bundle format version
+pub const MEMORY_EXPORT_VERSION: u32 = 1;
+
+/// Marker preceding the embedded JSON bundle in Markdown exports
+const MARKDOWN_BUNDLE_HEADING: &str = "## Bundle";
+
+/// Export output format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryExportFormat {
+    /// Pretty-printed JSON bundle
+    Json,
+    /// Human readable transcript with the JSON bundle embedded, so it can be imported back
+    Markdown,
+}
+
+/// A self-contained snapshot of one memory session: history, summary and metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryExportBundle {
+    /// Bundle format version
+    pub version: u32,
+    /// Session ID of the exported session
+    pub session_id: String,
+    /// Export time (ISO 8601 format)
+    pub exported_at: String,
+    /// Session creation time
+    pub created_at: String,
+    /// Session last update time
+    pub updated_at: String,
+    /// Message list (in chronological order)
+    pub messages: Vec<ChatMessageRecord>,
+    /// Conversation summary (if any)
+    pub summary: Option<SummaryData>,
+    /// Session-level metadata
+    pub metadata: Option<HashMap<String, Value>>,
+}
+
+impl MemoryExportBundle {
+    /// Serialize the bundle in the given format
+    pub fn render(&self, format: MemoryExportFormat) -> Result<String> {
+        match format {
+            MemoryExportFormat::Json => self.to_json(),
+            MemoryExportFormat::Markdown => self.to_markdown(),
+        }
+    }
+
+    /// Serialize the bundle as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Render the bundle as a Markdown transcript
+    pub fn to_markdown(&self) -> Result<String> {
+        let mut markdown = String::new();
+        markdown.push_str(&format!("# Conversation {}\n\n", self.session_id));
+        markdown.push_str(&format!("- Created: {}\n", self.created_at));
+        markdown.push_str(&format!("- Updated: {}\n", self.updated_at));
+        markdown.push_str(&format!("- Exported: {}\n", self.exported_at));
+        markdown.push_str(&format!("- Messages: {}\n\n", self.messages.len()));
+
+        if let Some(summary) = self.summary.as_ref().and_then(|s| s.summary.as_ref()) {
+            markdown.push_str("## Summary\n\n");
+            markdown.push_str(summary);
+            markdown.push_str("\n\n");
+        }
+
+        markdown.push_str("## Messages\n\n");
+        for message in &self.messages {
+            markdown.push_str(&format!("### {} ({})\n\n{}\n\n", message.role, message.timestamp, message.content));
+        }
+
+        markdown.push_str(MARKDOWN_BUNDLE_HEADING);
+        markdown.push_str("\n\n```json\n");
+        markdown.push_str(&self.to_json()?);
+        markdown.push_str("\n```\n");
+
+        Ok(markdown)
+    }
+
+    /// Parse a bundle from either a JSON export or a Markdown export
+    pub fn parse(content: &str) -> Result<Self> {
+        let trimmed = content.trim_start();
+        let json = if trimmed.starts_with('{') {
+            trimmed
+        } else {
+            Self::extract_markdown_bundle(content)
+                .ok_or_else(|| Error::msg("No embedded JSON bundle found in Markdown export"))?
+        };
+
+        let bundle: MemoryExportBundle = serde_json::from_str(json)
+            .map_err(|e| Error::msg(format!("Invalid memory export bundle: {}", e)))?;
+
+        if bundle.version > MEMORY_EXPORT_VERSION {
+            return Err(Error::msg(format!(
+                "Unsupported memory export version {} (max supported: {})",
+                bundle.version, MEMORY_EXPORT_VERSION
+            )));
+        }
+
+        Ok(bundle)
+    }
+
+    /// Apply a redactor to message contents and the summary, returns the number of changed entries
+    pub fn redact(&mut self, redactor: &dyn Redactor) -> usize {
+        let mut changed = 0;
+        for message in &mut self.messages {
+            let redacted = redactor.redact(&message.content);
+            if redacted != message.content {
+                message.content = redacted;
+                changed += 1;
+            }
+        }
+
+        if let Some(summary) = self.summary.as_mut().and_then(|s| s.summary.as_mut()) {
+            let redacted = redactor.redact(summary);
+            if redacted != *summary {
+                *summary = redacted;
+                changed += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// Find the JSON code block following the bundle heading
+    fn extract_markdown_bundle(content: &str) -> Option<&str> {
+        let after_heading = &content[content.rfind(MARKDOWN_BUNDLE_HEADING)?..];
+        let start = after_heading.find("```json")? + "```json".len();
+        let block = &after_heading[start..];
+        let end = block.rfind("```")?;
+        Some(block[..end].trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::redaction::RegexRedactor;
+
+    fn sample_bundle() -> MemoryExportBundle {
+        let now = chrono::Utc::now().to_rfc3339();
+        MemoryExportBundle {
+            version: MEMORY_EXPORT_VERSION,
+            session_id: "session_1".to_string(),
+            exported_at: now.clone(),
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            messages: vec![
+                ChatMessageRecord {
+                    role: "user".to_string(),
+                    content: "Contact me at bob@example.com".to_string(),
+                    name: None,
+                    additional_kwargs: None,
+                    timestamp: now.clone(),
+                    sequence_number: 1,
+                },
+                ChatMessageRecord {
+                    role: "assistant".to_string(),
+                    content: "Sure".to_string(),
+                    name: None,
+                    additional_kwargs: None,
+                    timestamp: now.clone(),
+                    sequence_number: 2,
+                },
+            ],
+            summary: Some(SummaryData {
+                session_id: "session_1".to_string(),
+                sequence_number: 2,
+                summary: Some("User shared bob@example.com".to_string()),
+                token_count: 5,
+                last_updated: now,
+            }),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_json_and_markdown_round_trip() {
+        let bundle = sample_bundle();
+
+        for format in [MemoryExportFormat::Json, MemoryExportFormat::Markdown] {
+            let rendered = bundle.render(format).unwrap();
+            let parsed = MemoryExportBundle::parse(&rendered).unwrap();
+            assert_eq!(parsed.session_id, bundle.session_id);
+            assert_eq!(parsed.messages.len(), 2);
+            assert_eq!(parsed.messages[0].content, bundle.messages[0].content);
+            assert_eq!(parsed.summary.unwrap().summary, bundle.summary.as_ref().unwrap().summary);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        assert!(MemoryExportBundle::parse("# Just a transcript").is_err());
+
+        let mut bundle = sample_bundle();
+        bundle.version = MEMORY_EXPORT_VERSION + 1;
+        assert!(MemoryExportBundle::parse(&bundle.to_json().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_redact_bundle() {
+        let mut bundle = sample_bundle();
+        let changed = bundle.redact(&RegexRedactor::with_default_rules());
+
+        assert_eq!(changed, 2);
+        assert!(!bundle.to_json().unwrap().contains("bob@example.com"));
+    }
+}
@@ -13,6 +13,8 @@ use std::future::Future;
 use log::{info, warn};
 use chrono::Utc;
 
+use crate::memory::encryption::{MemoryCipher, open_content, seal_content};
+
 // Chat message structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -67,6 +69,8 @@ pub struct FileChatMessageHistory {
     session_history: Arc<RwLock<ChatSessionHistory>>,
     /// Next message sequence number
     next_sequence_number: Arc<RwLock<u64>>,
+    /// Cipher used to encrypt the file at rest (optional)
+    cipher: Option<Arc<MemoryCipher>>,
 }
 
 impl Clone for FileChatMessageHistory {
@@ -76,6 +80,7 @@ impl Clone for FileChatMessageHistory {
             file_path: self.file_path.clone(),
             session_history: Arc::clone(&self.session_history),
             next_sequence_number: Arc::clone(&self.next_sequence_number),
+            cipher: self.cipher.clone(),
         }
     }
 }
@@ -83,6 +88,11 @@ impl Clone for FileChatMessageHistory {
 impl FileChatMessageHistory {
     /// Create a new file message history instance
     pub async fn new(session_id: String, file_path: PathBuf) -> Result<Self> {
+        Self::new_with_cipher(session_id, file_path, None).await
+    }
+    
+    /// Create a new file message history instance, encrypting the file at rest when a cipher is given
+    pub async fn new_with_cipher(session_id: String, file_path: PathBuf, cipher: Option<Arc<MemoryCipher>>) -> Result<Self> {
         // Ensure parent directory exists
         if let Some(parent) = file_path.parent() {
             tokio::fs::create_dir_all(parent).await?;
@@ -103,6 +113,7 @@ impl FileChatMessageHistory {
             file_path: file_path.clone(),
             session_history: Arc::new(RwLock::new(session_history)),
             next_sequence_number: Arc::new(RwLock::new(1)),
+            cipher,
         };
         
         // Try to load existing session history
@@ -122,6 +133,9 @@ impl FileChatMessageHistory {
         let mut contents = String::new();
         file.read_to_string(&mut contents).await?;
         
+        // Decrypt if the file is encrypted (fails with a clear error on a wrong or missing key)
+        let contents = open_content(self.cipher.as_deref(), &contents)?;
+        
         if contents.trim().is_empty() {
             return Ok(());
         }
@@ -258,7 +272,7 @@ impl FileChatMessageHistory {
             let mut file = File::create(&temp_path).await?;
             
             // Write entire session history as a JSON object to file
-            let json_content = serde_json::to_string_pretty(&history)?;
+            let json_content = seal_content(self.cipher.as_deref(), &serde_json::to_string_pretty(&history)?)?;
             file.write_all(json_content.as_bytes()).await?;
             
             file.flush().await?;
@@ -424,12 +438,17 @@ impl MessageHistoryMemory {
     
     /// Create a new MessageHistoryMemory instance with specified recent message count
     pub async fn new_with_recent_count(session_id: String, data_dir: PathBuf, recent_count: usize) -> Result<Self> {
+        Self::new_with_cipher(session_id, data_dir, recent_count, None).await
+    }
+    
+    /// Create a new MessageHistoryMemory instance whose history file is encrypted at rest
+    pub async fn new_with_cipher(session_id: String, data_dir: PathBuf, recent_count: usize, cipher: Option<Arc<MemoryCipher>>) -> Result<Self> {
         // Ensure data directory exists
         tokio::fs::create_dir_all(&data_dir).await?;
         
         // Create file message history, using JSONL format
         let file_path = data_dir.join(format!("{}_history.jsonl", session_id));
-        let chat_history = FileChatMessageHistory::new(session_id.clone(), file_path).await?;
+        let chat_history = FileChatMessageHistory::new_with_cipher(session_id.clone(), file_path, cipher).await?;
         
         Ok(Self {
             session_id,
@@ -501,8 +520,9 @@ impl MessageHistoryMemory {
     
     /// Add ChatMessage to history
     pub async fn add_message(&self, message: &ChatMessage) -> Result<()> {
-        // Check if message content is empty
-        if message.content.trim().is_empty() {
+        // Check if message content is empty, messages with only attachments are kept
+        let has_parts = message.metadata.as_ref().and_then(|m| m.get("parts")).is_some();
+        if message.content.trim().is_empty() && !has_parts {
             return Ok(());
         }
         
@@ -536,6 +556,39 @@ impl MessageHistoryMemory {
         Ok(())
     }
     
+    /// Get a snapshot of the whole session history, including session metadata
+    pub async fn get_session_history(&self) -> Result<ChatSessionHistory> {
+        let history = self.chat_history.session_history.read().await;
+        Ok(history.clone())
+    }
+
+    /// Replace all messages and session metadata, e.g. when importing or redacting a session
+    pub async fn replace_messages(&self, messages: Vec<ChatMessageRecord>, metadata: Option<HashMap<String, serde_json::Value>>) -> Result<()> {
+        let next_sequence_number = messages.iter()
+            .map(|m| m.sequence_number)
+            .max()
+            .unwrap_or(0) + 1;
+
+        // Update session history
+        {
+            let mut history = self.chat_history.session_history.write().await;
+            history.messages = messages;
+            history.metadata = metadata;
+            history.updated_at = Utc::now().to_rfc3339();
+        }
+
+        // Continue numbering after the imported messages
+        {
+            let mut next_sequence = self.chat_history.next_sequence_number.write().await;
+            *next_sequence = next_sequence_number;
+        }
+
+        // Save to file
+        self.chat_history.save_session_history().await?;
+
+        Ok(())
+    }
+
     /// Get the most recent N messages, return ChatMessage type
     pub async fn get_recent_chat_messages(&self, count: usize) -> Result<Vec<ChatMessage>> {
         let records = self.get_recent_messages(count).await?;
@@ -604,7 +657,19 @@ impl BaseMemory for MessageHistoryMemory {
             // Save user message
             if let Some(input_value) = inputs.get("input") {
                 if let Some(content) = input_value.as_str() {
-                    self.chat_history.add_user_message(content.to_string()).await?;
+                    match inputs.get("parts") {
+                        // Keep image and file parts with the message, they come back in load_memory_variables
+                        Some(parts) => {
+                            self.add_message(&ChatMessage {
+                                id: uuid::Uuid::new_v4().to_string(),
+                                role: "user".to_string(),
+                                content: content.to_string(),
+                                timestamp: Utc::now().to_rfc3339(),
+                                metadata: Some(serde_json::json!({ "parts": parts })),
+                            }).await?;
+                        }
+                        None => self.chat_history.add_user_message(content.to_string()).await?,
+                    }
                 }
             }
             
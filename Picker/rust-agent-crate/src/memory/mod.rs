@@ -4,10 +4,16 @@ pub mod message_history;
 pub mod summary;
 pub mod utils;
 pub mod composite_memory;
+pub mod export;
+pub mod redaction;
+pub mod encryption;
 
 // Export main types and traits
 pub use base::{BaseMemory, SimpleMemory, MemoryVariables};
 pub use message_history::{MessageHistoryMemory, ChatMessage, ChatMessageRecord};
 pub use summary::{SummaryMemory, SummaryData};
 pub use utils::*;
-pub use composite_memory::{CompositeMemory, CompositeMemoryConfig};
\ No newline at end of file
+pub use composite_memory::{CompositeMemory, CompositeMemoryConfig};
+pub use export::{MemoryExportBundle, MemoryExportFormat, MEMORY_EXPORT_VERSION};
+pub use redaction::{Redactor, RegexRedactor, RedactionRule};
+pub use encryption::{MemoryCipher, MemoryCipherError, MemoryEncryptionKey, rotate_memory_encryption};
\ No newline at end of file
@@ -0,0 +1,268 @@
+// Redaction of secrets in conversation content before it is persisted or summarized
+use anyhow::{Error, Result};
+use regex::{Captures, Regex};
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// Raw EVM private key: 64 hex characters, optionally prefixed with `0x`.
+/// Transaction hashes share this shape and are redacted as well.
+pub const EVM_PRIVATE_KEY_PATTERN: &str = r"\b(?:0x)?[0-9a-fA-F]{64}\b";
+/// Candidate runs for BIP-39 mnemonics: 12 or more consecutive words of 3-8 letters in any case,
+/// separated by whitespace or commas. Only stretches of wordlist words are redacted, see [`mnemonic_spans`].
+pub const MNEMONIC_PATTERN: &str = r"(?i)\b(?:[a-z]{3,8}[\s,]+){11,}[a-z]{3,8}\b";
+/// Email address
+pub const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b";
+/// EVM account address (not part of the default rule set)
+pub const EVM_ADDRESS_PATTERN: &str = r"\b0x[0-9a-fA-F]{40}\b";
+
+/// Redaction pass applied to message text
+///
+/// Implementations must be deterministic so that redacting already redacted text is a no-op.
+pub trait Redactor: Send + Sync + Debug {
+    /// Return a copy of `text` with sensitive content replaced
+    fn redact(&self, text: &str) -> String;
+}
+
+/// Selects the byte ranges of a rule match to replace
+pub type SpanSelector = fn(&str) -> Vec<Range<usize>>;
+
+/// A single named redaction rule
+#[derive(Debug, Clone)]
+pub struct RedactionRule {
+    /// Rule name, used in logs and default replacements
+    pub name: String,
+    /// Compiled pattern
+    pub pattern: Regex,
+    /// Replacement text
+    pub replacement: String,
+    /// Selects the byte ranges of a match to replace; the whole match is replaced when unset
+    pub spans: Option<SpanSelector>,
+}
+
+impl RedactionRule {
+    /// Create a rule from a regex pattern; the replacement defaults to `[REDACTED:<name>]`
+    pub fn new(name: &str, pattern: &str) -> Result<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| Error::msg(format!("Invalid redaction pattern for rule '{}': {}", name, e)))?;
+        Ok(Self {
+            name: name.to_string(),
+            pattern,
+            replacement: format!("[REDACTED:{}]", name),
+            spans: None,
+        })
+    }
+
+    /// Only replace the parts of each match selected by `spans`
+    pub fn with_spans(mut self, spans: SpanSelector) -> Self {
+        self.spans = Some(spans);
+        self
+    }
+
+    fn replace_all(&self, text: &str) -> String {
+        let Some(spans) = self.spans else {
+            return self.pattern.replace_all(text, self.replacement.as_str()).into_owned();
+        };
+        self.pattern
+            .replace_all(text, |caps: &Captures| {
+                let matched = &caps[0];
+                let mut replaced = String::with_capacity(matched.len());
+                let mut last = 0;
+                for span in spans(matched) {
+                    replaced.push_str(&matched[last..span.start]);
+                    replaced.push_str(&self.replacement);
+                    last = span.end;
+                }
+                replaced.push_str(&matched[last..]);
+                replaced
+            })
+            .into_owned()
+    }
+
+    /// Set a custom replacement text
+    pub fn with_replacement(mut self, replacement: String) -> Self {
+        self.replacement = replacement;
+        self
+    }
+}
+
+/// Shortest BIP-39 mnemonic; longer stretches are redacted whole, so a phrase with extra or missing words still hides
+const MNEMONIC_MIN_WORDS: usize = 12;
+
+fn bip39_words() -> &'static HashSet<&'static str> {
+    static WORDS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| include_str!("bip39_english.txt").lines().collect())
+}
+
+/// Byte ranges of `text` that look like a BIP-39 mnemonic: maximal stretches of at least 12 consecutive
+/// English wordlist words, compared case-insensitively and separated by whitespace or commas.
+/// Ordinary prose is left alone because it rarely consists only of wordlist words.
+pub fn mnemonic_spans(text: &str) -> Vec<Range<usize>> {
+    let words = bip39_words();
+    let mut spans = Vec::new();
+    let mut stretch: Option<(Range<usize>, usize)> = None;
+    let mut close = |stretch: &mut Option<(Range<usize>, usize)>| {
+        if let Some((span, count)) = stretch.take() {
+            if count >= MNEMONIC_MIN_WORDS {
+                spans.push(span);
+            }
+        }
+    };
+
+    for word in text.split(|c: char| c.is_whitespace() || c == ',').filter(|w| !w.is_empty()) {
+        let start = word.as_ptr() as usize - text.as_ptr() as usize;
+        let end = start + word.len();
+        if words.contains(word.to_ascii_lowercase().as_str()) {
+            match stretch.as_mut() {
+                Some((span, count)) => {
+                    span.end = end;
+                    *count += 1;
+                }
+                None => stretch = Some((start..end, 1)),
+            }
+        } else {
+            close(&mut stretch);
+        }
+    }
+    close(&mut stretch);
+    spans
+}
+
+/// Regex based redactor, rules are applied in insertion order
+#[derive(Debug, Clone, Default)]
+pub struct RegexRedactor {
+    rules: Vec<RedactionRule>,
+}
+
+impl RegexRedactor {
+    /// Create a redactor without any rules
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Create a redactor with rules for EVM private keys, mnemonics and email addresses
+    pub fn with_default_rules() -> Self {
+        Self::new()
+            .with_rule(RedactionRule::new("private_key", EVM_PRIVATE_KEY_PATTERN).expect("valid built-in pattern"))
+            .with_rule(
+                RedactionRule::new("mnemonic", MNEMONIC_PATTERN)
+                    .expect("valid built-in pattern")
+                    .with_spans(mnemonic_spans),
+            )
+            .with_rule(RedactionRule::new("email", EMAIL_PATTERN).expect("valid built-in pattern"))
+    }
+
+    /// Add a rule
+    pub fn with_rule(mut self, rule: RedactionRule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Add a rule from a name and a regex pattern
+    pub fn with_pattern(self, name: &str, pattern: &str) -> Result<Self> {
+        Ok(self.with_rule(RedactionRule::new(name, pattern)?))
+    }
+
+    /// Get the configured rules
+    pub fn rules(&self) -> &[RedactionRule] {
+        &self.rules
+    }
+}
+
+impl Redactor for RegexRedactor {
+    fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for rule in &self.rules {
+            if rule.pattern.is_match(&redacted) {
+                log::debug!("Redaction rule '{}' matched", rule.name);
+                redacted = rule.replace_all(&redacted);
+            }
+        }
+        redacted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_redact_secrets() {
+        let redactor = RegexRedactor::with_default_rules();
+        let key = format!("0x{}", "ab".repeat(32));
+        let text = format!(
+            "my key is {} and my seed is abandon ability able about above absent absorb abstract absurd abuse access accident, mail me at alice@example.com",
+            key
+        );
+
+        let redacted = redactor.redact(&text);
+        assert!(!redacted.contains(&key));
+        assert!(!redacted.contains("abandon ability"));
+        assert!(!redacted.contains("alice@example.com"));
+        assert!(redacted.contains("[REDACTED:private_key]"));
+        assert!(redacted.contains("[REDACTED:mnemonic]"));
+        assert!(redacted.contains("[REDACTED:email]"));
+
+        // Redacting twice is a no-op
+        assert_eq!(redactor.redact(&redacted), redacted);
+    }
+
+    #[test]
+    fn test_default_rules_keep_addresses_and_prose() {
+        let redactor = RegexRedactor::with_default_rules();
+        let text = "Transfer 1 USDT to 0x1234567890abcdef1234567890abcdef12345678 please";
+        assert_eq!(redactor.redact(text), text);
+    }
+
+    #[test]
+    fn test_mnemonic_rule_ignores_prose() {
+        let redactor = RegexRedactor::with_default_rules();
+        // 12+ short lowercase words, but not all from the BIP-39 wordlist
+        let text = "please check that the bridge sends the funds back to my main wallet before the end of the week";
+        assert_eq!(redactor.redact(text), text);
+
+        // 11 wordlist words are too short for a mnemonic
+        let text = "abandon ability able about above absent absorb abstract absurd abuse access";
+        assert_eq!(redactor.redact(text), text);
+
+        // A mnemonic next to prose only redacts the mnemonic
+        let text = "my seed is zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong, is it ok?";
+        assert_eq!(redactor.redact(text), "my seed is [REDACTED:mnemonic], is it ok?");
+    }
+
+    #[test]
+    fn test_mnemonic_rule_redacts_any_long_run() {
+        let redactor = RegexRedactor::with_default_rules();
+        // 13 words is not a valid mnemonic length but may be a phrase with a typo or an extra word
+        let text = "abandon ability able about above absent absorb abstract absurd abuse access accident account";
+        assert_eq!(redactor.redact(text), "[REDACTED:mnemonic]");
+    }
+
+    #[test]
+    fn test_mnemonic_rule_normalizes_case() {
+        let redactor = RegexRedactor::with_default_rules();
+        let text = "seed: Abandon ABILITY able About above absent absorb abstract absurd abuse access Accident";
+        assert_eq!(redactor.redact(text), "seed: [REDACTED:mnemonic]");
+    }
+
+    #[test]
+    fn test_mnemonic_rule_accepts_comma_separators() {
+        let redactor = RegexRedactor::with_default_rules();
+        let text = "seed: abandon, ability, able, about, above, absent, absorb, abstract, absurd, abuse, access, accident. done";
+        assert_eq!(redactor.redact(text), "seed: [REDACTED:mnemonic]. done");
+        let text = "seed: abandon,ability,able,about,above,absent,absorb,abstract,absurd,abuse,access,accident";
+        assert_eq!(redactor.redact(text), "seed: [REDACTED:mnemonic]");
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        let redactor = RegexRedactor::new()
+            .with_pattern("address", EVM_ADDRESS_PATTERN)
+            .unwrap();
+        let redacted = redactor.redact("to 0x1234567890abcdef1234567890abcdef12345678");
+        assert_eq!(redacted, "to [REDACTED:address]");
+
+        assert!(RegexRedactor::new().with_pattern("broken", "(").is_err());
+    }
+}
@@ -16,6 +16,8 @@ use std::sync::Arc;
 use crate::memory::message_history::{FileChatMessageHistory, ChatMessageRecord, MessageHistoryMemory};
 // Import utility functions
 use crate::memory::utils::estimate_text_tokens;
+use crate::memory::redaction::Redactor;
+use crate::memory::encryption::{MemoryCipher, open_content, seal_content};
 // Import common models
 use crate::{ChatModel, OpenAIChatModel, ModelChatMessage, ChatMessageContent};
 
@@ -65,6 +67,10 @@ pub struct SummaryMemory {
     recent_messages_count: usize,
     /// Shared message history memory (optional)
     message_history: Option<Arc<MessageHistoryMemory>>,
+    /// Redactor applied to the conversation before it is sent for summarization (optional)
+    redactor: Option<Arc<dyn Redactor>>,
+    /// Cipher used to encrypt summary and history files at rest (optional)
+    cipher: Option<Arc<MemoryCipher>>,
 }
 
 impl Clone for SummaryMemory {
@@ -76,6 +82,8 @@ impl Clone for SummaryMemory {
             summary_prompt_template: self.summary_prompt_template.clone(),
             recent_messages_count: self.recent_messages_count,
             message_history: self.message_history.clone(),
+            redactor: self.redactor.clone(),
+            cipher: self.cipher.clone(),
         }
     }
 }
@@ -93,6 +101,8 @@ impl SummaryMemory {
             summary_prompt_template: "Please provide a concise summary of the following conversation. Focus on the main topics discussed, key decisions made, and any important outcomes.\n\nConversation:\n{chat_history}\n\nSummary:".to_string(),
             recent_messages_count: crate::memory::utils::get_recent_messages_count_from_env(),
             message_history: None,
+            redactor: None,
+            cipher: None,
         })
     }
     
@@ -113,6 +123,8 @@ impl SummaryMemory {
             summary_prompt_template: "Please provide a concise summary of the following conversation. Focus on the main topics discussed, key decisions made, and any important outcomes.\n\nConversation:\n{chat_history}\n\nSummary:".to_string(),
             recent_messages_count: crate::memory::utils::get_recent_messages_count_from_env(),
             message_history: Some(message_history),
+            redactor: None,
+            cipher: None,
         })
     }
     
@@ -128,6 +140,18 @@ impl SummaryMemory {
         self
     }
     
+    /// Set the redactor applied before summarizing
+    pub fn with_redactor(mut self, redactor: Arc<dyn Redactor>) -> Self {
+        self.redactor = Some(redactor);
+        self
+    }
+    
+    /// Set the cipher used to encrypt files at rest
+    pub fn with_cipher(mut self, cipher: Option<Arc<MemoryCipher>>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+    
     /// Get summary file path
     fn get_summary_file_path(&self) -> PathBuf {
         self.data_dir.join(format!("{}_summary.json", self.session_id))
@@ -145,7 +169,7 @@ impl SummaryMemory {
         } else {
             // Create new FileChatMessageHistory instance
             let file_path = self.data_dir.join(format!("{}_history.jsonl", self.session_id));
-            let chat_history = FileChatMessageHistory::new(self.session_id.clone(), file_path).await?;
+            let chat_history = FileChatMessageHistory::new_with_cipher(self.session_id.clone(), file_path, self.cipher.clone()).await?;
             chat_history.get_messages().await?
         };
         
@@ -180,11 +204,23 @@ impl SummaryMemory {
         }
         
         let contents = tokio::fs::read_to_string(&file_path).await?;
+        let contents = open_content(self.cipher.as_deref(), &contents)?;
         let summary_data: SummaryData = serde_json::from_str(&contents)?;
         
         Ok(summary_data)
     }
     
+    /// Overwrite the stored summary, e.g. when importing or redacting a session
+    pub async fn restore_summary(&self, summary_data: &SummaryData) -> Result<()> {
+        let mut summary_data = summary_data.clone();
+        summary_data.session_id = self.session_id.clone();
+        
+        let json = seal_content(self.cipher.as_deref(), &serde_json::to_string(&summary_data)?)?;
+        tokio::fs::write(self.get_summary_file_path(), json).await?;
+        
+        Ok(())
+    }
+    
     /// Save summary
     async fn save_summary(&self, summary: &str, sequence_number: u64) -> Result<()> {
         let file_path = self.get_summary_file_path();
@@ -200,7 +236,7 @@ impl SummaryMemory {
             last_updated: chrono::Utc::now().to_rfc3339(),
         };
         
-        let json = serde_json::to_string(&summary_data)?;
+        let json = seal_content(self.cipher.as_deref(), &serde_json::to_string(&summary_data)?)?;
         tokio::fs::write(&file_path, json).await?;
         
         Ok(())
@@ -217,6 +253,11 @@ impl SummaryMemory {
             chat_text.push_str(&format!("{}: {}\n", role, msg.content));
         }
         
+        // Scrub secrets before the conversation leaves the process
+        if let Some(ref redactor) = self.redactor {
+            chat_text = redactor.redact(&chat_text);
+        }
+        
         // Use summary prompt template
         let summary_prompt = self.summary_prompt_template.replace("{chat_history}", &chat_text);
         
@@ -241,11 +282,13 @@ impl SummaryMemory {
                 content: "You are a helpful assistant that creates concise summaries of conversations.".to_string(),
                 name: None,
                 additional_kwargs: std::collections::HashMap::new(),
+                parts: Vec::new(),
             }),
             crate::ModelChatMessage::Human(crate::ChatMessageContent {
                 content: summary_prompt,
                 name: None,
                 additional_kwargs: std::collections::HashMap::new(),
+                parts: Vec::new(),
             }),
         ];
         
@@ -343,7 +386,7 @@ impl SummaryMemory {
         
         // Load message history
         let file_path = self.data_dir.join(format!("{}_history.jsonl", self.session_id.clone()));
-        let chat_history = FileChatMessageHistory::new(self.session_id.clone(), file_path).await?;
+        let chat_history = FileChatMessageHistory::new_with_cipher(self.session_id.clone(), file_path, self.cipher.clone()).await?;
         let messages = chat_history.get_messages().await?;
         
         // Calculate total tokens in messages
@@ -382,6 +425,7 @@ impl BaseMemory for SummaryMemory {
         let summary_threshold = self.summary_threshold;
         let recent_messages_count = self.recent_messages_count;
         let use_shared_history = self.message_history.is_some();
+        let cipher = self.cipher.clone();
         
         Box::pin(async move {
             // Load summary
@@ -392,6 +436,8 @@ impl BaseMemory for SummaryMemory {
                 summary_prompt_template: String::new(),
                 recent_messages_count,
                 message_history: None, // We'll handle this separately
+                redactor: None,
+                cipher: cipher.clone(),
             };
             
             let summary_data = summary_memory.load_summary().await?;
@@ -401,11 +447,11 @@ impl BaseMemory for SummaryMemory {
                 // This is a simplified approach - in a real implementation, we would need to pass the shared instance
                 // For now, we'll create a new instance but this should be improved
                 let file_path = data_dir.join(format!("{}_history.jsonl", session_id.clone()));
-                let chat_history = FileChatMessageHistory::new(session_id.clone(), file_path).await?;
+                let chat_history = FileChatMessageHistory::new_with_cipher(session_id.clone(), file_path, cipher.clone()).await?;
                 chat_history.get_messages().await?
             } else {
                 let file_path = data_dir.join(format!("{}_history.jsonl", session_id.clone()));
-                let chat_history = FileChatMessageHistory::new(session_id.clone(), file_path).await?;
+                let chat_history = FileChatMessageHistory::new_with_cipher(session_id.clone(), file_path, cipher.clone()).await?;
                 chat_history.get_messages().await?
             };
             
@@ -525,11 +571,12 @@ impl BaseMemory for SummaryMemory {
     fn clear<'a>(&'a self) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send + 'a>> {
         let session_id = self.session_id.clone();
         let data_dir = self.data_dir.clone();
+        let cipher = self.cipher.clone();
         
         Box::pin(async move {
             // Clear message history
             let file_path = data_dir.join(format!("{}_history.jsonl", session_id.clone()));
-            let chat_history = FileChatMessageHistory::new(session_id.clone(), file_path).await?;
+            let chat_history = FileChatMessageHistory::new_with_cipher(session_id.clone(), file_path, cipher.clone()).await?;
             chat_history.clear().await?;
             
             // Clear summary file
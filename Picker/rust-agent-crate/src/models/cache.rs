@@ -0,0 +1,569 @@
+// Response cache for chat models: exact matches on the normalized request, optional semantic matches on embeddings
+use anyhow::{Error, Result};
+use log::warn;
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::chat::{ChatCompletion, ChatModel};
+use super::embeddings::{cosine_similarity, Embeddings};
+use super::message::{ChatMessage, TokenUsage};
+use super::replay::{from_replay_message, normalize_request, to_replay_message, ReplayMessage};
+use crate::agents::AgentOutput;
+use crate::tools::parse_model_output;
+
+// Prefix of the inputs feeding a tool result back to the agent, see `run_agent_steps`
+const TOOL_RESULT_PREFIX: &str = "[CUSTOMIZE_TOOL_RESULT]";
+// Key of the hit marker in the returned message's additional_kwargs
+const CACHE_HIT_KWARG: &str = "cache_hit";
+
+/// A cached model response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub message: ReplayMessage,
+    pub usage: Option<TokenUsage>,
+    pub model_name: String,
+    /// Unix timestamp in seconds
+    pub created_at: i64,
+}
+
+// Response cache storage interface
+pub trait ResponseCache: Send + Sync {
+    fn get(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<Option<CachedResponse>>> + Send + '_>>;
+
+    fn put(&self, key: &str, response: CachedResponse) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+
+    fn remove(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+
+    fn clear(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+}
+
+/// In-process cache evicting the least recently used response beyond `capacity`
+pub struct InMemoryResponseCache {
+    entries: Mutex<LruCache<String, CachedResponse>>,
+}
+
+impl InMemoryResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: Mutex::new(LruCache::new(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN))) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ResponseCache for InMemoryResponseCache {
+    fn get(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<Option<CachedResponse>>> + Send + '_>> {
+        let response = self.entries.lock().unwrap().get(key).cloned();
+        Box::pin(async move { Ok(response) })
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        self.entries.lock().unwrap().put(key.to_string(), response);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn remove(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        self.entries.lock().unwrap().pop(key);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn clear(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        self.entries.lock().unwrap().clear();
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Cache persisted in a SQLite database, shared across restarts and processes
+#[cfg(feature = "sqlite-cache")]
+pub struct SqliteResponseCache {
+    pool: sqlx::SqlitePool,
+    max_entries: Option<usize>,
+}
+
+#[cfg(feature = "sqlite-cache")]
+impl SqliteResponseCache {
+    /// Open (or create) the database file
+    pub async fn open(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let options = sqlx::sqlite::SqliteConnectOptions::new()
+            .filename(path.as_ref())
+            .create_if_missing(true);
+        let pool = sqlx::sqlite::SqlitePoolOptions::new().connect_with(options).await?;
+        Self::with_pool(pool).await
+    }
+
+    /// Use an existing pool, the cache table is created when missing
+    pub async fn with_pool(pool: sqlx::SqlitePool) -> Result<Self> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS chat_response_cache (
+                key TEXT PRIMARY KEY,
+                response TEXT NOT NULL,
+                created_at INTEGER NOT NULL,
+                last_used_at INTEGER NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(Self { pool, max_entries: None })
+    }
+
+    /// Evict the least recently used responses beyond `max_entries`
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+}
+
+#[cfg(feature = "sqlite-cache")]
+impl ResponseCache for SqliteResponseCache {
+    fn get(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<Option<CachedResponse>>> + Send + '_>> {
+        let key = key.to_string();
+        Box::pin(async move {
+            let response: Option<String> = sqlx::query_scalar("SELECT response FROM chat_response_cache WHERE key = ?")
+                .bind(&key)
+                .fetch_optional(&self.pool)
+                .await?;
+            let Some(response) = response else {
+                return Ok(None);
+            };
+            sqlx::query("UPDATE chat_response_cache SET last_used_at = ? WHERE key = ?")
+                .bind(chrono::Utc::now().timestamp_millis())
+                .bind(&key)
+                .execute(&self.pool)
+                .await?;
+            Ok(Some(serde_json::from_str(&response)?))
+        })
+    }
+
+    fn put(&self, key: &str, response: CachedResponse) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        let key = key.to_string();
+        Box::pin(async move {
+            sqlx::query("INSERT OR REPLACE INTO chat_response_cache (key, response, created_at, last_used_at) VALUES (?, ?, ?, ?)")
+                .bind(&key)
+                .bind(serde_json::to_string(&response)?)
+                .bind(response.created_at)
+                .bind(chrono::Utc::now().timestamp_millis())
+                .execute(&self.pool)
+                .await?;
+            if let Some(max_entries) = self.max_entries {
+                sqlx::query("DELETE FROM chat_response_cache WHERE key NOT IN (SELECT key FROM chat_response_cache ORDER BY last_used_at DESC LIMIT ?)")
+                    .bind(max_entries as i64)
+                    .execute(&self.pool)
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
+    fn remove(&self, key: &str) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        let key = key.to_string();
+        Box::pin(async move {
+            sqlx::query("DELETE FROM chat_response_cache WHERE key = ?").bind(&key).execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+
+    fn clear(&self) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM chat_response_cache").execute(&self.pool).await?;
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheHitKind {
+    Exact,
+    Semantic,
+}
+
+/// Marker attached to responses served from the cache
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheHit {
+    pub kind: CacheHitKind,
+    /// Similarity of the matched question, semantic hits only
+    #[serde(default)]
+    pub similarity: Option<f32>,
+}
+
+impl CacheHit {
+    /// The marker of a response returned by `CachedChatModel`, None for model responses
+    pub fn from_message(message: &ChatMessage) -> Option<Self> {
+        let content = match message {
+            ChatMessage::System(content)
+            | ChatMessage::Human(content)
+            | ChatMessage::AIMessage(content)
+            | ChatMessage::ToolMessage(content) => content,
+        };
+        serde_json::from_value(content.additional_kwargs.get(CACHE_HIT_KWARG)?.clone()).ok()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub exact_hits: u64,
+    pub semantic_hits: u64,
+    pub misses: u64,
+    /// Requests sent straight to the model because they carry tool results
+    pub bypassed: u64,
+}
+
+struct SemanticEntry {
+    scope: String,
+    embedding: Vec<f32>,
+    key: String,
+}
+
+struct SemanticCache {
+    embeddings: Arc<dyn Embeddings>,
+    threshold: f32,
+    capacity: usize,
+    entries: Mutex<VecDeque<SemanticEntry>>,
+}
+
+impl SemanticCache {
+    // Key of the most similar question in the scope, when it reaches the threshold
+    fn best_match(&self, scope: &str, embedding: &[f32]) -> Option<(String, f32)> {
+        self.entries.lock().unwrap().iter()
+            .filter(|entry| entry.scope == scope)
+            .map(|entry| (entry.key.clone(), cosine_similarity(&entry.embedding, embedding)))
+            .filter(|(_, similarity)| *similarity >= self.threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn insert(&self, entry: SemanticEntry) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|existing| existing.key != entry.key);
+        entries.push_back(entry);
+        while entries.len() > self.capacity {
+            entries.pop_front();
+        }
+    }
+}
+
+fn sha256_hex(value: &Value) -> String {
+    Sha256::digest(value.to_string().as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// ChatModel wrapper answering repeated requests from a cache
+///
+/// Requests are keyed on the model name, base URL, request parameters and normalized messages. With
+/// `with_semantic_cache`, a first-turn question (system messages and one user message) also matches a
+/// previously answered question of the same scope whose embedding similarity reaches the threshold.
+///
+/// Tool-calling turns are never cached: requests feeding a tool result back go straight to the model,
+/// and responses calling a tool are not stored unless the tool is marked with `with_cacheable_tools`,
+/// so a cached answer never repeats a side effect such as a transfer.
+pub struct CachedChatModel {
+    inner: Arc<dyn ChatModel>,
+    cache: Arc<dyn ResponseCache>,
+    semantic: Option<SemanticCache>,
+    ttl: Option<Duration>,
+    cacheable_tools: HashSet<String>,
+    stats: Mutex<CacheStats>,
+}
+
+impl CachedChatModel {
+    pub fn new(inner: Arc<dyn ChatModel>, cache: Arc<dyn ResponseCache>) -> Self {
+        Self {
+            inner,
+            cache,
+            semantic: None,
+            ttl: None,
+            cacheable_tools: HashSet::new(),
+            stats: Mutex::new(CacheStats::default()),
+        }
+    }
+
+    /// Also match similar questions, `threshold` is the minimum cosine similarity (e.g. 0.95)
+    pub fn with_semantic_cache(mut self, embeddings: Arc<dyn Embeddings>, threshold: f32) -> Self {
+        self.semantic = Some(SemanticCache { embeddings, threshold, capacity: 1000, entries: Mutex::new(VecDeque::new()) });
+        self
+    }
+
+    /// Number of questions kept in the semantic index, the oldest are dropped first
+    pub fn with_semantic_capacity(mut self, capacity: usize) -> Self {
+        if let Some(semantic) = &mut self.semantic {
+            semantic.capacity = capacity;
+        }
+        self
+    }
+
+    /// Responses older than `ttl` are not served
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Tools without side effects (e.g. price lookups) whose calls may be cached
+    pub fn with_cacheable_tools(mut self, tools: Vec<String>) -> Self {
+        self.cacheable_tools = tools.into_iter().collect();
+        self
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    pub fn cache(&self) -> &Arc<dyn ResponseCache> {
+        &self.cache
+    }
+
+    fn cache_key(&self, messages: &[ChatMessage]) -> String {
+        sha256_hex(&json!({
+            "model": self.inner.model_name(),
+            "base_url": self.inner.base_url(),
+            "params": self.inner.request_params(),
+            "request": normalize_request(messages),
+        }))
+    }
+
+    // Scope and question of a first-turn request, the only requests matched semantically
+    fn semantic_query(&self, messages: &[ChatMessage]) -> Option<(String, String)> {
+        let (last, context) = messages.split_last()?;
+        let ChatMessage::Human(question) = last else {
+            return None;
+        };
+        if !question.parts.is_empty() || !context.iter().all(|message| matches!(message, ChatMessage::System(_))) {
+            return None;
+        }
+        let scope = sha256_hex(&json!({
+            "model": self.inner.model_name(),
+            "base_url": self.inner.base_url(),
+            "params": self.inner.request_params(),
+            "system": normalize_request(context),
+        }));
+        Some((scope, question.content.clone()))
+    }
+
+    // A request continuing a tool call depends on the tool's side effects
+    fn carries_tool_result(messages: &[ChatMessage]) -> bool {
+        match messages.last() {
+            Some(ChatMessage::ToolMessage(_)) => true,
+            Some(ChatMessage::Human(content)) => content.content.trim_start().starts_with(TOOL_RESULT_PREFIX),
+            _ => false,
+        }
+    }
+
+    fn is_cacheable_response(&self, message: &ChatMessage) -> bool {
+        match parse_model_output(&to_replay_message(message).content) {
+            Ok(AgentOutput::Action(action)) => self.cacheable_tools.contains(&action.tool),
+            _ => true,
+        }
+    }
+
+    // Cached response, expired or unreadable entries count as misses
+    async fn lookup(&self, key: &str) -> Option<CachedResponse> {
+        let response = match self.cache.get(key).await {
+            Ok(response) => response?,
+            Err(e) => {
+                warn!("Failed to read the response cache: {}", e);
+                return None;
+            }
+        };
+        if let Some(ttl) = self.ttl {
+            if chrono::Utc::now().timestamp() - response.created_at > ttl.as_secs() as i64 {
+                if let Err(e) = self.cache.remove(key).await {
+                    warn!("Failed to remove an expired cache entry: {}", e);
+                }
+                return None;
+            }
+        }
+        Some(response)
+    }
+
+    fn hit(&self, response: CachedResponse, hit: CacheHit) -> ChatCompletion {
+        {
+            let mut stats = self.stats.lock().unwrap();
+            match hit.kind {
+                CacheHitKind::Exact => stats.exact_hits += 1,
+                CacheHitKind::Semantic => stats.semantic_hits += 1,
+            }
+        }
+        let mut message = from_replay_message(&response.message);
+        if let ChatMessage::AIMessage(content) = &mut message {
+            content.additional_kwargs.insert(CACHE_HIT_KWARG.to_string(), serde_json::to_value(&hit).unwrap_or_default());
+        }
+        // A hit costs no tokens
+        ChatCompletion { message, usage: None, model_name: response.model_name }
+    }
+}
+
+impl ChatModel for CachedChatModel {
+    fn model_name(&self) -> Option<&str> {
+        self.inner.model_name()
+    }
+
+    fn base_url(&self) -> String {
+        self.inner.base_url()
+    }
+
+    fn request_params(&self) -> Value {
+        self.inner.request_params()
+    }
+
+    fn invoke(&self, messages: Vec<ChatMessage>) -> Pin<Box<dyn Future<Output = Result<ChatCompletion, Error>> + Send + '_>> {
+        Box::pin(async move {
+            if Self::carries_tool_result(&messages) {
+                self.stats.lock().unwrap().bypassed += 1;
+                return self.inner.invoke(messages).await;
+            }
+
+            let key = self.cache_key(&messages);
+            if let Some(response) = self.lookup(&key).await {
+                return Ok(self.hit(response, CacheHit { kind: CacheHitKind::Exact, similarity: None }));
+            }
+
+            // Embedding failures only disable the semantic lookup
+            let mut semantic_entry = None;
+            if let (Some(semantic), Some((scope, question))) = (&self.semantic, self.semantic_query(&messages)) {
+                match semantic.embeddings.embed_query(&question).await {
+                    Ok(embedding) => {
+                        if let Some((matched_key, similarity)) = semantic.best_match(&scope, &embedding) {
+                            if let Some(response) = self.lookup(&matched_key).await {
+                                return Ok(self.hit(response, CacheHit { kind: CacheHitKind::Semantic, similarity: Some(similarity) }));
+                            }
+                        }
+                        semantic_entry = Some(SemanticEntry { scope, embedding, key: key.clone() });
+                    }
+                    Err(e) => warn!("Failed to embed the question for the semantic cache: {}", e),
+                }
+            }
+
+            self.stats.lock().unwrap().misses += 1;
+            let completion = self.inner.invoke(messages).await?;
+            if self.is_cacheable_response(&completion.message) {
+                let response = CachedResponse {
+                    message: to_replay_message(&completion.message),
+                    usage: completion.usage.clone(),
+                    model_name: completion.model_name.clone(),
+                    created_at: chrono::Utc::now().timestamp(),
+                };
+                match self.cache.put(&key, response).await {
+                    Ok(()) => {
+                        if let (Some(semantic), Some(entry)) = (&self.semantic, semantic_entry) {
+                            semantic.insert(entry);
+                        }
+                    }
+                    Err(e) => warn!("Failed to write the response cache: {}", e),
+                }
+            }
+            Ok(completion)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fake::{FakeChatModel, FakeResponse};
+    use crate::models::message::ChatMessageContent;
+    use crate::models::embeddings::EmbeddingsFuture;
+
+    // Bag-of-words vectors over a tiny vocabulary, similar questions share most words
+    struct WordEmbeddings;
+
+    impl Embeddings for WordEmbeddings {
+        fn embed_documents(&self, texts: Vec<String>) -> EmbeddingsFuture<'_> {
+            let vocabulary = ["fee", "fees", "marketplace", "what", "is", "the", "how", "refund", "picker"];
+            Box::pin(async move {
+                Ok(texts.iter().map(|text| {
+                    let words: Vec<String> = text.to_lowercase().split(|c: char| !c.is_alphanumeric()).map(str::to_string).collect();
+                    vocabulary.iter().map(|term| words.iter().filter(|word| word == term).count() as f32).collect()
+                }).collect())
+            })
+        }
+    }
+
+    fn request(question: &str) -> Vec<ChatMessage> {
+        vec![ChatMessage::System(ChatMessageContent::text("You answer Picker questions.")), ChatMessage::Human(ChatMessageContent::text(question))]
+    }
+
+    #[tokio::test]
+    async fn test_exact_semantic_hits_and_tool_bypass() {
+        let inner = FakeChatModel::new(vec![
+            FakeResponse::answer("The fee is 2%"),
+            FakeResponse::answer("Refunds take 3 days"),
+            FakeResponse::tool_call("transfer_coin", json!({"to": "0xabc", "amount": 1})),
+            FakeResponse::tool_call("transfer_coin", json!({"to": "0xabc", "amount": 1})),
+            FakeResponse::answer("Sent"),
+        ]);
+        let model = CachedChatModel::new(Arc::new(inner.clone()), Arc::new(InMemoryResponseCache::new(10)))
+            .with_semantic_cache(Arc::new(WordEmbeddings), 0.9);
+
+        let first = model.invoke(request("What is the marketplace fee?")).await.unwrap();
+        assert_eq!(CacheHit::from_message(&first.message), None);
+        // Whitespace does not change the key
+        let exact = model.invoke(request("What is the  marketplace fee? ")).await.unwrap();
+        assert_eq!(CacheHit::from_message(&exact.message).unwrap().kind, CacheHitKind::Exact);
+        assert!(exact.usage.is_none());
+        let semantic = model.invoke(request("what is the marketplace fee")).await.unwrap();
+        let hit = CacheHit::from_message(&semantic.message).unwrap();
+        assert_eq!(hit.kind, CacheHitKind::Semantic);
+        assert!(hit.similarity.unwrap() >= 0.9);
+        assert!(to_replay_message(&semantic.message).content.contains("2%"));
+        model.invoke(request("How do refunds work?")).await.unwrap();
+
+        // Tool calls are not stored and tool results skip the cache
+        model.invoke(request("Send 1 coin to 0xabc")).await.unwrap();
+        model.invoke(request("Send 1 coin to 0xabc")).await.unwrap();
+        let mut follow_up = request("Send 1 coin to 0xabc");
+        follow_up.push(ChatMessage::Human(ChatMessageContent::text("[CUSTOMIZE_TOOL_RESULT] {\"tool\": \"transfer_coin\", \"result\": \"ok\"}")));
+        model.invoke(follow_up).await.unwrap();
+
+        assert_eq!(inner.call_count(), 5);
+        assert_eq!(model.stats(), CacheStats { exact_hits: 1, semantic_hits: 1, misses: 4, bypassed: 1 });
+    }
+
+    struct HitRecorder(Mutex<Vec<CacheHitKind>>);
+
+    impl crate::CallbackHandler for HitRecorder {
+        fn on_llm_cache_hit(&self, _model_name: &str, hit: &CacheHit) {
+            self.0.lock().unwrap().push(hit.kind);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_agent_reports_cache_hits_to_callbacks() {
+        let inner = FakeChatModel::new(vec![FakeResponse::answer("The fee is 2%")]);
+        let model = CachedChatModel::new(Arc::new(inner.clone()), Arc::new(InMemoryResponseCache::new(10)));
+        let agent = crate::McpAgent::with_chat_model(Arc::new(crate::SimpleMcpClient::new(String::new())), "You answer Picker questions.".to_string(), Arc::new(model));
+        let recorder = Arc::new(HitRecorder(Mutex::new(Vec::new())));
+        let config = crate::RunnableConfig::default().with_callback(recorder.clone());
+        for _ in 0..2 {
+            let answer = crate::run_agent_with_config(&agent, "What is the fee?".to_string(), config.clone()).await.unwrap();
+            assert!(answer.contains("2%"));
+        }
+        assert_eq!(inner.call_count(), 1);
+        assert_eq!(*recorder.0.lock().unwrap(), vec![CacheHitKind::Exact]);
+    }
+
+    #[cfg(feature = "sqlite-cache")]
+    #[tokio::test]
+    async fn test_sqlite_cache_persists_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let inner = FakeChatModel::new(vec![FakeResponse::answer("The fee is 2%")]);
+        let model = CachedChatModel::new(Arc::new(inner.clone()), Arc::new(SqliteResponseCache::open(&path).await.unwrap()));
+        model.invoke(request("What is the marketplace fee?")).await.unwrap();
+
+        // A new process reuses the stored answer
+        let reopened = CachedChatModel::new(Arc::new(inner.clone()), Arc::new(SqliteResponseCache::open(&path).await.unwrap()));
+        let cached = reopened.invoke(request("What is the marketplace fee?")).await.unwrap();
+        assert_eq!(CacheHit::from_message(&cached.message).unwrap().kind, CacheHitKind::Exact);
+        assert_eq!(inner.call_count(), 1);
+    }
+}
@@ -20,6 +20,11 @@ pub trait ChatModel: Send + Sync {
     fn base_url(&self) -> String {
         "https://api.openai.com/v1".to_string()
     }
+
+    // Parameters that change the output (temperature, max tokens, ...), part of response cache keys
+    fn request_params(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
     
     // Core method: handle chat messages
     fn invoke(&self, messages: Vec<ChatMessage>) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ChatCompletion, Error>> + Send + '_>> {
@@ -0,0 +1,108 @@
+// Embedding model interface and OpenAI-compatible implementation
+use anyhow::Error;
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::pin::Pin;
+
+// Vectors of a batch of texts
+pub type EmbeddingsFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Vec<f32>>, Error>> + Send + 'a>>;
+
+// Embedding model interface
+pub trait Embeddings: Send + Sync {
+    // Embed several texts, one vector per text in input order
+    fn embed_documents(&self, texts: Vec<String>) -> EmbeddingsFuture<'_>;
+
+    // Embed a single query text
+    fn embed_query(&self, text: &str) -> Pin<Box<dyn Future<Output = Result<Vec<f32>, Error>> + Send + '_>> {
+        let text = text.to_string();
+        Box::pin(async move {
+            self.embed_documents(vec![text]).await?
+                .pop()
+                .ok_or_else(|| Error::msg("The embedding model returned no vector"))
+        })
+    }
+}
+
+/// Cosine similarity of two vectors, 0 when either is empty or the lengths differ
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.is_empty() || a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Embeddings from the `/embeddings` endpoint of OpenAI or a compatible server
+#[derive(Clone)]
+pub struct OpenAIEmbeddings {
+    client: Client,
+    api_key: String,
+    base_url: String,
+    model_name: String,
+}
+
+impl OpenAIEmbeddings {
+    pub fn new(api_key: String, base_url: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_key,
+            base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+            model_name: "text-embedding-3-small".to_string(),
+        }
+    }
+
+    pub fn with_model(mut self, model_name: String) -> Self {
+        self.model_name = model_name;
+        self
+    }
+
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+}
+
+impl Embeddings for OpenAIEmbeddings {
+    fn embed_documents(&self, texts: Vec<String>) -> EmbeddingsFuture<'_> {
+        Box::pin(async move {
+            if texts.is_empty() {
+                return Ok(Vec::new());
+            }
+            let response = self.client
+                .post(format!("{}/embeddings", self.base_url.trim_end_matches('/')))
+                .bearer_auth(&self.api_key)
+                .json(&json!({ "model": self.model_name, "input": texts }))
+                .send()
+                .await?;
+            let status = response.status();
+            let body: Value = response.json().await?;
+            if !status.is_success() {
+                return Err(Error::msg(format!("Embedding request failed with status {}: {}", status, body)));
+            }
+
+            // Results carry their input index, they are not guaranteed to be in order
+            let mut vectors = vec![Vec::new(); texts.len()];
+            for item in body["data"].as_array().ok_or_else(|| Error::msg("Embedding response has no data array"))? {
+                let index = item["index"].as_u64().unwrap_or(0) as usize;
+                let vector = item["embedding"].as_array()
+                    .ok_or_else(|| Error::msg("Embedding response item has no embedding"))?
+                    .iter()
+                    .map(|value| value.as_f64().unwrap_or(0.0) as f32)
+                    .collect();
+                if let Some(slot) = vectors.get_mut(index) {
+                    *slot = vector;
+                }
+            }
+            if vectors.iter().any(|vector| vector.is_empty()) {
+                return Err(Error::msg("Embedding response is missing vectors"));
+            }
+            Ok(vectors)
+        })
+    }
+}
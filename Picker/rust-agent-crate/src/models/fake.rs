@@ -0,0 +1,198 @@
+// Deterministic chat model returning scripted responses, for tests without network access
+use anyhow::Error;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::chat::{ChatCompletion, ChatModel};
+use super::message::{ChatMessage, ChatMessageContent, TokenUsage};
+
+/// One scripted model response
+#[derive(Clone, Debug)]
+pub enum FakeResponse {
+    /// Plain content returned verbatim as the AI message
+    Text(String),
+    /// Final answer rendered in the agent's `{"content": ...}` format
+    Answer(String),
+    /// Tool call rendered in the agent's `{"call_tool": ...}` format
+    ToolCall { name: String, parameters: Value },
+    /// The call fails with this error message
+    Error(String),
+}
+
+impl FakeResponse {
+    pub fn text(content: &str) -> Self {
+        FakeResponse::Text(content.to_string())
+    }
+
+    pub fn answer(content: &str) -> Self {
+        FakeResponse::Answer(content.to_string())
+    }
+
+    pub fn tool_call(name: &str, parameters: Value) -> Self {
+        FakeResponse::ToolCall { name: name.to_string(), parameters }
+    }
+
+    pub fn error(message: &str) -> Self {
+        FakeResponse::Error(message.to_string())
+    }
+}
+
+/// Chat model that pops scripted responses in order and records every request it receives
+///
+/// Clones share the script and the request log, so a test can keep a handle after giving the model to an agent.
+#[derive(Clone)]
+pub struct FakeChatModel {
+    model_name: String,
+    responses: Arc<Mutex<VecDeque<FakeResponse>>>,
+    requests: Arc<Mutex<Vec<Vec<ChatMessage>>>>,
+}
+
+impl FakeChatModel {
+    pub fn new(responses: Vec<FakeResponse>) -> Self {
+        Self {
+            model_name: "fake-chat-model".to_string(),
+            responses: Arc::new(Mutex::new(responses.into())),
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_model_name(mut self, model_name: &str) -> Self {
+        self.model_name = model_name.to_string();
+        self
+    }
+
+    /// Append a response to the end of the script
+    pub fn push_response(&self, response: FakeResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Number of scripted responses not consumed yet
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+
+    /// Requests received so far, in call order
+    pub fn requests(&self) -> Vec<Vec<ChatMessage>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn call_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+}
+
+// Whitespace separated word count, used as a deterministic token estimate
+fn word_count(messages: &[ChatMessage]) -> usize {
+    messages.iter()
+        .map(|msg| match msg {
+            ChatMessage::System(content)
+            | ChatMessage::Human(content)
+            | ChatMessage::AIMessage(content)
+            | ChatMessage::ToolMessage(content) => content.content.split_whitespace().count(),
+        })
+        .sum()
+}
+
+impl ChatModel for FakeChatModel {
+    fn model_name(&self) -> Option<&str> {
+        Some(&self.model_name)
+    }
+
+    fn base_url(&self) -> String {
+        "fake://".to_string()
+    }
+
+    fn invoke(&self, messages: Vec<ChatMessage>) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ChatCompletion, Error>> + Send + '_>> {
+        let prompt_tokens = word_count(&messages);
+        self.requests.lock().unwrap().push(messages);
+        let response = self.responses.lock().unwrap().pop_front();
+        let model_name = self.model_name.clone();
+
+        Box::pin(async move {
+            let content = match response {
+                Some(FakeResponse::Text(content)) => content,
+                Some(FakeResponse::Answer(content)) => json!({ "content": content }).to_string(),
+                Some(FakeResponse::ToolCall { name, parameters }) => {
+                    json!({ "call_tool": { "name": name, "parameters": parameters } }).to_string()
+                }
+                Some(FakeResponse::Error(message)) => return Err(Error::msg(message)),
+                None => return Err(Error::msg("FakeChatModel has no scripted responses left")),
+            };
+
+            let completion_tokens = content.split_whitespace().count();
+            Ok(ChatCompletion {
+                message: ChatMessage::AIMessage(ChatMessageContent {
+                    content,
+                    name: None,
+                    additional_kwargs: HashMap::new(),
+                    parts: Vec::new(),
+                }),
+                usage: Some(TokenUsage {
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                }),
+                model_name,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::fake_agent;
+    use crate::{AgentOutput, McpAgent, Runnable};
+
+    fn agent_with(model: &FakeChatModel) -> McpAgent {
+        fake_agent("You are a test agent", model)
+    }
+
+    fn inputs(text: &str) -> HashMap<String, String> {
+        HashMap::from([("input".to_string(), text.to_string())])
+    }
+
+    #[tokio::test]
+    async fn test_scripted_agent_flow() {
+        let model = FakeChatModel::new(vec![
+            FakeResponse::tool_call("get_weather", json!({ "city": "Beijing" })),
+            FakeResponse::answer("Sunny"),
+            FakeResponse::error("rate limited"),
+        ]);
+        let agent = agent_with(&model);
+
+        match agent.invoke(inputs("Weather in Beijing?")).await.unwrap() {
+            AgentOutput::Action(action) => {
+                assert_eq!(action.tool, "get_weather");
+                assert_eq!(serde_json::from_str::<Value>(&action.tool_input).unwrap(), json!({ "city": "Beijing" }));
+            }
+            other => panic!("expected a tool call, got {:?}", other),
+        }
+
+        match agent.invoke(inputs("And now?")).await.unwrap() {
+            AgentOutput::Finish(finish) => {
+                assert!(finish.return_values["answer"].contains("Sunny"));
+                assert_eq!(finish.return_values["model"], "fake-chat-model");
+            }
+            other => panic!("expected an answer, got {:?}", other),
+        }
+
+        // Model errors are surfaced as an answer by McpAgent
+        match agent.invoke(inputs("Again")).await.unwrap() {
+            AgentOutput::Finish(finish) => assert!(finish.return_values["answer"].contains("rate limited")),
+            other => panic!("expected an answer, got {:?}", other),
+        }
+
+        assert_eq!(model.call_count(), 3);
+        assert_eq!(model.remaining(), 0);
+        let last_request = model.requests().pop().unwrap();
+        assert!(matches!(last_request.last(), Some(ChatMessage::Human(content)) if content.content == "Again"));
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_script_fails() {
+        let model = FakeChatModel::new(vec![]);
+        assert!(model.invoke(vec![]).await.is_err());
+    }
+}
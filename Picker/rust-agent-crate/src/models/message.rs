@@ -1,15 +1,119 @@
 // Message type definitions
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+// Non-text or extra content of a message, serialized with a `type` tag
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    ImageUrl {
+        url: String,
+        // "low", "high" or "auto" for vision models that support it
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    ImageBase64 {
+        media_type: String,
+        data: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    // Either an uploaded file id or inline base64 data
+    File {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file_id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        filename: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        media_type: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        data: Option<String>,
+    },
+}
+
+impl ContentPart {
+    pub fn image_url(url: &str) -> Self {
+        ContentPart::ImageUrl { url: url.to_string(), detail: None }
+    }
+
+    pub fn image_base64(media_type: &str, data: &str) -> Self {
+        ContentPart::ImageBase64 { media_type: media_type.to_string(), data: data.to_string(), detail: None }
+    }
+
+    pub fn file_id(file_id: &str) -> Self {
+        ContentPart::File { file_id: Some(file_id.to_string()), filename: None, media_type: None, data: None }
+    }
+
+    /// Parse parts from a JSON array (or a string holding one), invalid entries are skipped
+    pub fn parse_list(value: &Value) -> Vec<ContentPart> {
+        let parsed;
+        let value = match value {
+            Value::String(text) => {
+                parsed = serde_json::from_str(text).unwrap_or(Value::Null);
+                &parsed
+            }
+            value => value,
+        };
+        value.as_array()
+            .map(|items| items.iter().filter_map(|item| serde_json::from_value(item.clone()).ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Copy kept in conversation history: inline image and file data becomes a text placeholder,
+    /// so an attachment is sent to the model once instead of again on every later turn
+    pub fn for_history(&self) -> ContentPart {
+        match self {
+            ContentPart::ImageBase64 { .. } | ContentPart::File { data: Some(_), .. } => ContentPart::Text { text: self.summary() },
+            part => part.clone(),
+        }
+    }
+
+    /// Short description used in logs and fixtures, without inline data
+    pub fn summary(&self) -> String {
+        match self {
+            ContentPart::Text { text } => text.clone(),
+            ContentPart::ImageUrl { url, .. } => format!("[image {}]", url),
+            // Hash the payload so two images of the same size never share a summary
+            ContentPart::ImageBase64 { media_type, data, .. } => {
+                let digest: String = Sha256::digest(data.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+                format!("[image {} sha256:{}]", media_type, digest)
+            }
+            ContentPart::File { file_id, filename, .. } => {
+                format!("[file {}]", file_id.as_deref().or(filename.as_deref()).unwrap_or("inline"))
+            }
+        }
+    }
+}
 
 // Message content structure
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct ChatMessageContent {
     pub content: String,
     pub name: Option<String>,
     // OpenAI API tool_call_id parameter
     pub additional_kwargs: HashMap<String, Value>,
+    // Extra parts (images, files) sent after the text content
+    pub parts: Vec<ContentPart>,
+}
+
+impl ChatMessageContent {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self { content: content.into(), ..Default::default() }
+    }
+
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn is_multimodal(&self) -> bool {
+        !self.parts.is_empty()
+    }
 }
 
 // Simplified message type system (aligned with langchain-core)
@@ -22,7 +126,7 @@ pub enum ChatMessage {
 }
 
 // Token usage statistics
-#[derive(Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
@@ -2,8 +2,18 @@
 mod chat;
 mod message;
 mod openai;
+mod fake;
+mod replay;
+mod embeddings;
+mod cache;
 
 // Re-export module content
 pub use chat::{ChatModel, ChatCompletion};
-pub use message::{ChatMessage, ChatMessageContent, TokenUsage};
-pub use openai::OpenAIChatModel;
\ No newline at end of file
+pub use message::{ChatMessage, ChatMessageContent, ContentPart, TokenUsage};
+pub use openai::OpenAIChatModel;
+pub use fake::{FakeChatModel, FakeResponse};
+pub use replay::{RecordReplayChatModel, ReplayMode, ReplayEntry, ReplayMessage};
+pub use embeddings::{Embeddings, EmbeddingsFuture, OpenAIEmbeddings, cosine_similarity};
+pub use cache::{CachedChatModel, ResponseCache, InMemoryResponseCache, CachedResponse, CacheHit, CacheHitKind, CacheStats};
+#[cfg(feature = "sqlite-cache")]
+pub use cache::SqliteResponseCache;
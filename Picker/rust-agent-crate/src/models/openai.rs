@@ -1,6 +1,6 @@
 // OpenAI model implementation - based on LangChain design
 use super::chat::{ChatCompletion, ChatModel};
-use super::message::{ChatMessage, ChatMessageContent, TokenUsage};
+use super::message::{ChatMessage, ChatMessageContent, ContentPart, TokenUsage};
 use anyhow::Error;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
@@ -10,12 +10,120 @@ use log::info;
 #[derive(Serialize, Deserialize, Clone)]
 struct OpenAIMessage {
     role: String,
-    content: String,
+    // A string, or a list of content parts for multimodal messages (null for tool call responses)
+    #[serde(default)]
+    content: serde_json::Value,
     name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
 }
 
+// Serialize message content to the Chat Completions wire format, plain text stays a string
+fn to_openai_content(content: &ChatMessageContent) -> serde_json::Value {
+    if content.parts.is_empty() {
+        return serde_json::Value::String(content.content.clone());
+    }
+    let mut parts = Vec::new();
+    if !content.content.is_empty() {
+        parts.push(serde_json::json!({ "type": "text", "text": content.content }));
+    }
+    for part in &content.parts {
+        parts.push(match part {
+            ContentPart::Text { text } => serde_json::json!({ "type": "text", "text": text }),
+            ContentPart::ImageUrl { url, detail } => {
+                let mut image_url = serde_json::json!({ "url": url });
+                if let Some(detail) = detail {
+                    image_url["detail"] = serde_json::json!(detail);
+                }
+                serde_json::json!({ "type": "image_url", "image_url": image_url })
+            }
+            ContentPart::ImageBase64 { media_type, data, detail } => {
+                let mut image_url = serde_json::json!({ "url": format!("data:{};base64,{}", media_type, data) });
+                if let Some(detail) = detail {
+                    image_url["detail"] = serde_json::json!(detail);
+                }
+                serde_json::json!({ "type": "image_url", "image_url": image_url })
+            }
+            ContentPart::File { file_id, filename, media_type, data } => {
+                let mut file = serde_json::Map::new();
+                if let Some(file_id) = file_id {
+                    file.insert("file_id".to_string(), serde_json::json!(file_id));
+                }
+                if let Some(filename) = filename {
+                    file.insert("filename".to_string(), serde_json::json!(filename));
+                }
+                if let Some(data) = data {
+                    let media_type = media_type.as_deref().unwrap_or("application/octet-stream");
+                    file.insert("file_data".to_string(), serde_json::json!(format!("data:{};base64,{}", media_type, data)));
+                }
+                serde_json::json!({ "type": "file", "file": file })
+            }
+        });
+    }
+    serde_json::Value::Array(parts)
+}
+
+// Only user messages may carry images and files, other roles send their text (and text parts) as a string
+fn to_openai_text_content(content: &ChatMessageContent) -> serde_json::Value {
+    let texts: Vec<&str> = std::iter::once(content.content.as_str())
+        .chain(content.parts.iter().filter_map(|part| match part {
+            ContentPart::Text { text } => Some(text.as_str()),
+            _ => None,
+        }))
+        .filter(|text| !text.is_empty())
+        .collect();
+    serde_json::Value::String(texts.join("\n"))
+}
+
+// Convert a chat message to the Chat Completions wire format
+fn to_openai_message(message: ChatMessage) -> OpenAIMessage {
+    match message {
+        ChatMessage::System(content) => OpenAIMessage {
+            role: "system".to_string(),
+            content: to_openai_text_content(&content),
+            name: content.name,
+            tool_call_id: None,
+        },
+        ChatMessage::Human(content) => OpenAIMessage {
+            role: "user".to_string(),
+            content: to_openai_content(&content),
+            name: content.name,
+            tool_call_id: None,
+        },
+        ChatMessage::AIMessage(content) => OpenAIMessage {
+            role: "assistant".to_string(),
+            content: to_openai_text_content(&content),
+            name: content.name,
+            tool_call_id: None,
+        },
+        ChatMessage::ToolMessage(content) => {
+            info!("Converting tool message: role=tool, content={}", content.content);
+            // Add tool_call_id for tool messages
+            let tool_call_id = content.additional_kwargs.get("tool_call_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("default_tool_call_id").to_string();
+            OpenAIMessage {
+                role: "tool".to_string(),
+                content: to_openai_text_content(&content),
+                name: content.name,
+                tool_call_id: Some(tool_call_id),
+            }
+        },
+    }
+}
+
+// Text of a response message content, joining text parts when the provider returns a list
+fn content_text(content: &serde_json::Value) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => parts.iter()
+            .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join(""),
+        _ => String::new(),
+    }
+}
+
 // Token usage details structure - referencing LangChain's InputTokenDetails and OutputTokenDetails
 #[derive(Deserialize, Default)]
 struct InputTokenDetails {
@@ -211,6 +319,7 @@ impl OpenAIChatModel {
             content,
             name: None,
             additional_kwargs: HashMap::new(),
+            parts: Vec::new(),
         };
         
         match role {
@@ -232,6 +341,15 @@ impl ChatModel for OpenAIChatModel {
         self.base_url.to_string()
     }
 
+    fn request_params(&self) -> serde_json::Value {
+        serde_json::json!({
+            "api_type": format!("{:?}", self.api_type),
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+            "additional_params": self.additional_params,
+        })
+    }
+
     fn invoke(&self, messages: Vec<ChatMessage>) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ChatCompletion, Error>> + Send + '_>> {
         let messages = messages;
         let client = self.client.clone();
@@ -247,39 +365,7 @@ impl ChatModel for OpenAIChatModel {
             // Convert message format
             let openai_messages: Vec<OpenAIMessage> = messages
                 .into_iter()
-                .map(|msg| match msg {
-                    ChatMessage::System(content) => OpenAIMessage {
-                        role: "system".to_string(),
-                        content: content.content,
-                        name: content.name,
-                        tool_call_id: None,
-                    },
-                    ChatMessage::Human(content) => OpenAIMessage {
-                        role: "user".to_string(),
-                        content: content.content,
-                        name: content.name,
-                        tool_call_id: None,
-                    },
-                    ChatMessage::AIMessage(content) => OpenAIMessage {
-                        role: "assistant".to_string(),
-                        content: content.content,
-                        name: content.name,
-                        tool_call_id: None,
-                    },
-                    ChatMessage::ToolMessage(content) => {
-                        info!("Converting tool message: role=tool, content={}", content.content);
-                        // Add tool_call_id for tool messages
-                        let tool_call_id = content.additional_kwargs.get("tool_call_id")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("default_tool_call_id").to_string();
-                        OpenAIMessage {
-                            role: "tool".to_string(),
-                            content: content.content,
-                            name: content.name,
-                            tool_call_id: Some(tool_call_id),
-                        }
-                    },
-                })
+                .map(to_openai_message)
                 .collect();
 
             // Build request body
@@ -333,9 +419,10 @@ impl ChatModel for OpenAIChatModel {
                     let message = &choice.message;
                     match message.role.as_str() {
                         "assistant" => ChatMessage::AIMessage(ChatMessageContent {
-                            content: message.content.clone(),
+                            content: content_text(&message.content),
                             name: message.name.clone(),
                             additional_kwargs: HashMap::new(),
+                            parts: Vec::new(),
                         }),
                         _ => {
                             return Err(Error::msg(format!("Unexpected message role: {}", message.role)));
@@ -350,9 +437,10 @@ impl ChatModel for OpenAIChatModel {
                                 Some(choice) => {
                                     let message = &choice.message;
                                     ChatMessage::AIMessage(ChatMessageContent {
-                                        content: message.content.clone(),
+                                        content: content_text(&message.content),
                                         name: message.name.clone(),
                                         additional_kwargs: HashMap::new(),
+                                        parts: Vec::new(),
                                     })
                                 },
                                 None => return Err(Error::msg("No output returned from API")),
@@ -383,4 +471,40 @@ impl ChatModel for OpenAIChatModel {
             })
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multimodal_wire_format() {
+        let content = ChatMessageContent::text("What is in these files?")
+            .with_part(ContentPart::image_url("https://market.example/mug.png"))
+            .with_part(ContentPart::image_base64("image/png", "iVBORw0KGgo="))
+            .with_part(ContentPart::file_id("file-123"));
+        assert_eq!(to_openai_content(&content), serde_json::json!([
+            { "type": "text", "text": "What is in these files?" },
+            { "type": "image_url", "image_url": { "url": "https://market.example/mug.png" } },
+            { "type": "image_url", "image_url": { "url": "data:image/png;base64,iVBORw0KGgo=" } },
+            { "type": "file", "file": { "file_id": "file-123" } }
+        ]));
+        assert_eq!(to_openai_content(&ChatMessageContent::text("hi")), serde_json::json!("hi"));
+        assert_eq!(content_text(&serde_json::json!([{ "type": "text", "text": "a" }, { "type": "text", "text": "b" }])), "ab");
+    }
+
+    #[test]
+    fn test_images_only_sent_with_user_messages() {
+        let content = ChatMessageContent::text("Here is the mug")
+            .with_part(ContentPart::Text { text: "[image image/png sha256:00]".to_string() })
+            .with_part(ContentPart::image_base64("image/png", "iVBORw0KGgo="));
+
+        let system = to_openai_message(ChatMessage::System(content.clone()));
+        assert_eq!(system.content, serde_json::json!("Here is the mug\n[image image/png sha256:00]"));
+        let assistant = to_openai_message(ChatMessage::AIMessage(content.clone()));
+        assert_eq!(assistant.content, system.content);
+
+        let user = to_openai_message(ChatMessage::Human(content));
+        assert_eq!(user.content[2]["type"], "image_url");
+    }
+}
@@ -0,0 +1,273 @@
+// Record/replay chat model wrapper: captures real request/response pairs to a fixture file and replays them offline
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::chat::{ChatCompletion, ChatModel};
+use super::message::{ChatMessage, ChatMessageContent, ContentPart, TokenUsage};
+
+/// Whether the wrapper calls the inner model or serves responses from the fixture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Call the inner model and append every exchange to the fixture file
+    Record,
+    /// Serve responses from the fixture file, never touching the network
+    Replay,
+}
+
+/// A message as stored in a fixture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayMessage {
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
+}
+
+/// One recorded request/response pair, stored as one JSON line in the fixture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEntry {
+    /// Normalized request used for matching
+    pub request_key: String,
+    /// Original request messages, kept for readability of the fixture
+    pub request: Vec<ReplayMessage>,
+    pub response: ReplayMessage,
+    pub usage: Option<TokenUsage>,
+    pub model_name: String,
+}
+
+pub(super) fn to_replay_message(message: &ChatMessage) -> ReplayMessage {
+    let (role, content) = match message {
+        ChatMessage::System(content) => ("system", content),
+        ChatMessage::Human(content) => ("user", content),
+        ChatMessage::AIMessage(content) => ("assistant", content),
+        ChatMessage::ToolMessage(content) => ("tool", content),
+    };
+    ReplayMessage { role: role.to_string(), content: content.content.clone(), parts: content.parts.clone() }
+}
+
+pub(super) fn from_replay_message(message: &ReplayMessage) -> ChatMessage {
+    let content = ChatMessageContent {
+        content: message.content.clone(),
+        name: None,
+        additional_kwargs: HashMap::new(),
+        parts: message.parts.clone(),
+    };
+    match message.role.as_str() {
+        "system" => ChatMessage::System(content),
+        "user" => ChatMessage::Human(content),
+        "tool" => ChatMessage::ToolMessage(content),
+        _ => ChatMessage::AIMessage(content),
+    }
+}
+
+/// Build the matching key of a request: one `role: content` line per message with whitespace collapsed,
+/// followed by a summary of its image and file parts
+pub(super) fn normalize_request(messages: &[ChatMessage]) -> String {
+    messages.iter()
+        .map(|msg| {
+            let msg = to_replay_message(msg);
+            let text = std::iter::once(msg.content.clone())
+                .chain(msg.parts.iter().map(|part| part.summary()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!("{}: {}", msg.role, text.split_whitespace().collect::<Vec<_>>().join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// ChatModel wrapper recording exchanges of an inner model, or replaying them from a JSONL fixture
+///
+/// In replay mode identical requests are answered with their recordings in order, so a conversation
+/// that repeats a prompt replays the same way it was recorded.
+pub struct RecordReplayChatModel {
+    inner: Option<Arc<dyn ChatModel>>,
+    fixture_path: PathBuf,
+    mode: ReplayMode,
+    entries: Arc<Mutex<Vec<ReplayEntry>>>,
+    used: Arc<Mutex<Vec<bool>>>,
+}
+
+impl RecordReplayChatModel {
+    /// Record exchanges of `inner` into `fixture_path`, replacing any previous recording
+    pub fn record(inner: Arc<dyn ChatModel>, fixture_path: impl AsRef<Path>) -> Result<Self> {
+        let fixture_path = fixture_path.as_ref().to_path_buf();
+        if let Some(parent) = fixture_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&fixture_path, "")?;
+
+        Ok(Self {
+            inner: Some(inner),
+            fixture_path,
+            mode: ReplayMode::Record,
+            entries: Arc::new(Mutex::new(Vec::new())),
+            used: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    /// Replay exchanges from an existing fixture file
+    pub fn replay(fixture_path: impl AsRef<Path>) -> Result<Self> {
+        let fixture_path = fixture_path.as_ref().to_path_buf();
+        let content = fs::read_to_string(&fixture_path)
+            .map_err(|e| Error::msg(format!("Failed to read replay fixture {}: {}", fixture_path.display(), e)))?;
+
+        let mut entries = Vec::new();
+        for (index, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let entry: ReplayEntry = serde_json::from_str(line)
+                .map_err(|e| Error::msg(format!("Invalid replay entry on line {}: {}", index + 1, e)))?;
+            entries.push(entry);
+        }
+
+        Ok(Self {
+            inner: None,
+            fixture_path,
+            mode: ReplayMode::Replay,
+            used: Arc::new(Mutex::new(vec![false; entries.len()])),
+            entries: Arc::new(Mutex::new(entries)),
+        })
+    }
+
+    /// Replay when the fixture exists, otherwise record it with `inner`
+    pub fn auto(inner: Arc<dyn ChatModel>, fixture_path: impl AsRef<Path>) -> Result<Self> {
+        if fixture_path.as_ref().exists() {
+            Self::replay(fixture_path)
+        } else {
+            Self::record(inner, fixture_path)
+        }
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    pub fn fixture_path(&self) -> &Path {
+        &self.fixture_path
+    }
+
+    /// Recorded (or loaded) exchanges
+    pub fn entries(&self) -> Vec<ReplayEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    // Take the first unused recording matching the request key
+    fn take_recording(&self, request_key: &str) -> Option<ReplayEntry> {
+        let entries = self.entries.lock().unwrap();
+        let mut used = self.used.lock().unwrap();
+        let index = entries.iter().enumerate()
+            .position(|(i, entry)| !used[i] && entry.request_key == request_key)?;
+        used[index] = true;
+        Some(entries[index].clone())
+    }
+
+    fn append_recording(&self, entry: ReplayEntry) -> Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.fixture_path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        self.entries.lock().unwrap().push(entry);
+        self.used.lock().unwrap().push(true);
+        Ok(())
+    }
+}
+
+impl ChatModel for RecordReplayChatModel {
+    fn model_name(&self) -> Option<&str> {
+        self.inner.as_ref().and_then(|inner| inner.model_name())
+    }
+
+    fn invoke(&self, messages: Vec<ChatMessage>) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<ChatCompletion, Error>> + Send + '_>> {
+        Box::pin(async move {
+            let request_key = normalize_request(&messages);
+
+            match self.mode {
+                ReplayMode::Replay => {
+                    let entry = self.take_recording(&request_key).ok_or_else(|| Error::msg(format!(
+                        "No recording in {} matches request:\n{}",
+                        self.fixture_path.display(),
+                        request_key
+                    )))?;
+                    Ok(ChatCompletion {
+                        message: from_replay_message(&entry.response),
+                        usage: entry.usage,
+                        model_name: entry.model_name,
+                    })
+                }
+                ReplayMode::Record => {
+                    let inner = self.inner.as_ref().ok_or_else(|| Error::msg("Record mode requires an inner model"))?;
+                    let request = messages.iter().map(to_replay_message).collect();
+                    let completion = inner.invoke(messages).await?;
+
+                    self.append_recording(ReplayEntry {
+                        request_key,
+                        request,
+                        response: to_replay_message(&completion.message),
+                        usage: completion.usage.clone(),
+                        model_name: completion.model_name.clone(),
+                    })?;
+                    Ok(completion)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fake::{FakeChatModel, FakeResponse};
+
+    fn human(content: &str) -> ChatMessage {
+        ChatMessage::Human(ChatMessageContent {
+            content: content.to_string(),
+            name: None,
+            additional_kwargs: HashMap::new(),
+            parts: Vec::new(),
+        })
+    }
+
+    fn content_of(completion: &ChatCompletion) -> String {
+        to_replay_message(&completion.message).content
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = dir.path().join("fixtures/agent.jsonl");
+
+        let live = FakeChatModel::new(vec![FakeResponse::answer("first"), FakeResponse::answer("second")]);
+        let recorder = RecordReplayChatModel::auto(Arc::new(live.clone()), &fixture).unwrap();
+        assert_eq!(recorder.mode(), ReplayMode::Record);
+        recorder.invoke(vec![human("hello")]).await.unwrap();
+        recorder.invoke(vec![human("hello")]).await.unwrap();
+        assert_eq!(live.call_count(), 2);
+
+        let replayer = RecordReplayChatModel::auto(Arc::new(FakeChatModel::new(vec![])), &fixture).unwrap();
+        assert_eq!(replayer.mode(), ReplayMode::Replay);
+        // Whitespace differences do not affect matching, repeated requests replay in recording order
+        let first = replayer.invoke(vec![human("  hello ")]).await.unwrap();
+        let second = replayer.invoke(vec![human("hello")]).await.unwrap();
+        assert!(content_of(&first).contains("first"));
+        assert!(content_of(&second).contains("second"));
+        assert_eq!(first.usage.unwrap().total_tokens, 2);
+
+        assert!(replayer.invoke(vec![human("hello")]).await.is_err());
+        assert!(replayer.invoke(vec![human("unknown")]).await.is_err());
+    }
+
+    #[test]
+    fn test_images_of_same_size_have_distinct_keys() {
+        let with_image = |data: &str| {
+            let mut content = ChatMessageContent::text("what is this?");
+            content.parts.push(ContentPart::image_base64("image/png", data));
+            ChatMessage::Human(content)
+        };
+        let first = normalize_request(&[with_image("aGVsbG8=")]);
+        let second = normalize_request(&[with_image("d29ybGQ=")]);
+        assert_ne!(first, second);
+        assert!(first.starts_with("user: what is this? [image image/png sha256:"));
+    }
+}
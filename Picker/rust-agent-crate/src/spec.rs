@@ -0,0 +1,568 @@
+// Declarative agent spec: a TOML, YAML or JSON file describing the model, tools, memory, prompt and guardrails of an agent
+use anyhow::{Error, Result};
+use config::{Config, File, FileFormat};
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::agents::McpAgent;
+use crate::eval::{EvalDataset, EvalReport, EvalRunner};
+use crate::guardrails::{GuardrailConfig, Guardrails};
+use crate::mcp::{McpClient, McpClientGroup, SimpleMcpClient};
+use crate::memory::{BaseMemory, CompositeMemory, CompositeMemoryConfig, SimpleMemory};
+use crate::models::{ChatModel, OpenAIChatModel};
+use crate::RunnableConfig;
+
+/// File formats of an agent spec
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpecFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl SpecFormat {
+    /// Format from the file extension (`.toml`, `.yaml`/`.yml`, `.json`)
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "toml" => Some(Self::Toml),
+            "yaml" | "yml" => Some(Self::Yaml),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    fn file_format(self) -> FileFormat {
+        match self {
+            Self::Toml => FileFormat::Toml,
+            Self::Yaml => FileFormat::Yaml,
+            Self::Json => FileFormat::Json,
+        }
+    }
+}
+
+/// Model providers, `openai` covers every OpenAI compatible endpoint through `base_url`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelProvider {
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAI,
+}
+
+/// Model settings, the API key is read from an environment variable so spec files can be shared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelSpec {
+    #[serde(default)]
+    pub provider: ModelProvider,
+    pub model: String,
+    #[serde(default)]
+    pub base_url: Option<String>,
+    #[serde(default = "default_api_key_env")]
+    pub api_key_env: String,
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Extra request body parameters, e.g. `top_p`
+    #[serde(default)]
+    pub params: HashMap<String, Value>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+fn default_api_key_env() -> String {
+    "OPENAI_API_KEY".to_string()
+}
+
+/// System prompt template, `{{name}}` placeholders are filled from `variables`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptSpec {
+    #[serde(default = "default_system_prompt")]
+    pub template: String,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+fn default_system_prompt() -> String {
+    "You are an AI assistant that can use tools. Please decide whether to use tools based on the user's needs.".to_string()
+}
+
+impl Default for PromptSpec {
+    fn default() -> Self {
+        Self { template: default_system_prompt(), variables: HashMap::new() }
+    }
+}
+
+/// An MCP server, its tools are namespaced with `name` (`chain.transfer_coin`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct McpServerSpec {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HttpToolSpec {
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_response_bytes: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileSystemToolSpec {
+    pub root: PathBuf,
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub max_file_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShellToolSpec {
+    pub allowed_commands: Vec<String>,
+    pub working_dir: PathBuf,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Built-in tools, they require the `builtin-tools` feature
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuiltinToolsSpec {
+    #[serde(default)]
+    pub http: Option<HttpToolSpec>,
+    #[serde(default)]
+    pub filesystem: Option<FileSystemToolSpec>,
+    #[serde(default)]
+    pub json_query: bool,
+    #[serde(default)]
+    pub shell: Option<ShellToolSpec>,
+}
+
+impl BuiltinToolsSpec {
+    pub fn is_empty(&self) -> bool {
+        self.http.is_none() && self.filesystem.is_none() && !self.json_query && self.shell.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemoryBackend {
+    None,
+    /// In-process history, lost when the agent is dropped
+    Simple,
+    /// Persistent history with summaries, see `CompositeMemory`
+    #[default]
+    Composite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemorySpec {
+    #[serde(default)]
+    pub backend: MemoryBackend,
+    #[serde(default = "default_data_dir")]
+    pub data_dir: PathBuf,
+    /// Session to continue, a new one is generated when absent
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default = "default_summary_threshold")]
+    pub summary_threshold: usize,
+    #[serde(default = "default_recent_messages")]
+    pub recent_messages: usize,
+}
+
+fn default_data_dir() -> PathBuf {
+    PathBuf::from("data")
+}
+
+fn default_summary_threshold() -> usize {
+    200
+}
+
+fn default_recent_messages() -> usize {
+    10
+}
+
+impl Default for MemorySpec {
+    fn default() -> Self {
+        Self {
+            backend: MemoryBackend::default(),
+            data_dir: default_data_dir(),
+            session_id: None,
+            summary_threshold: default_summary_threshold(),
+            recent_messages: default_recent_messages(),
+        }
+    }
+}
+
+/// Everything needed to build a ready-to-run agent
+///
+/// ```toml
+/// name = "picker-assistant"
+///
+/// [model]
+/// model = "gpt-4o-mini"
+/// temperature = 0.2
+///
+/// [prompt]
+/// template = "You are the assistant of {{project}}."
+/// variables = { project = "Picker" }
+///
+/// [[mcp_servers]]
+/// name = "chain"
+/// url = "http://127.0.0.1:6000"
+///
+/// [memory]
+/// backend = "composite"
+/// data_dir = "data"
+///
+/// [guardrails]
+/// max_input_chars = 4000
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentSpec {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub model: ModelSpec,
+    #[serde(default)]
+    pub prompt: PromptSpec,
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServerSpec>,
+    #[serde(default)]
+    pub builtin_tools: BuiltinToolsSpec,
+    #[serde(default)]
+    pub memory: MemorySpec,
+    #[serde(default)]
+    pub guardrails: Option<GuardrailConfig>,
+}
+
+fn placeholder_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap()
+}
+
+impl AgentSpec {
+    /// Parse and validate a spec
+    pub fn parse(content: &str, format: SpecFormat) -> Result<Self> {
+        let spec: Self = Config::builder()
+            .add_source(File::from_str(content, format.file_format()))
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .map_err(|e| Error::msg(format!("Invalid agent spec: {}", e)))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Load a spec file, the format follows the extension
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = SpecFormat::from_path(path)
+            .ok_or_else(|| Error::msg(format!("Agent spec {} must have a .toml, .yaml, .yml or .json extension", path.display())))?;
+        let content = tokio::fs::read_to_string(path).await
+            .map_err(|e| Error::msg(format!("Failed to read agent spec {}: {}", path.display(), e)))?;
+        Self::parse(&content, format).map_err(|e| Error::msg(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Check the spec, every problem is reported in one error
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if self.model.model.trim().is_empty() {
+            problems.push("model.model must not be empty".to_string());
+        }
+        if let Some(temperature) = self.model.temperature {
+            if !(0.0..=2.0).contains(&temperature) {
+                problems.push(format!("model.temperature must be between 0 and 2, got {}", temperature));
+            }
+        }
+        if self.model.max_tokens == Some(0) {
+            problems.push("model.max_tokens must be greater than 0".to_string());
+        }
+        if self.model.api_key_env.is_empty() {
+            problems.push("model.api_key_env must not be empty".to_string());
+        }
+
+        if self.prompt.template.trim().is_empty() {
+            problems.push("prompt.template must not be empty".to_string());
+        }
+        for captures in placeholder_regex().captures_iter(&self.prompt.template) {
+            if !self.prompt.variables.contains_key(&captures[1]) {
+                problems.push(format!("prompt.template uses {{{{{}}}}} but prompt.variables has no {}", &captures[1], &captures[1]));
+            }
+        }
+
+        let mut names = HashSet::new();
+        for (i, server) in self.mcp_servers.iter().enumerate() {
+            if server.name.is_empty() || server.name.contains('.') {
+                problems.push(format!("mcp_servers[{}].name {:?} must be non-empty and contain no '.'", i, server.name));
+            } else if !names.insert(server.name.as_str()) {
+                problems.push(format!("mcp_servers[{}].name {} is used twice", i, server.name));
+            }
+            if !server.url.starts_with("http://") && !server.url.starts_with("https://") {
+                problems.push(format!("mcp_servers[{}].url {:?} must be an http(s) URL", i, server.url));
+            }
+        }
+
+        if !self.builtin_tools.is_empty() && !cfg!(feature = "builtin-tools") {
+            problems.push("builtin_tools requires rust-agent to be built with the builtin-tools feature".to_string());
+        }
+        if let Some(http) = &self.builtin_tools.http {
+            if http.allowed_domains.is_empty() {
+                problems.push("builtin_tools.http.allowed_domains must list at least one domain".to_string());
+            }
+        }
+        if let Some(shell) = &self.builtin_tools.shell {
+            if shell.allowed_commands.is_empty() {
+                problems.push("builtin_tools.shell.allowed_commands must list at least one command".to_string());
+            }
+        }
+
+        if self.memory.backend == MemoryBackend::Composite {
+            if self.memory.summary_threshold == 0 {
+                problems.push("memory.summary_threshold must be greater than 0".to_string());
+            }
+            if self.memory.recent_messages == 0 {
+                problems.push("memory.recent_messages must be greater than 0".to_string());
+            }
+        }
+
+        if let Some(config) = &self.guardrails {
+            if let Err(e) = Guardrails::from_config(config) {
+                problems.push(format!("guardrails: {}", e));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::msg(format!("Invalid agent spec:\n  - {}", problems.join("\n  - "))))
+        }
+    }
+
+    /// System prompt with the template variables filled in
+    pub fn system_prompt(&self) -> String {
+        placeholder_regex()
+            .replace_all(&self.prompt.template, |captures: &regex::Captures| {
+                self.prompt.variables.get(&captures[1]).cloned().unwrap_or_else(|| captures[0].to_string())
+            })
+            .into_owned()
+    }
+
+    /// Chat model of the spec, fails when the API key variable is not set
+    pub fn chat_model(&self) -> Result<Arc<dyn ChatModel>> {
+        match self.model.provider {
+            ModelProvider::OpenAI => {
+                let api_key = std::env::var(&self.model.api_key_env)
+                    .map_err(|_| Error::msg(format!("Environment variable {} with the model API key is not set", self.model.api_key_env)))?;
+                let mut model = OpenAIChatModel::new(api_key, self.model.base_url.clone()).with_model(self.model.model.clone());
+                if let Some(temperature) = self.model.temperature {
+                    model = model.with_temperature(temperature);
+                }
+                if let Some(max_tokens) = self.model.max_tokens {
+                    model = model.with_max_tokens(max_tokens);
+                }
+                for (key, value) in &self.model.params {
+                    model = model.with_additional_param(key.clone(), value.clone());
+                }
+                for (key, value) in &self.model.headers {
+                    model = model.with_additional_header(key.clone(), value.clone());
+                }
+                Ok(Arc::new(model))
+            }
+        }
+    }
+
+    /// All MCP servers behind one client, servers that are down are skipped
+    pub async fn mcp_client(&self) -> Result<McpClientGroup> {
+        let mut group = McpClientGroup::new();
+        for server in &self.mcp_servers {
+            group.add_server(&server.name, &server.url, Box::new(SimpleMcpClient::new(server.url.clone())))?;
+        }
+        // Failures are logged per server by the group
+        if !self.mcp_servers.is_empty() {
+            let _ = group.connect("").await;
+        }
+        Ok(group)
+    }
+
+    pub async fn memory(&self) -> Result<Option<Box<dyn BaseMemory>>> {
+        Ok(match self.memory.backend {
+            MemoryBackend::None => None,
+            MemoryBackend::Simple => Some(Box::new(SimpleMemory::new())),
+            MemoryBackend::Composite => Some(Box::new(CompositeMemory::with_config(CompositeMemoryConfig {
+                data_dir: self.memory.data_dir.clone(),
+                session_id: self.memory.session_id.clone(),
+                summary_threshold: self.memory.summary_threshold,
+                recent_messages_count: self.memory.recent_messages,
+                auto_generate_summary: true,
+            }).await?)),
+        })
+    }
+
+    /// Build the agent with the model of the spec
+    pub async fn build(&self) -> Result<SpecAgent> {
+        self.build_with_model(self.chat_model()?).await
+    }
+
+    /// Build the agent with another chat model, e.g. a `FakeChatModel` in tests
+    pub async fn build_with_model(&self, chat_model: Arc<dyn ChatModel>) -> Result<SpecAgent> {
+        self.validate()?;
+        let client: Arc<dyn McpClient> = Arc::new(self.mcp_client().await?);
+        let mut agent = McpAgent::with_chat_model(client, self.system_prompt(), chat_model);
+        if let Some(memory) = self.memory().await? {
+            agent.set_memory(memory);
+        }
+        if let Err(e) = agent.auto_add_tools().await {
+            warn!("Failed to load MCP tools: {}", e);
+        }
+        self.add_builtin_tools(&mut agent);
+        let guardrails = match &self.guardrails {
+            Some(config) => Some(Guardrails::from_config(config)?),
+            None => None,
+        };
+        Ok(SpecAgent { name: self.name.clone(), agent, guardrails })
+    }
+
+    #[cfg(feature = "builtin-tools")]
+    fn add_builtin_tools(&self, agent: &mut McpAgent) {
+        use crate::tools::{FileSystemTool, HttpRequestTool, JsonQueryTool, ShellTool};
+        use std::time::Duration;
+
+        let tools = &self.builtin_tools;
+        if let Some(http) = &tools.http {
+            let mut tool = HttpRequestTool::new(http.allowed_domains.clone());
+            if let Some(seconds) = http.timeout_secs {
+                tool = tool.with_timeout(Duration::from_secs(seconds));
+            }
+            if let Some(bytes) = http.max_response_bytes {
+                tool = tool.with_max_response_bytes(bytes);
+            }
+            agent.add_tool(Box::new(tool));
+        }
+        if let Some(filesystem) = &tools.filesystem {
+            let mut tool = FileSystemTool::new(filesystem.root.clone()).with_read_only(filesystem.read_only);
+            if let Some(bytes) = filesystem.max_file_bytes {
+                tool = tool.with_max_file_bytes(bytes);
+            }
+            agent.add_tool(Box::new(tool));
+        }
+        if tools.json_query {
+            agent.add_tool(Box::new(JsonQueryTool::new()));
+        }
+        if let Some(shell) = &tools.shell {
+            let mut tool = ShellTool::new(shell.allowed_commands.clone(), shell.working_dir.clone());
+            if let Some(seconds) = shell.timeout_secs {
+                tool = tool.with_timeout(Duration::from_secs(seconds));
+            }
+            agent.add_tool(Box::new(tool));
+        }
+    }
+
+    // validate rejects built-in tools without the feature
+    #[cfg(not(feature = "builtin-tools"))]
+    fn add_builtin_tools(&self, _agent: &mut McpAgent) {}
+}
+
+/// Agent built from a spec, `run` applies the guardrails of the spec
+pub struct SpecAgent {
+    pub name: Option<String>,
+    pub agent: McpAgent,
+    pub guardrails: Option<Guardrails>,
+}
+
+impl SpecAgent {
+    pub async fn run(&self, input: String) -> Result<String> {
+        self.run_with_config(input, RunnableConfig::default()).await
+    }
+
+    pub async fn run_with_config(&self, input: String, config: RunnableConfig) -> Result<String> {
+        match &self.guardrails {
+            Some(guardrails) => crate::run_agent_with_guardrails(&self.agent, input, config, guardrails).await,
+            None => crate::run_agent_with_config(&self.agent, input, config).await,
+        }
+    }
+
+    /// Run an eval dataset, the guardrails of the spec apply as in `run`
+    pub async fn eval(&self, runner: &EvalRunner, dataset: &EvalDataset) -> Result<EvalReport> {
+        match &self.guardrails {
+            Some(guardrails) => runner.run_with_guardrails(&self.agent, dataset, guardrails).await,
+            None => runner.run(&self.agent, dataset).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::Agent;
+    use crate::models::{FakeChatModel, FakeResponse};
+
+    const SPEC: &str = r#"
+        name = "picker-assistant"
+
+        [model]
+        model = "gpt-4o-mini"
+        temperature = 0.2
+        params = { top_p = 0.9 }
+
+        [prompt]
+        template = "You are the {{role}} of {{projectName}}."
+        variables = { role = "assistant", projectName = "Picker" }
+
+        [memory]
+        backend = "simple"
+
+        [guardrails]
+        max_input_chars = 20
+    "#;
+
+    #[tokio::test]
+    async fn test_parse_validate_and_build() {
+        let spec = AgentSpec::parse(SPEC, SpecFormat::Toml).unwrap();
+        assert_eq!(spec.model.api_key_env, "OPENAI_API_KEY");
+        assert_eq!(spec.model.params["top_p"], 0.9);
+        assert_eq!(spec.system_prompt(), "You are the assistant of Picker.");
+
+        let yaml = "model:\n  model: gpt-4o-mini\nmcp_servers:\n  - name: chain\n    url: http://127.0.0.1:6000\n";
+        let spec_yaml = AgentSpec::parse(yaml, SpecFormat::Yaml).unwrap();
+        assert_eq!(spec_yaml.mcp_servers[0].name, "chain");
+        assert_eq!(spec_yaml.memory.backend, MemoryBackend::Composite);
+
+        let typo = AgentSpec::parse("[model]\nmodel = \"gpt-4o\"\ntemprature = 0.2", SpecFormat::Toml).unwrap_err();
+        assert!(typo.to_string().contains("temprature"), "{}", typo);
+        let invalid = r#"
+            [model]
+            model = ""
+            temperature = 3.0
+            [prompt]
+            template = "Hello {{user}}"
+            [[mcp_servers]]
+            name = "a.b"
+            url = "localhost:6000"
+        "#;
+        let problems = AgentSpec::parse(invalid, SpecFormat::Toml).unwrap_err().to_string();
+        for expected in ["model.model", "model.temperature", "{{user}}", "mcp_servers[0].name", "mcp_servers[0].url"] {
+            assert!(problems.contains(expected), "{} missing from {}", expected, problems);
+        }
+
+        let agent = spec.build_with_model(Arc::new(FakeChatModel::new(vec![FakeResponse::answer("Hi!")]))).await.unwrap();
+        assert_eq!(agent.name.as_deref(), Some("picker-assistant"));
+        assert!(agent.agent.get_memory().is_some());
+        assert!(agent.agent.tools().is_empty());
+        assert!(agent.run("This input is longer than twenty characters".to_string()).await.is_err());
+        assert!(agent.run("Hello".to_string()).await.unwrap().contains("Hi!"));
+    }
+}
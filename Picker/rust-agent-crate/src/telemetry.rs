@@ -0,0 +1,169 @@
+// Span instrumentation for agent runs, LLM calls and tool calls
+// Spans are only recorded with the `otel` feature, otherwise the helpers just await the future
+use anyhow::Error;
+use serde_json::Value;
+use std::future::Future;
+
+/// Instrumentation scope name used for all spans emitted by this crate
+pub const TRACER_NAME: &str = "rust-agent";
+
+// Span attributes, numbers and booleans keep their type, everything else is recorded as a string
+pub(crate) type SpanAttributes = Vec<(&'static str, Value)>;
+
+#[cfg(feature = "otel")]
+mod otel {
+    use super::*;
+    use opentelemetry::trace::{FutureExt, Status, TraceContextExt, Tracer};
+    use opentelemetry::{global, Context, KeyValue};
+    use opentelemetry_otlp::{Protocol, SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use std::time::Instant;
+
+    /// Install a global tracer provider exporting spans as OTLP/HTTP JSON
+    ///
+    /// `endpoint` is the collector base URL (e.g. `http://localhost:4318`), spans are posted to `<endpoint>/v1/traces`.
+    /// Keep the returned provider and call `shutdown()` on exit to flush pending spans.
+    pub fn init_otlp_tracing(endpoint: &str, service_name: &str) -> Result<SdkTracerProvider, Error> {
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_protocol(Protocol::HttpJson)
+            .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+            .build()
+            .map_err(|e| Error::msg(format!("Failed to build OTLP exporter: {}", e)))?;
+
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(service_name.to_string()).build())
+            .build();
+        global::set_tracer_provider(provider.clone());
+        Ok(provider)
+    }
+
+    fn to_key_value(key: &'static str, value: Value) -> KeyValue {
+        match value {
+            Value::Bool(b) => KeyValue::new(key, b),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => KeyValue::new(key, i),
+                None => KeyValue::new(key, n.as_f64().unwrap_or_default()),
+            },
+            Value::String(s) => KeyValue::new(key, s),
+            other => KeyValue::new(key, other.to_string()),
+        }
+    }
+
+    pub(crate) async fn traced<O, F, A>(name: &'static str, attributes: SpanAttributes, future: F, on_ok: A) -> Result<O, Error>
+    where
+        F: Future<Output = Result<O, Error>>,
+        A: FnOnce(&O) -> SpanAttributes,
+    {
+        let tracer = global::tracer(TRACER_NAME);
+        let span = tracer
+            .span_builder(name)
+            .with_attributes(attributes.into_iter().map(|(k, v)| to_key_value(k, v)))
+            .start_with_context(&tracer, &Context::current());
+        let cx = Context::current_with_span(span);
+
+        let started = Instant::now();
+        let result = future.with_context(cx.clone()).await;
+
+        let span = cx.span();
+        span.set_attribute(KeyValue::new("duration_ms", started.elapsed().as_millis() as i64));
+        match &result {
+            Ok(output) => {
+                for (key, value) in on_ok(output) {
+                    span.set_attribute(to_key_value(key, value));
+                }
+                span.set_status(Status::Ok);
+            }
+            Err(e) => {
+                span.set_attribute(KeyValue::new("error.message", e.to_string()));
+                span.set_status(Status::error(e.to_string()));
+            }
+        }
+        span.end();
+
+        result
+    }
+}
+
+#[cfg(feature = "otel")]
+pub use otel::init_otlp_tracing;
+#[cfg(feature = "otel")]
+pub(crate) use otel::traced;
+
+#[cfg(not(feature = "otel"))]
+pub(crate) async fn traced<O, F, A>(_name: &'static str, _attributes: SpanAttributes, future: F, _on_ok: A) -> Result<O, Error>
+where
+    F: Future<Output = Result<O, Error>>,
+    A: FnOnce(&O) -> SpanAttributes,
+{
+    future.await
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use axum::{body::Bytes, extract::State, routing::post, Router};
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    // Minimal OTLP/HTTP collector stand-in that keeps the received JSON payloads
+    async fn start_collector() -> (String, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let app = Router::new()
+            .route("/v1/traces", post(|State(received): State<Arc<Mutex<Vec<Value>>>>, body: Bytes| async move {
+                if let Ok(payload) = serde_json::from_slice::<Value>(&body) {
+                    received.lock().unwrap().push(payload);
+                }
+                "{}"
+            }))
+            .with_state(received.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (endpoint, received)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_spans_exported_to_collector() {
+        let (endpoint, received) = start_collector().await;
+        let provider = init_otlp_tracing(&endpoint, "rust-agent-test").unwrap();
+
+        let result = traced("agent.run", vec![("agent.input_chars", json!(5))], async {
+            traced("llm.call", vec![("llm.model", json!("gpt-test"))], async { Ok::<_, Error>(42) }, |_| {
+                vec![("llm.usage.total_tokens", json!(12))]
+            }).await?;
+            traced("tool.call", vec![("tool.name", json!("broken"))], async {
+                Err::<(), _>(Error::msg("tool failed"))
+            }, |_| vec![]).await
+        }, |_| vec![]).await;
+        assert!(result.is_err());
+
+        // The batch exporter blocks while sending, keep it off the runtime worker serving the collector
+        tokio::task::spawn_blocking(move || provider.shutdown()).await.unwrap().unwrap();
+
+        let payloads = received.lock().unwrap().clone();
+        let spans: Vec<Value> = payloads.iter()
+            .flat_map(|p| p["resourceSpans"].as_array().cloned().unwrap_or_default())
+            .flat_map(|rs| rs["scopeSpans"].as_array().cloned().unwrap_or_default())
+            .flat_map(|ss| ss["spans"].as_array().cloned().unwrap_or_default())
+            .collect();
+        let find = |name: &str| spans.iter().find(|s| s["name"] == name).cloned().unwrap();
+
+        let agent_span = find("agent.run");
+        let llm_span = find("llm.call");
+        let tool_span = find("tool.call");
+        assert_eq!(llm_span["parentSpanId"], agent_span["spanId"]);
+        assert_eq!(tool_span["parentSpanId"], agent_span["spanId"]);
+        assert_eq!(llm_span["traceId"], agent_span["traceId"]);
+
+        let serialized = serde_json::to_string(&spans).unwrap();
+        assert!(serialized.contains("llm.usage.total_tokens"));
+        assert!(serialized.contains("tool failed"));
+        assert!(serialized.contains("duration_ms"));
+    }
+}
@@ -0,0 +1,14 @@
+// Shared fixtures for the unit tests
+use crate::models::FakeChatModel;
+use crate::{McpAgent, SimpleMcpClient};
+use std::sync::Arc;
+
+// Client for a server that is never started, tools registered through it are only resolved, not called
+pub(crate) fn offline_mcp_client() -> Arc<SimpleMcpClient> {
+    Arc::new(SimpleMcpClient::new("http://localhost:6000".to_string()))
+}
+
+// Agent answering with the scripted responses, the model is shared so tests can inspect its requests
+pub(crate) fn fake_agent(system_prompt: &str, model: &FakeChatModel) -> McpAgent {
+    McpAgent::with_chat_model(offline_mcp_client(), system_prompt.to_string(), Arc::new(model.clone()))
+}
@@ -0,0 +1,213 @@
+// Filesystem read/write/list tool confined to a root directory
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::tools::Tool;
+use super::{describe, parse_args, resolve_in_root, verify_in_root};
+
+const DEFAULT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+// The last path component is never followed, a symlink there fails to open
+fn open_options() -> OpenOptions {
+    let mut options = OpenOptions::new();
+    #[cfg(unix)]
+    options.custom_flags(libc::O_NOFOLLOW);
+    options
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "operation", rename_all = "lowercase")]
+enum FsArgs {
+    Read { path: String },
+    Write { path: String, content: String },
+    List {
+        #[serde(default)]
+        path: Option<String>,
+    },
+}
+
+/// Reads, writes and lists files under a root directory, paths escaping the root (`..`, absolute paths, symlinks) are rejected
+#[derive(Clone)]
+pub struct FileSystemTool {
+    description: String,
+    root: PathBuf,
+    read_only: bool,
+    max_file_bytes: u64,
+}
+
+impl FileSystemTool {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            description: describe(
+                "Read, write or list files relative to the workspace directory.",
+                &Self::schema(),
+            ),
+            root: root.into(),
+            read_only: false,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+        }
+    }
+
+    /// Reject write operations
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn with_max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = max_file_bytes;
+        self
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": { "type": "string", "enum": ["read", "write", "list"] },
+                "path": { "type": "string", "description": "Path relative to the workspace directory" },
+                "content": { "type": "string", "description": "File content, required for write" }
+            },
+            "required": ["operation"]
+        })
+    }
+
+    async fn resolve(&self, path: &str) -> Result<PathBuf> {
+        resolve_in_root(&self.root, path).await
+    }
+
+    async fn run(&self, args: FsArgs) -> Result<String> {
+        match args {
+            FsArgs::Read { path } => {
+                let target = self.resolve(&path).await?;
+                let mut file = open_options().read(true).open(&target).await?;
+                let metadata = file.metadata().await?;
+                verify_in_root(&self.root, &path, &target, Some(&metadata)).await?;
+                if metadata.len() > self.max_file_bytes {
+                    return Err(Error::msg(format!("File {} has {} bytes, limit is {}", path, metadata.len(), self.max_file_bytes)));
+                }
+                let mut content = String::new();
+                file.read_to_string(&mut content).await?;
+                Ok(content)
+            }
+            FsArgs::Write { path, content } => {
+                if self.read_only {
+                    return Err(Error::msg("Workspace is read-only"));
+                }
+                if content.len() as u64 > self.max_file_bytes {
+                    return Err(Error::msg(format!("Content has {} bytes, limit is {}", content.len(), self.max_file_bytes)));
+                }
+                let target = self.resolve(&path).await?;
+                if let Some(parent) = target.parent() {
+                    tokio::fs::create_dir_all(parent).await?;
+                }
+                // Truncate only once the opened file is known to be inside the root
+                let mut file = open_options().write(true).create(true).truncate(false).open(&target).await?;
+                verify_in_root(&self.root, &path, &target, Some(&file.metadata().await?)).await?;
+                file.set_len(0).await?;
+                file.write_all(content.as_bytes()).await?;
+                file.flush().await?;
+                Ok(format!("Wrote {} bytes to {}", content.len(), path))
+            }
+            FsArgs::List { path } => {
+                let path = path.as_deref().unwrap_or(".");
+                let target = self.resolve(path).await?;
+                // Listing the canonical path and checking it again leaves no symlink to swap in between
+                let resolved = verify_in_root(&self.root, path, &target, None).await?;
+                let mut dir = tokio::fs::read_dir(&resolved).await?;
+                if verify_in_root(&self.root, path, &resolved, None).await? != resolved {
+                    return Err(Error::msg(format!("Path {} changed while it was opened", path)));
+                }
+                let mut entries = Vec::new();
+                while let Some(entry) = dir.next_entry().await? {
+                    let metadata = entry.metadata().await?;
+                    entries.push(json!({
+                        "name": entry.file_name().to_string_lossy(),
+                        "type": if metadata.is_dir() { "dir" } else { "file" },
+                        "size": metadata.len(),
+                    }));
+                }
+                entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+                Ok(Value::Array(entries).to_string())
+            }
+        }
+    }
+}
+
+impl Tool for FileSystemTool {
+    fn name(&self) -> &str {
+        "filesystem"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn args_schema(&self) -> Option<Value> {
+        Some(Self::schema())
+    }
+
+    fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        let args = parse_args::<FsArgs>(self.name(), input);
+        Box::pin(async move { self.run(args?).await })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sandboxed_read_write_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = FileSystemTool::new(dir.path());
+
+        tool.invoke(r#"{"operation": "write", "path": "config/app.toml", "content": "debug = true"}"#).await.unwrap();
+        assert_eq!(tool.invoke(r#"{"operation": "read", "path": "config/app.toml"}"#).await.unwrap(), "debug = true");
+        let listing: Value = serde_json::from_str(&tool.invoke(r#"{"operation": "list"}"#).await.unwrap()).unwrap();
+        assert_eq!(listing, json!([{ "name": "config", "type": "dir", "size": listing[0]["size"] }]));
+
+        assert!(tool.invoke(r#"{"operation": "read", "path": "../secret"}"#).await.is_err());
+        assert!(tool.invoke(r#"{"operation": "read", "path": "/etc/passwd"}"#).await.is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", dir.path().join("etc")).unwrap();
+            assert!(tool.invoke(r#"{"operation": "read", "path": "etc/hostname"}"#).await.is_err());
+        }
+
+        let read_only = FileSystemTool::new(dir.path()).with_read_only(true);
+        assert!(read_only.invoke(r#"{"operation": "write", "path": "a.txt", "content": "x"}"#).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_path_swapped_after_resolve_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        std::fs::create_dir(dir.path().join("notes")).unwrap();
+        let tool = FileSystemTool::new(dir.path());
+
+        // notes/ checks out, then is replaced by a symlink out of the root before the open
+        let target = tool.resolve("notes/secret.txt").await.unwrap();
+        std::fs::remove_dir(dir.path().join("notes")).unwrap();
+        std::os::unix::fs::symlink(outside.path(), dir.path().join("notes")).unwrap();
+        let file = std::fs::File::open(&target).unwrap();
+        let error = verify_in_root(dir.path(), "notes/secret.txt", &target, Some(&file.metadata().unwrap())).await.unwrap_err();
+        assert!(error.to_string().contains("escapes the workspace"));
+
+        // A symlink as the last component is never followed
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), dir.path().join("link.txt")).unwrap();
+        assert!(tool.invoke(r#"{"operation": "write", "path": "link.txt", "content": "x"}"#).await.is_err());
+        assert_eq!(std::fs::read_to_string(outside.path().join("secret.txt")).unwrap(), "secret");
+    }
+}
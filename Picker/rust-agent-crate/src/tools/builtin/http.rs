@@ -0,0 +1,199 @@
+// HTTP GET/POST tool restricted to a domain allowlist
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::tools::Tool;
+use super::{describe, parse_args};
+
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+#[derive(Deserialize)]
+struct HttpArgs {
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<Value>,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+/// Sends GET/POST requests to allowlisted domains, the response body is truncated to a size cap
+///
+/// A domain in the allowlist also allows its subdomains, an empty allowlist denies every request.
+#[derive(Clone)]
+pub struct HttpRequestTool {
+    description: String,
+    allowed_domains: Arc<Vec<String>>,
+    max_response_bytes: usize,
+    timeout: Duration,
+    // Built once so connections are pooled across calls, clones share it
+    client: reqwest::Client,
+}
+
+impl HttpRequestTool {
+    pub fn new(allowed_domains: Vec<String>) -> Self {
+        let allowed_domains: Arc<Vec<String>> = Arc::new(allowed_domains.into_iter().map(|d| d.to_lowercase()).collect());
+        Self {
+            description: describe(
+                "Send an HTTP GET or POST request and return the status and response body.",
+                &Self::schema(),
+            ),
+            client: Self::build_client(allowed_domains.clone()),
+            allowed_domains,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    // Redirects must stay on allowed domains too
+    fn build_client(allowed_domains: Arc<Vec<String>>) -> reqwest::Client {
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 5 {
+                    attempt.error("too many redirects")
+                } else if Self::is_allowed(&allowed_domains, attempt.url()) {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }))
+            .build()
+            .expect("HTTP client with the default TLS backend")
+    }
+
+    pub fn with_max_response_bytes(mut self, max_response_bytes: usize) -> Self {
+        self.max_response_bytes = max_response_bytes;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": { "type": "string", "description": "http(s) URL on an allowed domain" },
+                "method": { "type": "string", "enum": ["GET", "POST"], "default": "GET" },
+                "headers": { "type": "object", "additionalProperties": { "type": "string" } },
+                "body": { "description": "POST body, objects are sent as JSON" }
+            },
+            "required": ["url"]
+        })
+    }
+
+    fn is_allowed(allowed_domains: &[String], url: &reqwest::Url) -> bool {
+        if !matches!(url.scheme(), "http" | "https") {
+            return false;
+        }
+        let Some(host) = url.host_str().map(|h| h.to_lowercase()) else {
+            return false;
+        };
+        allowed_domains.iter().any(|domain| host == *domain || host.ends_with(&format!(".{}", domain)))
+    }
+
+    async fn request(&self, args: HttpArgs) -> Result<String> {
+        let url = reqwest::Url::parse(&args.url)
+            .map_err(|e| Error::msg(format!("Invalid URL {}: {}", args.url, e)))?;
+        if !Self::is_allowed(&self.allowed_domains, &url) {
+            return Err(Error::msg(format!("URL {} is not in the allowed domains", url)));
+        }
+
+        let client = &self.client;
+        let mut request = match args.method.to_uppercase().as_str() {
+            "GET" => client.get(url),
+            "POST" => match args.body {
+                Some(Value::String(body)) => client.post(url).body(body),
+                Some(body) => client.post(url).json(&body),
+                None => client.post(url),
+            },
+            other => return Err(Error::msg(format!("Unsupported HTTP method {}", other))),
+        };
+        for (name, value) in &args.headers {
+            request = request.header(name, value);
+        }
+        request = request.timeout(self.timeout);
+
+        let mut response = request.send().await?;
+        let status = response.status().as_u16();
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = response.chunk().await? {
+            let remaining = self.max_response_bytes - body.len();
+            if chunk.len() > remaining {
+                body.extend_from_slice(&chunk[..remaining]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(json!({
+            "status": status,
+            "body": String::from_utf8_lossy(&body),
+            "truncated": truncated,
+        }).to_string())
+    }
+}
+
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn args_schema(&self) -> Option<Value> {
+        Some(Self::schema())
+    }
+
+    fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        let args = parse_args::<HttpArgs>(self.name(), input);
+        Box::pin(async move { self.request(args?).await })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+
+    #[tokio::test]
+    async fn test_allowlist_and_size_cap() {
+        let app = Router::new().route("/big", get(|| async { "x".repeat(100) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let tool = HttpRequestTool::new(vec!["127.0.0.1".to_string()]).with_max_response_bytes(10);
+        let output: Value = serde_json::from_str(
+            &tool.invoke(&json!({ "url": format!("http://{}/big", addr) }).to_string()).await.unwrap()
+        ).unwrap();
+        assert_eq!(output["status"], 200);
+        assert_eq!(output["body"], "x".repeat(10));
+        assert_eq!(output["truncated"], true);
+
+        let denied = tool.invoke(r#"{"url": "http://example.com/"}"#).await.unwrap_err();
+        assert!(denied.to_string().contains("not in the allowed domains"));
+        assert!(tool.invoke(r#"{"url": "file:///etc/passwd"}"#).await.is_err());
+    }
+}
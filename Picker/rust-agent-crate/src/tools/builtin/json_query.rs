@@ -0,0 +1,189 @@
+// JSONPath-style query tool
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::tools::Tool;
+use super::{describe, parse_args};
+
+#[derive(Deserialize)]
+struct QueryArgs {
+    json: Value,
+    query: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    // `..key` matches the key at any depth
+    Descendant(String),
+}
+
+fn parse_query(query: &str) -> Result<Vec<Segment>> {
+    let invalid = |reason: &str| Error::msg(format!("Invalid query {}: {}", query, reason));
+    let mut rest = query.trim().strip_prefix('$').unwrap_or(query.trim());
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid("expected a key after .."));
+            }
+            segments.push(Segment::Descendant(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            match &after[..end] {
+                "" => return Err(invalid("empty key")),
+                "*" => segments.push(Segment::Wildcard),
+                key => segments.push(Segment::Key(key.to_string())),
+            }
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed ["))?;
+            let inner = after[..end].trim();
+            if inner == "*" {
+                segments.push(Segment::Wildcard);
+            } else if let Some(key) = inner.strip_prefix(['"', '\'']).and_then(|k| k.strip_suffix(['"', '\''])) {
+                segments.push(Segment::Key(key.to_string()));
+            } else {
+                segments.push(Segment::Index(inner.parse().map_err(|_| invalid("index must be an integer"))?));
+            }
+            rest = &after[end + 1..];
+        } else {
+            // Allow a leading bare key, e.g. `items[0].name`
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            segments.push(Segment::Key(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+    }
+    Ok(segments)
+}
+
+fn collect_descendants<'a>(value: &'a Value, key: &str, matches: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(found) = map.get(key) {
+                matches.push(found);
+            }
+            map.values().for_each(|child| collect_descendants(child, key, matches));
+        }
+        Value::Array(items) => items.iter().for_each(|child| collect_descendants(child, key, matches)),
+        _ => {}
+    }
+}
+
+/// Evaluate a JSONPath-style query (`$.a.b`, `[0]`, `[-1]`, `[*]`, `.*`, `["key"]`, `..key`) and return every match
+pub fn query_json(value: &Value, query: &str) -> Result<Vec<Value>> {
+    let mut current = vec![value];
+    for segment in parse_query(query)? {
+        let mut next = Vec::new();
+        for value in current {
+            match (&segment, value) {
+                (Segment::Key(key), Value::Object(map)) => next.extend(map.get(key)),
+                (Segment::Index(index), Value::Array(items)) => {
+                    let index = if *index < 0 { items.len() as i64 + index } else { *index };
+                    next.extend(usize::try_from(index).ok().and_then(|i| items.get(i)));
+                }
+                (Segment::Wildcard, Value::Array(items)) => next.extend(items.iter()),
+                (Segment::Wildcard, Value::Object(map)) => next.extend(map.values()),
+                (Segment::Descendant(key), value) => collect_descendants(value, key, &mut next),
+                _ => {}
+            }
+        }
+        current = next;
+    }
+    Ok(current.into_iter().cloned().collect())
+}
+
+/// Runs JSONPath-style queries over a JSON document, returns the matches as a JSON array
+#[derive(Clone)]
+pub struct JsonQueryTool {
+    description: String,
+}
+
+impl JsonQueryTool {
+    pub fn new() -> Self {
+        Self {
+            description: describe(
+                "Extract values from a JSON document with a JSONPath-style query such as $.items[*].name; returns an array of matches.",
+                &Self::schema(),
+            ),
+        }
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "json": { "description": "JSON document, or a string containing JSON" },
+                "query": { "type": "string", "description": "Query, e.g. $.items[0].name, $..id or $.items[*]" }
+            },
+            "required": ["json", "query"]
+        })
+    }
+}
+
+impl Default for JsonQueryTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tool for JsonQueryTool {
+    fn name(&self) -> &str {
+        "json_query"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn args_schema(&self) -> Option<Value> {
+        Some(Self::schema())
+    }
+
+    fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        let args = parse_args::<QueryArgs>(self.name(), input);
+        Box::pin(async move {
+            let args = args?;
+            // Documents fetched by other tools usually arrive as strings
+            let document = match args.json {
+                Value::String(text) => serde_json::from_str(&text).unwrap_or(Value::String(text)),
+                other => other,
+            };
+            Ok(Value::Array(query_json(&document, &args.query)?).to_string())
+        })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_json() {
+        let doc = json!({
+            "tasks": [
+                { "id": 1, "name": "sync", "owner": { "id": 7 } },
+                { "id": 2, "name": "backup" }
+            ],
+            "meta": { "total": 2 }
+        });
+        assert_eq!(query_json(&doc, "$.tasks[*].name").unwrap(), vec![json!("sync"), json!("backup")]);
+        assert_eq!(query_json(&doc, "$.tasks[-1].id").unwrap(), vec![json!(2)]);
+        assert_eq!(query_json(&doc, "meta['total']").unwrap(), vec![json!(2)]);
+        assert_eq!(query_json(&doc, "$..id").unwrap(), vec![json!(1), json!(7), json!(2)]);
+        assert!(query_json(&doc, "$.missing.key").unwrap().is_empty());
+        assert!(query_json(&doc, "$.tasks[x]").is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// Built-in general-purpose tools, enabled with the `builtin-tools` feature
+mod http;
+mod fs;
+mod json_query;
+mod shell;
+
+pub use http::HttpRequestTool;
+pub use fs::FileSystemTool;
+pub use json_query::{JsonQueryTool, query_json};
+pub use shell::ShellTool;
+
+use anyhow::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+use super::Tool;
+
+// Parse tool input against the tool's argument struct
+fn parse_args<T: DeserializeOwned>(tool: &str, input: &str) -> Result<T> {
+    serde_json::from_str(input)
+        .map_err(|e| Error::msg(format!("Invalid arguments for {}: {}", tool, e)))
+}
+
+// Description shown to the model, with the argument schema inlined
+fn describe(description: &str, schema: &Value) -> String {
+    format!("{} Parameters (JSON schema): {}", description, schema)
+}
+
+// Resolve a relative path inside the root, the deepest existing ancestor must stay inside the root after resolving symlinks
+async fn resolve_in_root(root: &Path, path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(Error::msg(format!("Path {} must be relative and stay inside the workspace", path)));
+    }
+    let root = tokio::fs::canonicalize(root).await
+        .map_err(|e| Error::msg(format!("Workspace {} is not accessible: {}", root.display(), e)))?;
+    let target = root.join(relative);
+
+    let mut existing = target.as_path();
+    while tokio::fs::symlink_metadata(existing).await.is_err() {
+        existing = existing.parent().unwrap_or(&root);
+    }
+    if !tokio::fs::canonicalize(existing).await?.starts_with(&root) {
+        return Err(Error::msg(format!("Path {} escapes the workspace", path)));
+    }
+    Ok(target)
+}
+
+// Re-check a path after opening it: a component swapped for a symlink since resolve_in_root makes the
+// canonical path leave the root, or name a different file than the opened one
+async fn verify_in_root(root: &Path, path: &str, target: &Path, opened: Option<&std::fs::Metadata>) -> Result<PathBuf> {
+    let root = tokio::fs::canonicalize(root).await?;
+    let resolved = tokio::fs::canonicalize(target).await?;
+    if !resolved.starts_with(&root) {
+        return Err(Error::msg(format!("Path {} escapes the workspace", path)));
+    }
+    #[cfg(unix)]
+    if let Some(opened) = opened {
+        use std::os::unix::fs::MetadataExt;
+        let current = tokio::fs::metadata(&resolved).await?;
+        if (opened.dev(), opened.ino()) != (current.dev(), current.ino()) {
+            return Err(Error::msg(format!("Path {} changed while it was opened", path)));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = opened;
+    Ok(resolved)
+}
+
+// Built-in tools are cheap to clone, used by agents that hand out copies of their tools
+pub(crate) fn clone_builtin_tool(tool: &dyn Tool) -> Option<Box<dyn Tool + Send + Sync>> {
+    let any = tool.as_any();
+    if let Some(tool) = any.downcast_ref::<HttpRequestTool>() {
+        Some(Box::new(tool.clone()))
+    } else if let Some(tool) = any.downcast_ref::<FileSystemTool>() {
+        Some(Box::new(tool.clone()))
+    } else if let Some(tool) = any.downcast_ref::<ShellTool>() {
+        Some(Box::new(tool.clone()))
+    } else {
+        any.downcast_ref::<JsonQueryTool>().map(|tool| Box::new(tool.clone()) as Box<dyn Tool + Send + Sync>)
+    }
+}
@@ -0,0 +1,277 @@
+// Shell tool running allowlisted commands inside a workspace directory
+use anyhow::{Error, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+use crate::tools::Tool;
+use super::{describe, parse_args, resolve_in_root};
+
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+struct ShellArgs {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    stdin: Option<String>,
+}
+
+/// Runs allowlisted commands (no shell interpretation) confined to a working directory
+///
+/// The child gets a scrubbed environment (only `PATH`, `HOME` set to the workspace, `LANG` and explicit
+/// `with_env` variables), its output is truncated, and it is killed with its process group on timeout.
+/// CPU time and address space limits are applied with rlimits on Linux.
+#[derive(Clone)]
+pub struct ShellTool {
+    description: String,
+    allowed_commands: Arc<Vec<String>>,
+    working_dir: PathBuf,
+    env: HashMap<String, String>,
+    timeout: Duration,
+    max_output_bytes: usize,
+    cpu_limit_secs: Option<u64>,
+    memory_limit_bytes: Option<u64>,
+}
+
+impl ShellTool {
+    pub fn new(allowed_commands: Vec<String>, working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            description: describe(
+                &format!("Run a command in the workspace directory and return its exit code and output. Allowed commands: {}.", allowed_commands.join(", ")),
+                &Self::schema(),
+            ),
+            allowed_commands: Arc::new(allowed_commands),
+            working_dir: working_dir.into(),
+            env: HashMap::new(),
+            timeout: Duration::from_secs(30),
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            cpu_limit_secs: None,
+            memory_limit_bytes: None,
+        }
+    }
+
+    /// Pass an environment variable to every command
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Cap applied to stdout and stderr separately
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// RLIMIT_CPU on Linux, ignored elsewhere
+    pub fn with_cpu_limit_secs(mut self, seconds: u64) -> Self {
+        self.cpu_limit_secs = Some(seconds);
+        self
+    }
+
+    /// RLIMIT_AS on Linux, ignored elsewhere
+    pub fn with_memory_limit_bytes(mut self, bytes: u64) -> Self {
+        self.memory_limit_bytes = Some(bytes);
+        self
+    }
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "command": { "type": "string", "description": "Name of an allowed command" },
+                "args": { "type": "array", "items": { "type": "string" } },
+                "cwd": { "type": "string", "description": "Directory relative to the workspace" },
+                "stdin": { "type": "string" }
+            },
+            "required": ["command"]
+        })
+    }
+
+    fn build_command(&self, args: &ShellArgs, cwd: PathBuf) -> Command {
+        let mut command = Command::new(&args.command);
+        command.args(&args.args)
+            .current_dir(cwd)
+            .env_clear()
+            .env("HOME", &self.working_dir)
+            .env("LANG", "C.UTF-8")
+            .stdin(if args.stdin.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Ok(path) = std::env::var("PATH") {
+            command.env("PATH", path);
+        }
+        command.envs(&self.env);
+
+        #[cfg(unix)]
+        command.process_group(0);
+
+        #[cfg(target_os = "linux")]
+        {
+            let cpu_limit = self.cpu_limit_secs;
+            let memory_limit = self.memory_limit_bytes;
+            if cpu_limit.is_some() || memory_limit.is_some() {
+                // Only async-signal-safe calls are allowed between fork and exec
+                unsafe {
+                    command.pre_exec(move || {
+                        let set = |resource, value: u64| {
+                            let limit = libc::rlimit { rlim_cur: value as libc::rlim_t, rlim_max: value as libc::rlim_t };
+                            if libc::setrlimit(resource, &limit) != 0 {
+                                return Err(std::io::Error::last_os_error());
+                            }
+                            Ok(())
+                        };
+                        if let Some(seconds) = cpu_limit {
+                            set(libc::RLIMIT_CPU, seconds)?;
+                        }
+                        if let Some(bytes) = memory_limit {
+                            set(libc::RLIMIT_AS, bytes)?;
+                        }
+                        Ok(())
+                    });
+                }
+            }
+        }
+
+        command
+    }
+
+    async fn run(&self, args: ShellArgs) -> Result<String> {
+        if args.command.contains(['/', '\\']) || !self.allowed_commands.contains(&args.command) {
+            return Err(Error::msg(format!("Command {} is not allowed", args.command)));
+        }
+        let cwd = resolve_in_root(&self.working_dir, args.cwd.as_deref().unwrap_or(".")).await?;
+
+        let mut child = self.build_command(&args, cwd).spawn()
+            .map_err(|e| Error::msg(format!("Failed to start {}: {}", args.command, e)))?;
+        if let (Some(mut stdin), Some(input)) = (child.stdin.take(), args.stdin) {
+            tokio::spawn(async move {
+                let _ = stdin.write_all(input.as_bytes()).await;
+            });
+        }
+        let stdout = tokio::spawn(read_capped(child.stdout.take(), self.max_output_bytes));
+        let stderr = tokio::spawn(read_capped(child.stderr.take(), self.max_output_bytes));
+
+        let (exit_code, timed_out) = match tokio::time::timeout(self.timeout, child.wait()).await {
+            Ok(status) => (status?.code(), false),
+            Err(_) => {
+                // Kill the whole process group so grandchildren do not keep the pipes open
+                #[cfg(unix)]
+                if let Some(pid) = child.id() {
+                    unsafe {
+                        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+                    }
+                }
+                let _ = child.kill().await;
+                (None, true)
+            }
+        };
+
+        let (stdout, stdout_truncated) = stdout.await?;
+        let (stderr, stderr_truncated) = stderr.await?;
+        Ok(json!({
+            "exit_code": exit_code,
+            "timed_out": timed_out,
+            "stdout": stdout,
+            "stderr": stderr,
+            "truncated": stdout_truncated || stderr_truncated,
+        }).to_string())
+    }
+}
+
+// Read a pipe to the end, keeping at most `cap` bytes so the child never blocks on a full pipe
+async fn read_capped(reader: Option<impl AsyncRead + Unpin>, cap: usize) -> (String, bool) {
+    let Some(mut reader) = reader else {
+        return (String::new(), false);
+    };
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buffer = [0u8; 8192];
+    while let Ok(read) = reader.read(&mut buffer).await {
+        if read == 0 {
+            break;
+        }
+        let remaining = cap.saturating_sub(kept.len());
+        kept.extend_from_slice(&buffer[..read.min(remaining)]);
+        truncated |= read > remaining;
+    }
+    (String::from_utf8_lossy(&kept).into_owned(), truncated)
+}
+
+impl Tool for ShellTool {
+    fn name(&self) -> &str {
+        "shell"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn args_schema(&self) -> Option<Value> {
+        Some(Self::schema())
+    }
+
+    fn invoke(&self, input: &str) -> Pin<Box<dyn Future<Output = Result<String, Error>> + Send + '_>> {
+        let args = parse_args::<ShellArgs>(self.name(), input);
+        Box::pin(async move { self.run(args?).await })
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_allowlist_env_truncation_and_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let tool = ShellTool::new(vec!["sh".to_string(), "env".to_string()], dir.path())
+            .with_env("TASK_ID", "42")
+            .with_max_output_bytes(16)
+            .with_timeout(Duration::from_millis(500));
+
+        let output: Value = serde_json::from_str(&tool.invoke(r#"{"command": "env"}"#).await.unwrap()).unwrap();
+        assert_eq!(output["exit_code"], 0);
+        assert_eq!(output["truncated"], true);
+        let full = ShellTool::new(vec!["env".to_string()], dir.path()).with_env("TASK_ID", "42");
+        let output: Value = serde_json::from_str(&full.invoke(r#"{"command": "env"}"#).await.unwrap()).unwrap();
+        let stdout = output["stdout"].as_str().unwrap();
+        assert!(stdout.contains("TASK_ID=42"));
+        // Nothing is inherited from the parent environment except PATH, and HOME points at the sandbox
+        assert!(stdout.lines().any(|line| line == format!("HOME={}", dir.path().display())));
+        let mut keys: Vec<&str> = stdout.lines().filter_map(|line| line.split('=').next()).collect();
+        keys.sort();
+        assert_eq!(keys, vec!["HOME", "LANG", "PATH", "TASK_ID"]);
+
+        assert!(tool.invoke(r#"{"command": "rm", "args": ["-rf", "."]}"#).await.is_err());
+        assert!(tool.invoke(r#"{"command": "/bin/sh"}"#).await.is_err());
+        assert!(tool.invoke(r#"{"command": "sh", "cwd": ".."}"#).await.is_err());
+
+        let started = std::time::Instant::now();
+        let output: Value = serde_json::from_str(
+            &tool.invoke(r#"{"command": "sh", "args": ["-c", "sleep 5 & sleep 5"]}"#).await.unwrap()
+        ).unwrap();
+        assert_eq!(output["timed_out"], true);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -1,7 +1,11 @@
 // Tools module definition
 mod tool;
 mod utils;
+#[cfg(feature = "builtin-tools")]
+pub mod builtin;
 
 // Re-export module content
-pub use tool::{Tool, Toolkit, ExampleTool, ExampleToolkit};
+pub use tool::{Tool, Toolkit, ExampleTool, ExampleToolkit, ToolContext, ToolProgress, ProgressReporter};
 pub use utils::{find_matching_tool_index, parse_model_output};
+#[cfg(feature = "builtin-tools")]
+pub use builtin::{HttpRequestTool, FileSystemTool, JsonQueryTool, ShellTool};
@@ -1,6 +1,101 @@
 // Tool interface and implementation
 use anyhow::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::pin::Pin;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use crate::core::{RunnableConfig, RunnableInterrupt};
+
+/// Progress update of a long-running tool call, mirrors MCP `notifications/progress`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolProgress {
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ToolProgress {
+    /// Progress in percent, when the total is known
+    pub fn percentage(&self) -> Option<f64> {
+        self.total.filter(|total| *total > 0.0).map(|total| self.progress / total * 100.0)
+    }
+}
+
+/// Receives progress updates of a tool call
+#[derive(Clone)]
+pub struct ProgressReporter {
+    sink: Arc<dyn Fn(ToolProgress) + Send + Sync>,
+}
+
+impl ProgressReporter {
+    pub fn new(sink: impl Fn(ToolProgress) + Send + Sync + 'static) -> Self {
+        Self { sink: Arc::new(sink) }
+    }
+
+    pub fn report(&self, progress: ToolProgress) {
+        (self.sink)(progress);
+    }
+
+    /// Report a percentage between 0 and 100
+    pub fn percent(&self, percent: f64, message: &str) {
+        self.report(ToolProgress {
+            progress: percent,
+            total: Some(100.0),
+            message: Some(message.to_string()).filter(|m| !m.is_empty()),
+        });
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressReporter")
+    }
+}
+
+/// Optional progress reporter and cancellation token passed to `Tool::invoke_with_context`
+#[derive(Debug, Clone, Default)]
+pub struct ToolContext {
+    pub progress: Option<ProgressReporter>,
+    pub cancellation_token: CancellationToken,
+    /// Config of the calling run, tools running nested runnables (e.g. agents) inherit its callbacks, tags and metadata
+    pub config: Option<RunnableConfig>,
+}
+
+impl ToolContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_progress(mut self, progress: ProgressReporter) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = token;
+        self
+    }
+
+    pub fn with_config(mut self, config: RunnableConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Report progress if a reporter is attached
+    pub fn report_progress(&self, percent: f64, message: &str) {
+        if let Some(progress) = &self.progress {
+            progress.percent(percent, message);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+}
 
 // Minimal tool interface (aligned with langchain-core)
 pub trait Tool: Send + Sync {
@@ -9,9 +104,26 @@ pub trait Tool: Send + Sync {
     
     fn description(&self) -> &str;
     
+    // JSON schema of the tool arguments, if the tool declares one
+    fn args_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+    
     // Core execution method
     fn invoke(&self, input: &str) -> Pin<Box<dyn std::future::Future<Output = Result<String, Error>> + Send + '_>>;
     
+    // Execution with progress reporting and cancellation, long-running tools override it to report progress
+    // and stop cooperatively; the default runs invoke and gives up with RunnableInterrupt::Cancelled
+    fn invoke_with_context(&self, input: &str, context: ToolContext) -> Pin<Box<dyn std::future::Future<Output = Result<String, Error>> + Send + '_>> {
+        let future = self.invoke(input);
+        Box::pin(async move {
+            tokio::select! {
+                _ = context.cancellation_token.cancelled() => Err(Error::new(RunnableInterrupt::Cancelled)),
+                result = future => result,
+            }
+        })
+    }
+    
     // Add as_any method to support runtime type checking
     fn as_any(&self) -> &dyn std::any::Any;
 }
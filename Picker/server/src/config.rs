@@ -13,6 +13,8 @@ pub struct Config {
     pub pending_registration: PendingRegistrationConfig,
     pub blockchain: BlockchainConfig,
     pub premium: PremiumConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -52,6 +54,13 @@ pub struct PremiumConfig {
     pub start: bool,
 }
 
+// 管理员配置，启动时将这些邮箱对应的已注册用户设为管理员
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct AdminConfig {
+    #[serde(default)]
+    pub emails: Vec<String>,
+}
+
 impl Config {
     pub fn from_file() -> Result<Self, config::ConfigError> {
         let mut builder = config::Config::builder();
@@ -114,6 +123,7 @@ pub struct AppState {
     pub blockchain_authorized_contract_address: String,
     pub blockchain_retry_times: i8,
     pub blockchain_retry_interval_seconds: i8,
+    pub admin_emails: Vec<String>,
     pub verification_codes: Arc<Mutex<HashMap<String, VerificationCode>>>,
     pub download_tokens: Arc<Mutex<HashMap<String, DownloadToken>>>,
     pub pending_registrations: Arc<Mutex<HashMap<String, PendingRegistration>>>,
@@ -153,6 +163,7 @@ impl AppState {
                     period: 30,
                     start: true,
                 },
+                admin: AdminConfig::default(),
             }
         });
 
@@ -175,6 +186,7 @@ impl AppState {
             premium_free: config.premium.free,
             premium_period: config.premium.period,
             premium_start: config.premium.start,
+            admin_emails: config.admin.emails,
             verification_codes: Arc::new(Mutex::new(HashMap::new())),
             download_tokens: Arc::new(Mutex::new(HashMap::new())),
             pending_registrations: Arc::new(Mutex::new(HashMap::new())),
@@ -315,6 +327,7 @@ mod tests {
         let valid_token = DownloadToken {
             token: "valid_token".to_string(),
             order_id: order_id1,
+            version_id: None,
             expires_at: now + Duration::minutes(10),
         };
         
@@ -322,6 +335,7 @@ mod tests {
         let expired_token = DownloadToken {
             token: "expired_token".to_string(),
             order_id: order_id2,
+            version_id: None,
             expires_at: now - Duration::minutes(10),
         };
         
@@ -393,6 +407,7 @@ mod tests {
             let token = DownloadToken {
                 token: format!("token{}", i),
                 order_id: Uuid::new_v4(),
+                version_id: None,
                 expires_at: now + Duration::minutes(10),
             };
             state.download_tokens.lock().unwrap().insert(format!("token{}", i), token);
@@ -505,6 +520,7 @@ mod tests {
                 let token = DownloadToken {
                     token: format!("token{}", i),
                     order_id: Uuid::new_v4(),
+                    version_id: None,
                     expires_at: Utc::now() + Duration::minutes(10),
                 };
                 state_clone1.download_tokens.lock().unwrap().insert(format!("token{}", i), token);
@@ -516,6 +532,7 @@ mod tests {
                 let token = DownloadToken {
                     token: format!("token{}", i),
                     order_id: Uuid::new_v4(),
+                    version_id: None,
                     expires_at: Utc::now() + Duration::minutes(10),
                 };
                 state_clone2.download_tokens.lock().unwrap().insert(format!("token{}", i), token);
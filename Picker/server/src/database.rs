@@ -1,4 +1,4 @@
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
+use sqlx::{sqlite::SqlitePoolOptions, Acquire, Pool, Sqlite};
 use tracing::info;
 use uuid::Uuid;
 use chrono::Utc;
@@ -59,62 +59,91 @@ pub async fn init_database(pool: &DbPool) -> Result<(), sqlx::Error> {
 
     // 创建用户表
     // user_password 加密存储使用的 salt 是 user_id<UUID>字符串与"openpick"字符串的组合，这样使得每个用户的密码都有独立的加密salt，密码存储更加安全
+    // 旧库中没有 admin 类型和 status 列，需要重建用户表
+    upgrade_table(pool, "users", users_table_sql, "'suspended'").await?;
+
+    // 创建Picker表
+    // 旧库中没有审核相关的状态和评分统计列，需要重建Picker表
+    upgrade_table(pool, "pickers", pickers_table_sql, "rating_total").await?;
+
+    // 创建订单表
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS users (
-            user_id BLOB PRIMARY KEY,
-            email TEXT UNIQUE NOT NULL,
-            user_name TEXT NOT NULL,
-            user_password TEXT NOT NULL,
-            user_type TEXT NOT NULL CHECK (user_type IN ('gen', 'dev')),
-            private_key TEXT NOT NULL,
-            wallet_address TEXT NOT NULL,
-            premium_balance INTEGER DEFAULT 0,
-            created_at TEXT NOT NULL
+        CREATE TABLE IF NOT EXISTS orders (
+            order_id BLOB PRIMARY KEY,
+            status TEXT NOT NULL CHECK (status IN ('pending', 'success', 'expired')),
+            user_id BLOB NOT NULL,
+            picker_id BLOB NOT NULL,
+            pay_type TEXT NOT NULL CHECK (pay_type IN ('wallet', 'premium')),
+            amount INTEGER NOT NULL,
+            tx_hash TEXT,
+            created_at TEXT NOT NULL,
+            expires_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE,
+            FOREIGN KEY (picker_id) REFERENCES pickers (picker_id) ON DELETE CASCADE
         )
         "#,
     )
     .execute(pool)
     .await?;
 
-    // 创建Picker表
+    // 创建Picker版本表，checksum 为文件的 SHA-256
+    // 旧库中没有版本审核状态，需要重建版本表
+    upgrade_table(pool, "picker_versions", picker_versions_table_sql, "review_notes").await?;
+
+    // 创建Picker评价表，每个用户对每个Picker只能有一条评价
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS pickers (
-            picker_id BLOB PRIMARY KEY,
-            dev_user_id BLOB NOT NULL,
-            alias TEXT NOT NULL,
-            description TEXT NOT NULL,
-            price INTEGER NOT NULL,
-            image_path TEXT NOT NULL,
-            file_path TEXT NOT NULL,
-            version TEXT NOT NULL,
-            status TEXT NOT NULL CHECK (status IN ('active', 'inactive')),
-            download_count INTEGER DEFAULT 0,
+        CREATE TABLE IF NOT EXISTS picker_reviews (
+            review_id BLOB PRIMARY KEY,
+            picker_id BLOB NOT NULL,
+            user_id BLOB NOT NULL,
+            rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+            content TEXT NOT NULL,
+            dev_reply TEXT,
+            replied_at TEXT,
             created_at TEXT NOT NULL,
             updated_at TEXT NOT NULL,
-            FOREIGN KEY (dev_user_id) REFERENCES users (user_id)
+            UNIQUE (picker_id, user_id),
+            FOREIGN KEY (picker_id) REFERENCES pickers (picker_id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
         )
         "#,
     )
     .execute(pool)
     .await?;
 
-    // 创建订单表
+    // 创建管理员审计日志表，details 为操作前后的 JSON 快照
     sqlx::query(
         r#"
-        CREATE TABLE IF NOT EXISTS orders (
-            order_id BLOB PRIMARY KEY,
-            status TEXT NOT NULL CHECK (status IN ('pending', 'success', 'expired')),
+        CREATE TABLE IF NOT EXISTS admin_audit_logs (
+            log_id BLOB PRIMARY KEY,
+            admin_user_id BLOB NOT NULL,
+            action TEXT NOT NULL,
+            target_type TEXT NOT NULL CHECK (target_type IN ('user', 'picker', 'order')),
+            target_id BLOB NOT NULL,
+            reason TEXT NOT NULL,
+            details TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (admin_user_id) REFERENCES users (user_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // 创建站内通知表
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS notifications (
+            notification_id BLOB PRIMARY KEY,
             user_id BLOB NOT NULL,
-            picker_id BLOB NOT NULL,
-            pay_type TEXT NOT NULL CHECK (pay_type IN ('wallet', 'premium')),
-            amount INTEGER NOT NULL,
-            tx_hash TEXT,
+            picker_id BLOB,
+            kind TEXT NOT NULL,
+            message TEXT NOT NULL,
+            is_read INTEGER NOT NULL DEFAULT 0,
             created_at TEXT NOT NULL,
-            expires_at TEXT,
-            FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE,
-            FOREIGN KEY (picker_id) REFERENCES pickers (picker_id) ON DELETE CASCADE
+            FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
         )
         "#,
     )
@@ -146,10 +175,172 @@ pub async fn init_database(pool: &DbPool) -> Result<(), sqlx::Error> {
         .execute(pool)
         .await?;
 
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_picker_versions_picker_id ON picker_versions (picker_id)")
+        .execute(pool)
+        .await?;
+
+    // 每个Picker同时最多一个等待审核的版本
+    sqlx::query("CREATE UNIQUE INDEX IF NOT EXISTS idx_picker_versions_pending ON picker_versions (picker_id) WHERE status = 'pending'")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_picker_reviews_picker_id ON picker_reviews (picker_id)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_admin_audit_logs_created_at ON admin_audit_logs (created_at)")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_notifications_user_id ON notifications (user_id)")
+        .execute(pool)
+        .await?;
+
     insert_test_data(pool).await?;
     Ok(())
 }
 
+// Picker版本表结构
+// 升级前的版本都已经提供下载，默认视为审核通过；新版本插入时显式写入 pending
+fn picker_versions_table_sql(table: &str) -> String {
+    format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {table} (
+            version_id BLOB PRIMARY KEY,
+            picker_id BLOB NOT NULL,
+            version TEXT NOT NULL,
+            changelog TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'approved' CHECK (status IN ('pending', 'approved', 'rejected')),
+            review_notes TEXT,
+            UNIQUE (picker_id, version),
+            FOREIGN KEY (picker_id) REFERENCES pickers (picker_id) ON DELETE CASCADE
+        )
+        "#
+    )
+}
+
+// 用户表结构
+fn users_table_sql(table: &str) -> String {
+    format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {table} (
+            user_id BLOB PRIMARY KEY,
+            email TEXT UNIQUE NOT NULL,
+            user_name TEXT NOT NULL,
+            user_password TEXT NOT NULL,
+            user_type TEXT NOT NULL CHECK (user_type IN ('gen', 'dev', 'admin')),
+            private_key TEXT NOT NULL,
+            wallet_address TEXT NOT NULL,
+            premium_balance INTEGER DEFAULT 0,
+            created_at TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'suspended'))
+        )
+        "#
+    )
+}
+
+// Picker表结构
+fn pickers_table_sql(table: &str) -> String {
+    format!(
+        r#"
+        CREATE TABLE IF NOT EXISTS {table} (
+            picker_id BLOB PRIMARY KEY,
+            dev_user_id BLOB NOT NULL,
+            alias TEXT NOT NULL,
+            description TEXT NOT NULL,
+            price INTEGER NOT NULL,
+            image_path TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            version TEXT NOT NULL,
+            status TEXT NOT NULL CHECK (status IN ('draft', 'pending_review', 'active', 'rejected', 'suspended', 'inactive')),
+            download_count INTEGER DEFAULT 0,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            review_notes TEXT,
+            submitted_at TEXT,
+            reviewed_at TEXT,
+            rating_count INTEGER NOT NULL DEFAULT 0,
+            rating_total INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (dev_user_id) REFERENCES users (user_id)
+        )
+        "#
+    )
+}
+
+/// 创建表，或在旧库的表定义缺少 marker 时重建该表
+///
+/// SQLite 无法修改 CHECK 约束，这里按官方推荐的步骤新建表、复制同名列的数据、删除旧表后改名，
+/// 新增列使用建表语句中的默认值。
+async fn upgrade_table(pool: &DbPool, table: &str, create_sql: fn(&str) -> String, marker: &str) -> Result<(), sqlx::Error> {
+    let existing: Option<(String,)> = sqlx::query_as("SELECT sql FROM sqlite_master WHERE type = 'table' AND name = ?")
+        .bind(table)
+        .fetch_optional(pool)
+        .await?;
+
+    match existing {
+        None => {
+            sqlx::query(&create_sql(table)).execute(pool).await?;
+            return Ok(());
+        }
+        Some((sql,)) if sql.contains(marker) => return Ok(()),
+        Some(_) => {}
+    }
+
+    info!("Upgrading table schema: {}", table);
+    let new_table = format!("{}_new", table);
+
+    // 外键开关只对当前连接生效且不能在事务中修改，所以整个重建过程使用同一个连接
+    let mut conn = pool.acquire().await?;
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+
+    let result = async {
+        let mut tx = conn.begin().await?;
+        sqlx::query(&format!("DROP TABLE IF EXISTS {}", new_table)).execute(&mut *tx).await?;
+        sqlx::query(&create_sql(&new_table)).execute(&mut *tx).await?;
+
+        let old_columns = table_columns(&mut tx, table).await?;
+        let columns = table_columns(&mut tx, &new_table).await?
+            .into_iter()
+            .filter(|column| old_columns.contains(column))
+            .collect::<Vec<_>>()
+            .join(", ");
+        sqlx::query(&format!("INSERT INTO {new_table} ({columns}) SELECT {columns} FROM {table}"))
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(&format!("DROP TABLE {}", table)).execute(&mut *tx).await?;
+        sqlx::query(&format!("ALTER TABLE {} RENAME TO {}", new_table, table)).execute(&mut *tx).await?;
+        tx.commit().await
+    }
+    .await;
+
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+    result
+}
+
+// 获取表的所有列名
+async fn table_columns(conn: &mut sqlx::SqliteConnection, table: &str) -> Result<Vec<String>, sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+        .fetch_all(conn)
+        .await?;
+    Ok(columns.into_iter().map(|(name,)| name).collect())
+}
+
+/// 将配置中的邮箱对应的已注册用户设为管理员，返回更新的用户数
+pub async fn promote_admins(pool: &DbPool, emails: &[String]) -> Result<u64, sqlx::Error> {
+    let mut promoted = 0;
+    for email in emails {
+        promoted += sqlx::query("UPDATE users SET user_type = 'admin' WHERE email = ? AND user_type != 'admin'")
+            .bind(email)
+            .execute(pool)
+            .await?
+            .rows_affected();
+    }
+    Ok(promoted)
+}
+
 /// 插入测试数据到数据库，默认执行，自动被调用
 pub async fn insert_test_data(pool: &DbPool) -> Result<(), sqlx::Error> {
     info!("Inserting test data...");
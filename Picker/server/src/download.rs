@@ -12,7 +12,7 @@ use tracing::info;
 use chrono::Utc;
 
 use crate::config::AppState;
-use crate::models::{Order, OrderStatus, Picker};
+use crate::models::{Order, OrderStatus, Picker, PickerVersion};
 use crate::utils::AppError;
 
 // 下载请求的查询参数
@@ -45,7 +45,7 @@ pub async fn download(
     info!("Download request received with token: {}", query.token);
     // 1. 验证token
     let token = query.token;
-    let order_id = {
+    let (order_id, version_id) = {
         let mut tokens = state.download_tokens.lock().map_err(|e| AppError::InternalServerError(format!("Download token lock error: {:?}", e)))?;
         let download_token = tokens.remove(&token).ok_or(AppError::Unauthorized("Invalid download token".to_string()))?;
         
@@ -54,7 +54,7 @@ pub async fn download(
             return Err(AppError::Unauthorized("Download token is expired".to_string()));
         }
         
-        download_token.order_id
+        (download_token.order_id, download_token.version_id)
     };
     info!("Download request for order ID: {}", order_id);
     // 3. 获取订单信息
@@ -95,16 +95,29 @@ pub async fn download(
             return Err(AppError::DatabaseError(format!("Picker fetch error: {:?}", e)));
         },
     };
-    info!("Download request for order ID: {}, picker ID: {}, file path: {}", order_id, order.picker_id, picker.file_path);
+    // 指定了版本时下载该版本的文件，只能下载审核通过的版本，否则下载最新版本
+    let file_path = match version_id {
+        Some(version_id) => {
+            let version: PickerVersion = sqlx::query_as("SELECT * FROM picker_versions WHERE version_id = ? AND picker_id = ? AND status = 'approved'")
+                .bind(version_id)
+                .bind(order.picker_id)
+                .fetch_optional(&state.db)
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Picker version fetch error: {:?}", e)))?
+                .ok_or(AppError::NotFound("Picker version not found".to_string()))?;
+            version.file_path
+        }
+        None => picker.file_path.clone(),
+    };
+    info!("Download request for order ID: {}, picker ID: {}, file path: {}", order_id, order.picker_id, file_path);
     
     // 6. 检查文件是否存在
-    let file_path = &picker.file_path;
-    if !tokio::fs::metadata(file_path).await.is_ok() {
+    if !tokio::fs::metadata(&file_path).await.is_ok() {
         return Err(AppError::NotFound("File not found".to_string()));
     }
     
     // 7. 打开文件
-    let file = File::open(file_path).await.map_err(|e| AppError::InternalServerError(format!("File open error: {:?}", e)))?;
+    let file = File::open(&file_path).await.map_err(|e| AppError::InternalServerError(format!("File open error: {:?}", e)))?;
     let stream = ReaderStream::new(file);
     let body = AxumBody::from_stream(stream);
     info!("Download request update times");
@@ -121,7 +134,7 @@ pub async fn download(
     // 重新格式化文件名
     let download_date = Utc::now().format("%Y-%m-%d");
     // 从文件路径中提取文件名
-    let original_filename = picker.file_path.split('/').last().unwrap_or("picker.exe");
+    let original_filename = file_path.split('/').last().unwrap_or("picker.exe");
     // 分离文件名和扩展名
     let mut parts: Vec<&str> = original_filename.split('.').collect();
     let extension = if parts.len() > 1 {
@@ -229,6 +242,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() + Duration::minutes(10),
         };
         state.download_tokens.lock().unwrap().insert(token.clone(), download_token);
@@ -296,6 +310,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() - Duration::minutes(10), // 已过期
         };
         state.download_tokens.lock().unwrap().insert(token.clone(), download_token);
@@ -329,6 +344,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() + Duration::minutes(10),
         };
         state.download_tokens.lock().unwrap().insert(token.clone(), download_token);
@@ -418,6 +434,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() + Duration::minutes(10),
         };
         info!("Inserting download token: {}, order_id: {}", token, order_id);
@@ -522,6 +539,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() + Duration::minutes(10),
         };
         state.download_tokens.lock().unwrap().insert(token.clone(), download_token);
@@ -619,6 +637,7 @@ mod tests {
         let download_token = DownloadToken {
             token: token.clone(),
             order_id,
+            version_id: None,
             expires_at: Utc::now() + Duration::minutes(10),
         };
         state.download_tokens.lock().unwrap().insert(token.clone(), download_token);
@@ -0,0 +1,1583 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AppState;
+use crate::handlers::orders::{OrderInfo, OrderListResponse};
+use crate::handlers::pickers::{PickerInfo, PickerVersionInfo};
+use crate::handlers::users::UserInfo;
+use crate::handlers::notifications::notify;
+use crate::models::{AdminAuditLog, Order, OrderStatus, Picker, PickerStatus, PickerVersion, PickerVersionStatus, User, UserStatus, UserType};
+use crate::utils::AppError;
+
+// 管理员用户查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminUserQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    /// 按邮箱或用户名搜索
+    pub keyword: Option<String>,
+    pub user_type: Option<UserType>,
+    pub status: Option<UserStatus>,
+}
+
+// 管理员用户列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUserListResponse {
+    pub users: Vec<UserInfo>,
+    pub total: u64,
+}
+
+// 管理员Picker查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminPickerQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    pub keyword: Option<String>,
+    pub status: Option<PickerStatus>,
+}
+
+// 管理员Picker列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminPickerListResponse {
+    pub pickers: Vec<PickerInfo>,
+    pub total: u64,
+}
+
+// 管理员订单查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminOrderQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    pub status: Option<OrderStatus>,
+    pub user_id: Option<Uuid>,
+    pub picker_id: Option<Uuid>,
+}
+
+// 审计日志查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AuditLogQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    pub target_id: Option<Uuid>,
+}
+
+// 审计日志信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditLogInfo {
+    pub log_id: Uuid,
+    pub admin_user_id: Uuid,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Uuid,
+    pub reason: String,
+    /// 操作前后的数据快照
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AdminAuditLog> for AuditLogInfo {
+    fn from(log: AdminAuditLog) -> Self {
+        Self {
+            log_id: log.log_id,
+            admin_user_id: log.admin_user_id,
+            action: log.action,
+            target_type: log.target_type,
+            target_id: log.target_id,
+            reason: log.reason,
+            details: serde_json::from_str(&log.details).unwrap_or(serde_json::Value::Null),
+            created_at: log.created_at,
+        }
+    }
+}
+
+// 审计日志列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditLogListResponse {
+    pub logs: Vec<AuditLogInfo>,
+    pub total: u64,
+}
+
+// 管理员操作请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminActionRequest {
+    /// 操作原因，记录到审计日志
+    pub reason: String,
+}
+
+// 管理员操作响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminActionResponse {
+    pub log_id: Uuid,
+    pub message: String,
+}
+
+// Picker审核决定
+#[derive(Debug, Clone, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReviewDecision {
+    /// 待审核 -> 上架
+    Approve,
+    /// 待审核 -> 拒绝
+    Reject,
+    /// 上架 -> 暂停
+    Suspend,
+    /// 暂停 -> 上架
+    Reinstate,
+}
+
+// Picker审核请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReviewPickerRequest {
+    pub decision: ReviewDecision,
+    /// 审核意见，拒绝和暂停时必填，会通知给开发者
+    pub notes: Option<String>,
+}
+
+// 待审核版本查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdminVersionQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+}
+
+// 待审核版本信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminVersionInfo {
+    pub picker_id: Uuid,
+    pub picker_alias: String,
+    #[serde(flatten)]
+    pub version: PickerVersionInfo,
+}
+
+// 待审核版本列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminVersionListResponse {
+    pub versions: Vec<AdminVersionInfo>,
+    pub total: u64,
+}
+
+// 版本审核决定
+#[derive(Debug, Clone, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VersionReviewDecision {
+    /// 待审核 -> 通过，Picker切换到该版本
+    Approve,
+    /// 待审核 -> 拒绝
+    Reject,
+}
+
+// 版本审核请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReviewVersionRequest {
+    pub decision: VersionReviewDecision,
+    /// 审核意见，拒绝时必填，会通知给开发者
+    pub notes: Option<String>,
+}
+
+// 调整Premium余额请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AdjustPremiumRequest {
+    /// 调整数量，正数增加，负数扣减
+    pub amount: i64,
+    pub reason: String,
+}
+
+// 调整Premium余额响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdjustPremiumResponse {
+    pub log_id: Uuid,
+    pub premium_balance: i64,
+}
+
+// 订单及对应的Picker别名
+#[derive(FromRow)]
+struct OrderRow {
+    #[sqlx(flatten)]
+    order: Order,
+    picker_alias: String,
+}
+
+// 版本及对应的Picker别名
+#[derive(FromRow)]
+struct VersionRow {
+    #[sqlx(flatten)]
+    version: PickerVersion,
+    picker_alias: String,
+}
+
+// 分页参数，与市场接口保持一致
+fn pagination(page: Option<u32>, size: Option<u32>) -> (u32, u32, u32) {
+    let page = page.unwrap_or(1).max(1);
+    let size = size.unwrap_or(10);
+    (page, size, (page - 1) * size)
+}
+
+fn require_reason(reason: &str) -> Result<(), AppError> {
+    if reason.trim().is_empty() {
+        return Err(AppError::BadRequest("Reason is required".to_string()));
+    }
+    Ok(())
+}
+
+// 写入审计日志，与被审计的操作在同一事务中执行
+async fn record_audit(
+    conn: &mut SqliteConnection,
+    admin_user_id: Uuid,
+    action: &str,
+    target_type: &str,
+    target_id: Uuid,
+    reason: &str,
+    details: serde_json::Value,
+) -> Result<Uuid, AppError> {
+    let log_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO admin_audit_logs (log_id, admin_user_id, action, target_type, target_id, reason, details, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(log_id)
+    .bind(admin_user_id)
+    .bind(action)
+    .bind(target_type)
+    .bind(target_id)
+    .bind(reason.trim())
+    .bind(details.to_string())
+    .bind(Utc::now().to_rfc3339())
+    .execute(conn)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to write audit log: {:?}", e)))?;
+    Ok(log_id)
+}
+
+fn push_user_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &AdminUserQuery) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(keyword) = &query.keyword {
+        let pattern = format!("%{}%", keyword);
+        builder.push(" AND (email LIKE ").push_bind(pattern.clone())
+            .push(" OR user_name LIKE ").push_bind(pattern).push(")");
+    }
+    if let Some(user_type) = &query.user_type {
+        builder.push(" AND user_type = ").push_bind(user_type.clone());
+    }
+    if let Some(status) = &query.status {
+        builder.push(" AND status = ").push_bind(status.clone());
+    }
+}
+
+// 获取所有用户
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    tag = "admin",
+    summary = "List all users",
+    description = "Administrator lists users of every type, supports search and filtering by type or status",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("keyword" = Option<String>, Query, description = "Search by email or user name"),
+        ("user_type" = Option<UserType>, Query, description = "User type filter"),
+        ("status" = Option<UserStatus>, Query, description = "User status filter")
+    ),
+    responses(
+        (status = 200, description = "Get user list successfully", body = AdminUserListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_list_users(
+    State(state): State<AppState>,
+    Query(query): Query<AdminUserQuery>,
+) -> Result<Json<AdminUserListResponse>, AppError> {
+    let (_, size, offset) = pagination(query.page, query.size);
+
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM users");
+    push_user_filters(&mut count_builder, &query);
+    let total: (i64,) = count_builder.build_query_as()
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get user total: {:?}", e)))?;
+
+    let mut builder = QueryBuilder::new("SELECT * FROM users");
+    push_user_filters(&mut builder, &query);
+    builder.push(" ORDER BY created_at DESC LIMIT ").push_bind(size as i64)
+        .push(" OFFSET ").push_bind(offset as i64);
+    let users: Vec<User> = builder.build_query_as()
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get users: {:?}", e)))?;
+
+    Ok(Json(AdminUserListResponse {
+        users: users.into_iter().map(UserInfo::from).collect(),
+        total: total.0 as u64,
+    }))
+}
+
+fn push_picker_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &AdminPickerQuery) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(keyword) = &query.keyword {
+        let pattern = format!("%{}%", keyword);
+        builder.push(" AND (alias LIKE ").push_bind(pattern.clone())
+            .push(" OR description LIKE ").push_bind(pattern).push(")");
+    }
+    if let Some(status) = &query.status {
+        builder.push(" AND status = ").push_bind(status.clone());
+    }
+}
+
+// 获取所有Picker
+#[utoipa::path(
+    get,
+    path = "/api/admin/pickers",
+    tag = "admin",
+    summary = "List all pickers",
+    description = "Administrator lists pickers in any status, supports search and filtering by status",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("keyword" = Option<String>, Query, description = "Search keyword"),
+        ("status" = Option<PickerStatus>, Query, description = "Picker status filter, use pending_review for the review queue")
+    ),
+    responses(
+        (status = 200, description = "Get picker list successfully", body = AdminPickerListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_list_pickers(
+    State(state): State<AppState>,
+    Query(query): Query<AdminPickerQuery>,
+) -> Result<Json<AdminPickerListResponse>, AppError> {
+    let (_, size, offset) = pagination(query.page, query.size);
+
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM pickers");
+    push_picker_filters(&mut count_builder, &query);
+    let total: (i64,) = count_builder.build_query_as()
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker total: {:?}", e)))?;
+
+    let mut builder = QueryBuilder::new("SELECT * FROM pickers");
+    push_picker_filters(&mut builder, &query);
+    builder.push(" ORDER BY created_at DESC LIMIT ").push_bind(size as i64)
+        .push(" OFFSET ").push_bind(offset as i64);
+    let pickers: Vec<Picker> = builder.build_query_as()
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get pickers: {:?}", e)))?;
+
+    Ok(Json(AdminPickerListResponse {
+        pickers: pickers.into_iter().map(PickerInfo::from).collect(),
+        total: total.0 as u64,
+    }))
+}
+
+// 获取待审核的版本
+#[utoipa::path(
+    get,
+    path = "/api/admin/versions",
+    tag = "admin",
+    summary = "List versions waiting for review",
+    description = "Administrator lists the Picker versions published by developers that are waiting for review, oldest first",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10")
+    ),
+    responses(
+        (status = 200, description = "Get version list successfully", body = AdminVersionListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_list_pending_versions(
+    State(state): State<AppState>,
+    Query(query): Query<AdminVersionQuery>,
+) -> Result<Json<AdminVersionListResponse>, AppError> {
+    let (_, size, offset) = pagination(query.page, query.size);
+
+    let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM picker_versions WHERE status = 'pending'")
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get version total: {:?}", e)))?;
+
+    let rows: Vec<VersionRow> = sqlx::query_as(
+        r#"
+        SELECT v.*, p.alias AS picker_alias
+        FROM picker_versions v JOIN pickers p ON p.picker_id = v.picker_id
+        WHERE v.status = 'pending'
+        ORDER BY v.created_at ASC LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(size as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get versions: {:?}", e)))?;
+
+    Ok(Json(AdminVersionListResponse {
+        versions: rows
+            .into_iter()
+            .map(|row| AdminVersionInfo {
+                picker_id: row.version.picker_id,
+                picker_alias: row.picker_alias,
+                version: row.version.into(),
+            })
+            .collect(),
+        total: total.0 as u64,
+    }))
+}
+
+fn push_order_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &AdminOrderQuery) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(status) = &query.status {
+        builder.push(" AND o.status = ").push_bind(status.clone());
+    }
+    if let Some(user_id) = query.user_id {
+        builder.push(" AND o.user_id = ").push_bind(user_id);
+    }
+    if let Some(picker_id) = query.picker_id {
+        builder.push(" AND o.picker_id = ").push_bind(picker_id);
+    }
+}
+
+// 获取所有订单
+#[utoipa::path(
+    get,
+    path = "/api/admin/orders",
+    tag = "admin",
+    summary = "List all orders",
+    description = "Administrator lists orders of all users, supports filtering by status, user or picker",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("status" = Option<OrderStatus>, Query, description = "Order status filter"),
+        ("user_id" = Option<Uuid>, Query, description = "Buyer filter"),
+        ("picker_id" = Option<Uuid>, Query, description = "Picker filter")
+    ),
+    responses(
+        (status = 200, description = "Get order list successfully", body = OrderListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_list_orders(
+    State(state): State<AppState>,
+    Query(query): Query<AdminOrderQuery>,
+) -> Result<Json<OrderListResponse>, AppError> {
+    let (page, size, offset) = pagination(query.page, query.size);
+
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM orders o");
+    push_order_filters(&mut count_builder, &query);
+    let total: (i64,) = count_builder.build_query_as()
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get total order count: {:?}", e)))?;
+
+    let mut builder = QueryBuilder::new("SELECT o.*, p.alias AS picker_alias FROM orders o JOIN pickers p ON p.picker_id = o.picker_id");
+    push_order_filters(&mut builder, &query);
+    builder.push(" ORDER BY o.created_at DESC LIMIT ").push_bind(size as i64)
+        .push(" OFFSET ").push_bind(offset as i64);
+    let rows: Vec<OrderRow> = builder.build_query_as()
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get order list: {:?}", e)))?;
+
+    let orders = rows.into_iter().map(|row| OrderInfo {
+        order_id: row.order.order_id,
+        user_id: row.order.user_id,
+        picker_id: row.order.picker_id,
+        picker_alias: row.picker_alias,
+        amount: row.order.amount,
+        pay_type: row.order.pay_type,
+        status: row.order.status,
+        created_at: row.order.created_at,
+    }).collect();
+
+    Ok(Json(OrderListResponse {
+        orders,
+        total: total.0 as u64,
+        page,
+        size,
+        has_next: (page * size) < total.0 as u32,
+    }))
+}
+
+// 获取审计日志
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-logs",
+    tag = "admin",
+    summary = "List audit logs",
+    description = "Administrator lists the audit trail of moderation actions, newest first",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("target_id" = Option<Uuid>, Query, description = "Only actions on this user or picker")
+    ),
+    responses(
+        (status = 200, description = "Get audit logs successfully", body = AuditLogListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_list_audit_logs(
+    State(state): State<AppState>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<AuditLogListResponse>, AppError> {
+    let (_, size, offset) = pagination(query.page, query.size);
+
+    let mut count_builder = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM admin_audit_logs");
+    let mut builder = QueryBuilder::<Sqlite>::new("SELECT * FROM admin_audit_logs");
+    if let Some(target_id) = query.target_id {
+        count_builder.push(" WHERE target_id = ").push_bind(target_id);
+        builder.push(" WHERE target_id = ").push_bind(target_id);
+    }
+    builder.push(" ORDER BY created_at DESC LIMIT ").push_bind(size as i64)
+        .push(" OFFSET ").push_bind(offset as i64);
+
+    let total: (i64,) = count_builder.build_query_as()
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get audit log total: {:?}", e)))?;
+    let logs: Vec<AdminAuditLog> = builder.build_query_as()
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get audit logs: {:?}", e)))?;
+
+    Ok(Json(AuditLogListResponse {
+        logs: logs.into_iter().map(AuditLogInfo::from).collect(),
+        total: total.0 as u64,
+    }))
+}
+
+// 修改用户状态并记录审计日志
+async fn set_user_status(
+    state: &AppState,
+    admin_user_id: Uuid,
+    user_id: Uuid,
+    status: UserStatus,
+    reason: &str,
+) -> Result<Uuid, AppError> {
+    require_reason(reason)?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get user: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    if user.user_type == UserType::Admin {
+        return Err(AppError::BadRequest("Administrators cannot be suspended or reinstated".to_string()));
+    }
+    if user.status == status {
+        let message = match status {
+            UserStatus::Suspended => "User is already suspended",
+            UserStatus::Active => "User is not suspended",
+        };
+        return Err(AppError::BadRequest(message.to_string()));
+    }
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    // 仅当状态仍为读取时的值才更新，避免并发请求重复记录审计日志
+    let updated = sqlx::query("UPDATE users SET status = ? WHERE user_id = ? AND status = ?")
+        .bind(&status)
+        .bind(user_id)
+        .bind(&user.status)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update user status: {:?}", e)))?;
+    if updated.rows_affected() != 1 {
+        return Err(AppError::Conflict("User status was changed by another request".to_string()));
+    }
+
+    let action = match status {
+        UserStatus::Suspended => "suspend_user",
+        UserStatus::Active => "reinstate_user",
+    };
+    let log_id = record_audit(
+        &mut tx,
+        admin_user_id,
+        action,
+        "user",
+        user_id,
+        reason,
+        json!({ "before": { "status": user.status }, "after": { "status": status } }),
+    ).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(log_id)
+}
+
+// 封禁用户
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/suspend",
+    tag = "admin",
+    summary = "Suspend a user",
+    description = "Administrator suspends a user, the user can no longer log in or call protected endpoints",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("user_id" = uuid::Uuid, Path, description = "User's unique identifier")
+    ),
+    request_body = AdminActionRequest,
+    responses(
+        (status = 200, description = "User suspended", body = AdminActionResponse),
+        (status = 400, description = "Missing reason, user is an administrator or already suspended", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "User not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "User status was changed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_suspend_user(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminActionRequest>,
+) -> Result<Json<AdminActionResponse>, AppError> {
+    let log_id = set_user_status(&state, admin_user_id, user_id, UserStatus::Suspended, &payload.reason).await?;
+    Ok(Json(AdminActionResponse {
+        log_id,
+        message: "User suspended".to_string(),
+    }))
+}
+
+// 解除封禁
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/reinstate",
+    tag = "admin",
+    summary = "Reinstate a user",
+    description = "Administrator lifts the suspension of a user",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("user_id" = uuid::Uuid, Path, description = "User's unique identifier")
+    ),
+    request_body = AdminActionRequest,
+    responses(
+        (status = 200, description = "User reinstated", body = AdminActionResponse),
+        (status = 400, description = "Missing reason, user is an administrator or not suspended", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "User not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "User status was changed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_reinstate_user(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdminActionRequest>,
+) -> Result<Json<AdminActionResponse>, AppError> {
+    let log_id = set_user_status(&state, admin_user_id, user_id, UserStatus::Active, &payload.reason).await?;
+    Ok(Json(AdminActionResponse {
+        log_id,
+        message: "User reinstated".to_string(),
+    }))
+}
+
+// 调整用户Premium余额
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{user_id}/premium",
+    tag = "admin",
+    summary = "Adjust premium balance",
+    description = "Administrator credits or debits a user's premium balance, the balance can not become negative",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("user_id" = uuid::Uuid, Path, description = "User's unique identifier")
+    ),
+    request_body = AdjustPremiumRequest,
+    responses(
+        (status = 200, description = "Premium balance adjusted", body = AdjustPremiumResponse),
+        (status = 400, description = "Missing reason, zero amount, insufficient balance or overflow", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "User not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Premium balance was changed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_adjust_premium(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<AdjustPremiumRequest>,
+) -> Result<Json<AdjustPremiumResponse>, AppError> {
+    require_reason(&payload.reason)?;
+    if payload.amount == 0 {
+        return Err(AppError::BadRequest("Amount must not be zero".to_string()));
+    }
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    let before: (i64,) = sqlx::query_as("SELECT premium_balance FROM users WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get user: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+    let premium_balance = before.0.checked_add(payload.amount)
+        .ok_or_else(|| AppError::BadRequest("Premium balance would overflow".to_string()))?;
+    if premium_balance < 0 {
+        return Err(AppError::BadRequest("Premium balance cannot become negative".to_string()));
+    }
+
+    let updated = sqlx::query("UPDATE users SET premium_balance = ? WHERE user_id = ? AND premium_balance = ?")
+        .bind(premium_balance)
+        .bind(user_id)
+        .bind(before.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update premium balance: {:?}", e)))?;
+    if updated.rows_affected() != 1 {
+        return Err(AppError::Conflict("Premium balance was changed by another request".to_string()));
+    }
+
+    let log_id = record_audit(
+        &mut tx,
+        admin_user_id,
+        "adjust_premium",
+        "user",
+        user_id,
+        &payload.reason,
+        json!({ "before": { "premium_balance": before.0 }, "after": { "premium_balance": premium_balance }, "amount": payload.amount }),
+    ).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(AdjustPremiumResponse {
+        log_id,
+        premium_balance,
+    }))
+}
+
+// 强制下架Picker
+#[utoipa::path(
+    post,
+    path = "/api/admin/pickers/{picker_id}/deactivate",
+    tag = "admin",
+    summary = "Force a picker inactive",
+    description = "Administrator takes a picker off the market, files are kept for review",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
+    ),
+    request_body = AdminActionRequest,
+    responses(
+        (status = 200, description = "Picker deactivated", body = AdminActionResponse),
+        (status = 400, description = "Missing reason or picker already inactive", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Picker status was changed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_deactivate_picker(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path(picker_id): Path<Uuid>,
+    Json(payload): Json<AdminActionRequest>,
+) -> Result<Json<AdminActionResponse>, AppError> {
+    require_reason(&payload.reason)?;
+
+    let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    if picker.status == PickerStatus::Inactive {
+        return Err(AppError::BadRequest("Picker is already inactive".to_string()));
+    }
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    let updated = sqlx::query("UPDATE pickers SET status = 'inactive', updated_at = ? WHERE picker_id = ? AND status = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(picker_id)
+        .bind(&picker.status)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to deactivate picker: {:?}", e)))?;
+    if updated.rows_affected() != 1 {
+        return Err(AppError::Conflict("Picker status was changed by another request".to_string()));
+    }
+
+    let log_id = record_audit(
+        &mut tx,
+        admin_user_id,
+        "deactivate_picker",
+        "picker",
+        picker_id,
+        &payload.reason,
+        json!({ "before": { "status": picker.status }, "after": { "status": PickerStatus::Inactive } }),
+    ).await?;
+
+    notify(
+        &mut tx,
+        picker.dev_user_id,
+        Some(picker_id),
+        "picker_deactivated",
+        &format!("Your picker \"{}\" was taken down by an administrator: {}", picker.alias, payload.reason.trim()),
+    ).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(AdminActionResponse {
+        log_id,
+        message: "Picker deactivated".to_string(),
+    }))
+}
+
+// 审核Picker
+#[utoipa::path(
+    post,
+    path = "/api/admin/pickers/{picker_id}/review",
+    tag = "admin",
+    summary = "Review a picker",
+    description = "Administrator approves or rejects a picker pending review, or suspends and reinstates an active one. The developer is notified of the decision",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
+    ),
+    request_body = ReviewPickerRequest,
+    responses(
+        (status = 200, description = "Review recorded", body = AdminActionResponse),
+        (status = 400, description = "Missing notes or decision not allowed in the current status", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Picker was reviewed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_review_picker(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path(picker_id): Path<Uuid>,
+    Json(payload): Json<ReviewPickerRequest>,
+) -> Result<Json<AdminActionResponse>, AppError> {
+    let notes = payload.notes.as_deref().map(str::trim).unwrap_or_default();
+
+    // 每种决定允许的原状态、目标状态和通知内容
+    let (from, to, action, kind, verb) = match payload.decision {
+        ReviewDecision::Approve => (PickerStatus::PendingReview, PickerStatus::Active, "approve_picker", "picker_approved", "approved and is now on the market"),
+        ReviewDecision::Reject => (PickerStatus::PendingReview, PickerStatus::Rejected, "reject_picker", "picker_rejected", "rejected"),
+        ReviewDecision::Suspend => (PickerStatus::Active, PickerStatus::Suspended, "suspend_picker", "picker_suspended", "suspended"),
+        ReviewDecision::Reinstate => (PickerStatus::Suspended, PickerStatus::Active, "reinstate_picker", "picker_reinstated", "reinstated and is back on the market"),
+    };
+    if matches!(payload.decision, ReviewDecision::Reject | ReviewDecision::Suspend) {
+        require_reason(notes).map_err(|_| AppError::BadRequest("Notes are required when rejecting or suspending a picker".to_string()))?;
+    }
+
+    let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    if picker.status != from {
+        return Err(AppError::BadRequest(format!(
+            "Cannot {} a picker in status {}",
+            action.trim_end_matches("_picker"),
+            picker.status.as_str()
+        )));
+    }
+
+    // 通过或拒绝的是等待审核的版本；被拒后未发布新版本就重新提交时，重新审核当前版本
+    let version = match payload.decision {
+        ReviewDecision::Approve | ReviewDecision::Reject => sqlx::query_as::<_, PickerVersion>(
+            "SELECT * FROM picker_versions WHERE picker_id = ? AND (status = ? OR version = ?) ORDER BY CASE WHEN status = ? THEN 0 ELSE 1 END LIMIT 1",
+        )
+        .bind(picker_id)
+        .bind(PickerVersionStatus::Pending)
+        .bind(&picker.version)
+        .bind(PickerVersionStatus::Pending)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker version: {:?}", e)))?,
+        ReviewDecision::Suspend | ReviewDecision::Reinstate => None,
+    };
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    let now = Utc::now().to_rfc3339();
+    // 两个管理员同时审核时只有一个决定生效
+    let updated = sqlx::query("UPDATE pickers SET status = ?, review_notes = ?, reviewed_at = ?, updated_at = ? WHERE picker_id = ? AND status = ?")
+        .bind(&to)
+        .bind(if notes.is_empty() { None } else { Some(notes) })
+        .bind(&now)
+        .bind(&now)
+        .bind(picker_id)
+        .bind(&from)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update picker status: {:?}", e)))?;
+    if updated.rows_affected() != 1 {
+        return Err(AppError::Conflict("Picker was reviewed by another request".to_string()));
+    }
+
+    // 版本随Picker一起审核，通过后Picker切换到该版本
+    if let Some(version) = &version {
+        let version_status = if payload.decision == ReviewDecision::Approve { PickerVersionStatus::Approved } else { PickerVersionStatus::Rejected };
+        sqlx::query("UPDATE picker_versions SET status = ?, review_notes = ? WHERE version_id = ?")
+            .bind(&version_status)
+            .bind(if notes.is_empty() { None } else { Some(notes) })
+            .bind(version.version_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to update picker version status: {:?}", e)))?;
+        if version_status == PickerVersionStatus::Approved {
+            sqlx::query("UPDATE pickers SET version = ?, file_path = ? WHERE picker_id = ?")
+                .bind(&version.version)
+                .bind(&version.file_path)
+                .bind(picker_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Failed to update picker version: {:?}", e)))?;
+        }
+    }
+
+    let log_id = record_audit(
+        &mut tx,
+        admin_user_id,
+        action,
+        "picker",
+        picker_id,
+        notes,
+        json!({
+            "version_id": version.as_ref().map(|v| v.version_id),
+            "before": { "status": picker.status, "version": picker.version },
+            "after": { "status": to, "version": version.as_ref().filter(|_| to == PickerStatus::Active).map_or(&picker.version, |v| &v.version) },
+        }),
+    ).await?;
+
+    let mut message = format!("Your picker \"{}\" was {}", picker.alias, verb);
+    if !notes.is_empty() {
+        message.push_str(&format!(". Reviewer notes: {}", notes));
+    }
+    notify(&mut tx, picker.dev_user_id, Some(picker_id), kind, &message).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(AdminActionResponse {
+        log_id,
+        message: format!("Picker {}", verb),
+    }))
+}
+
+// 审核Picker的新版本
+#[utoipa::path(
+    post,
+    path = "/api/admin/pickers/{picker_id}/versions/{version_id}/review",
+    tag = "admin",
+    summary = "Review a picker version",
+    description = "Administrator approves or rejects a version waiting for review. Approving makes it the latest version served to buyers. The developer is notified of the decision",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier"),
+        ("version_id" = uuid::Uuid, Path, description = "Version's unique identifier")
+    ),
+    request_body = ReviewVersionRequest,
+    responses(
+        (status = 200, description = "Review recorded", body = AdminActionResponse),
+        (status = 400, description = "Missing notes or version not waiting for review", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Administrator privileges required", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker or version not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Version was reviewed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn admin_review_version(
+    State(state): State<AppState>,
+    Extension(admin_user_id): Extension<Uuid>,
+    Path((picker_id, version_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ReviewVersionRequest>,
+) -> Result<Json<AdminActionResponse>, AppError> {
+    let notes = payload.notes.as_deref().map(str::trim).unwrap_or_default();
+    let (to, action, kind, verb) = match payload.decision {
+        VersionReviewDecision::Approve => (PickerVersionStatus::Approved, "approve_version", "version_approved", "approved and is now available to buyers"),
+        VersionReviewDecision::Reject => (PickerVersionStatus::Rejected, "reject_version", "version_rejected", "rejected"),
+    };
+    if payload.decision == VersionReviewDecision::Reject {
+        require_reason(notes).map_err(|_| AppError::BadRequest("Notes are required when rejecting a version".to_string()))?;
+    }
+
+    let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    let version = sqlx::query_as::<_, PickerVersion>("SELECT * FROM picker_versions WHERE version_id = ? AND picker_id = ?")
+        .bind(version_id)
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker version: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker version not found".to_string()))?;
+
+    if version.status != PickerVersionStatus::Pending {
+        return Err(AppError::BadRequest(format!("Version {} is not waiting for review", version.version)));
+    }
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    // 两个管理员同时审核时只有一个决定生效
+    let updated = sqlx::query("UPDATE picker_versions SET status = ?, review_notes = ? WHERE version_id = ? AND status = ?")
+        .bind(&to)
+        .bind(if notes.is_empty() { None } else { Some(notes) })
+        .bind(version_id)
+        .bind(PickerVersionStatus::Pending)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update picker version status: {:?}", e)))?;
+    if updated.rows_affected() != 1 {
+        return Err(AppError::Conflict("Version was reviewed by another request".to_string()));
+    }
+
+    // 审核通过后Picker才切换到新版本
+    if to == PickerVersionStatus::Approved {
+        sqlx::query("UPDATE pickers SET version = ?, file_path = ?, updated_at = ? WHERE picker_id = ?")
+            .bind(&version.version)
+            .bind(&version.file_path)
+            .bind(Utc::now().to_rfc3339())
+            .bind(picker_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to update picker version: {:?}", e)))?;
+    }
+
+    let log_id = record_audit(
+        &mut tx,
+        admin_user_id,
+        action,
+        "picker",
+        picker_id,
+        notes,
+        json!({
+            "version_id": version_id,
+            "before": { "version": picker.version, "version_status": version.status },
+            "after": { "version": if to == PickerVersionStatus::Approved { &version.version } else { &picker.version }, "version_status": to },
+        }),
+    ).await?;
+
+    let mut message = format!("Version {} of your picker \"{}\" was {}", version.version, picker.alias, verb);
+    if !notes.is_empty() {
+        message.push_str(&format!(". Reviewer notes: {}", notes));
+    }
+    notify(&mut tx, picker.dev_user_id, Some(picker_id), kind, &message).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(AdminActionResponse {
+        log_id,
+        message: format!("Version {}", verb),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils_tests::create_test_app_state;
+    use serial_test::serial;
+
+    async fn insert_user(state: &AppState, user_type: &str, premium_balance: i64) -> Uuid {
+        let user_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+            VALUES (?, ?, 'Admin Test User', 'hashed_password', ?, 'private_key', 'wallet', ?, ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(format!("{}@admin-test.com", user_id))
+        .bind(user_type)
+        .bind(premium_balance)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+        user_id
+    }
+
+    async fn audit_count(state: &AppState, target_id: Uuid) -> i64 {
+        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM admin_audit_logs WHERE target_id = ?")
+            .bind(target_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        count.0
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_suspend_and_reinstate_user() {
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let user_id = insert_user(&state, "gen", 0).await;
+
+        let request = || Json(AdminActionRequest { reason: "spam".to_string() });
+        let response = admin_suspend_user(State(state.clone()), Extension(admin_id), Path(user_id), request()).await.unwrap();
+        assert_eq!(response.message, "User suspended");
+        let status: (UserStatus,) = sqlx::query_as("SELECT status FROM users WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(status.0, UserStatus::Suspended);
+
+        // 重复封禁和封禁管理员都会被拒绝
+        assert!(matches!(
+            admin_suspend_user(State(state.clone()), Extension(admin_id), Path(user_id), request()).await,
+            Err(AppError::BadRequest(_))
+        ));
+        assert!(matches!(
+            admin_suspend_user(State(state.clone()), Extension(admin_id), Path(admin_id), request()).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let response = admin_reinstate_user(State(state.clone()), Extension(admin_id), Path(user_id), request()).await.unwrap();
+        assert_eq!(response.message, "User reinstated");
+        assert_eq!(audit_count(&state, user_id).await, 2);
+
+        // 并发封禁只有一个请求生效，另一个返回冲突或已封禁
+        let (first, second) = tokio::join!(
+            admin_suspend_user(State(state.clone()), Extension(admin_id), Path(user_id), request()),
+            admin_suspend_user(State(state.clone()), Extension(admin_id), Path(user_id), request()),
+        );
+        assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+        assert!([first, second].into_iter().any(|result| matches!(result, Err(AppError::Conflict(_)) | Err(AppError::BadRequest(_)))));
+        assert_eq!(audit_count(&state, user_id).await, 3);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_adjust_premium_balance() {
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let user_id = insert_user(&state, "gen", 10).await;
+
+        let response = admin_adjust_premium(
+            State(state.clone()),
+            Extension(admin_id),
+            Path(user_id),
+            Json(AdjustPremiumRequest { amount: -4, reason: "refund correction".to_string() }),
+        ).await.unwrap();
+        assert_eq!(response.premium_balance, 6);
+
+        // 余额不足时不修改余额也不写审计日志
+        let result = admin_adjust_premium(
+            State(state.clone()),
+            Extension(admin_id),
+            Path(user_id),
+            Json(AdjustPremiumRequest { amount: -7, reason: "too much".to_string() }),
+        ).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert_eq!(audit_count(&state, user_id).await, 1);
+
+        // 溢出同样被拒绝
+        let result = admin_adjust_premium(
+            State(state.clone()),
+            Extension(admin_id),
+            Path(user_id),
+            Json(AdjustPremiumRequest { amount: i64::MAX, reason: "overflow".to_string() }),
+        ).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+        assert_eq!(audit_count(&state, user_id).await, 1);
+
+        let logs = admin_list_audit_logs(
+            State(state.clone()),
+            Query(AuditLogQuery { page: None, size: None, target_id: Some(user_id) }),
+        ).await.unwrap();
+        assert_eq!(logs.logs[0].details["after"]["premium_balance"], 6);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_deactivate_picker_and_list() {
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let dev_id = insert_user(&state, "dev", 0).await;
+        let picker_id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Malicious Picker', 'Test Description', 100, 'test.jpg', 'test.exe', '1.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        let response = admin_deactivate_picker(
+            State(state.clone()),
+            Extension(admin_id),
+            Path(picker_id),
+            Json(AdminActionRequest { reason: "malware".to_string() }),
+        ).await.unwrap();
+        assert_eq!(response.message, "Picker deactivated");
+
+        // 下架的Picker仍然出现在管理员列表中
+        let response = admin_list_pickers(
+            State(state.clone()),
+            Query(AdminPickerQuery { page: None, size: Some(100), keyword: Some("Malicious Picker".to_string()), status: Some(PickerStatus::Inactive) }),
+        ).await.unwrap();
+        assert!(response.pickers.iter().any(|p| p.picker_id == picker_id));
+        assert_eq!(audit_count(&state, picker_id).await, 1);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_new_version_is_served_after_approval() {
+        use crate::handlers::orders::{create_download_token, DownloadTokenRequest};
+        use crate::handlers::pickers::get_picker_detail;
+
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let dev_id = insert_user(&state, "dev", 0).await;
+        let buyer_id = insert_user(&state, "gen", 0).await;
+        let picker_id = Uuid::new_v4();
+        let order_id = Uuid::new_v4();
+        let pending_id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Versioned Picker', 'Test Description', 100, 'test.jpg', 'test_v1.exe', '1.0.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+        // 已上架的 1.0.0 和开发者刚发布的 2.0.0
+        for (version_id, version, file_path, status) in [(Uuid::new_v4(), "1.0.0", "test_v1.exe", "approved"), (pending_id, "2.0.0", "test_v2.exe", "pending")] {
+            sqlx::query(
+                r#"
+                INSERT INTO picker_versions (version_id, picker_id, version, changelog, file_path, checksum, created_at, status)
+                VALUES (?, ?, ?, 'changes', ?, 'checksum', ?, ?)
+                "#,
+            )
+            .bind(version_id)
+            .bind(picker_id)
+            .bind(version)
+            .bind(file_path)
+            .bind(&now)
+            .bind(status)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO orders (order_id, user_id, picker_id, amount, pay_type, status, tx_hash, created_at, expires_at)
+            VALUES (?, ?, ?, 100, 'premium', 'success', NULL, ?, NULL)
+            "#,
+        )
+        .bind(order_id)
+        .bind(buyer_id)
+        .bind(picker_id)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        let token = |version: Option<&str>| create_download_token(
+            State(state.clone()),
+            Extension(buyer_id),
+            Path(order_id),
+            Json(DownloadTokenRequest { version: version.map(str::to_string) }),
+        );
+        let review = |decision: VersionReviewDecision, notes: Option<&str>| Json(ReviewVersionRequest {
+            decision,
+            notes: notes.map(str::to_string),
+        });
+
+        // 审核通过前买家仍然拿到 1.0.0，也不能指定下载 2.0.0
+        assert_eq!(token(None).await.unwrap().version, "1.0.0");
+        assert!(matches!(token(Some("2.0.0")).await, Err(AppError::NotFound(_))));
+        let detail = get_picker_detail(State(state.clone()), Path(picker_id)).await.unwrap();
+        assert_eq!(detail.picker.version, "1.0.0");
+        assert_eq!(detail.versions.len(), 1);
+
+        let pending = admin_list_pending_versions(State(state.clone()), Query(AdminVersionQuery { page: None, size: Some(100) })).await.unwrap();
+        assert!(pending.versions.iter().any(|v| v.version.version_id == pending_id && v.picker_alias == "Versioned Picker"));
+
+        assert!(matches!(
+            admin_review_version(State(state.clone()), Extension(admin_id), Path((picker_id, pending_id)), review(VersionReviewDecision::Reject, None)).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        // 两个管理员同时审核时只有一个生效
+        let (first, second) = tokio::join!(
+            admin_review_version(State(state.clone()), Extension(admin_id), Path((picker_id, pending_id)), review(VersionReviewDecision::Approve, None)),
+            admin_review_version(State(state.clone()), Extension(admin_id), Path((picker_id, pending_id)), review(VersionReviewDecision::Approve, None)),
+        );
+        assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+        assert!([first, second].into_iter().any(|result| matches!(result, Err(AppError::Conflict(_)) | Err(AppError::BadRequest(_)))));
+        assert_eq!(audit_count(&state, picker_id).await, 1);
+
+        assert_eq!(token(None).await.unwrap().version, "2.0.0");
+        assert_eq!(token(Some("2.0.0")).await.unwrap().version, "2.0.0");
+        let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+            .bind(picker_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(picker.file_path, "test_v2.exe");
+        let detail = get_picker_detail(State(state.clone()), Path(picker_id)).await.unwrap();
+        assert_eq!(detail.versions.len(), 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_review_picker_approves_republished_version() {
+        use axum::http::{Request, StatusCode};
+        use crate::handlers::pickers::{get_picker_versions, publish_picker_version, submit_picker};
+        use tower::ServiceExt;
+
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let dev_id = insert_user(&state, "dev", 0).await;
+        let picker_id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Republished Picker', 'Test Description', 100, 'test.jpg', 'test_v1.exe', '1.0.0', 'pending_review', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO picker_versions (version_id, picker_id, version, changelog, file_path, checksum, created_at, status)
+            VALUES (?, ?, '1.0.0', 'Initial release', 'test_v1.exe', 'checksum', ?, 'pending')
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(picker_id)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        let review = |decision: ReviewDecision, notes: Option<&str>| Json(ReviewPickerRequest {
+            decision,
+            notes: notes.map(str::to_string),
+        });
+        let response = admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Reject, Some("crashes on start"))).await.unwrap();
+        assert_eq!(response.message, "Picker rejected");
+
+        // 被拒后发布修复版本再重新提交
+        let app = axum::Router::new()
+            .route("/api/pickers/{picker_id}/versions", axum::routing::post(publish_picker_version))
+            .layer(axum::middleware::from_fn(move |mut request: axum::http::Request<axum::body::Body>, next: axum::middleware::Next| async move {
+                request.extensions_mut().insert(dev_id);
+                next.run(request).await
+            }))
+            .with_state(state.clone());
+        let boundary = "boundary123";
+        let body = format!("--{boundary}\r\nContent-Disposition: form-data; name=\"version\"\r\n\r\n1.0.1\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"changelog\"\r\n\r\nFix crash\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"test.exe\"\r\nContent-Type: application/octet-stream\r\n\r\nfixed_file_data\r\n--{boundary}--\r\n");
+        let request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/pickers/{}/versions", picker_id))
+            .header("content-type", format!("multipart/form-data; boundary={boundary}"))
+            .body(axum::body::Body::from(body))
+            .unwrap();
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::OK);
+        submit_picker(State(state.clone()), Extension(dev_id), Path(picker_id)).await.unwrap();
+
+        let response = admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Approve, None)).await.unwrap();
+        assert_eq!(response.message, "Picker approved and is now on the market");
+
+        // 通过的是修复版本，被拒的版本保持拒绝
+        let versions = get_picker_versions(&state.db, picker_id).await.unwrap();
+        let fixed = versions.iter().find(|v| v.version == "1.0.1").unwrap();
+        assert_eq!(fixed.status, PickerVersionStatus::Approved);
+        assert_eq!(versions.iter().find(|v| v.version == "1.0.0").unwrap().status, PickerVersionStatus::Rejected);
+        let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+            .bind(picker_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(picker.status, PickerStatus::Active);
+        assert_eq!(picker.version, "1.0.1");
+        assert_eq!(picker.file_path, fixed.file_path);
+        let _ = tokio::fs::remove_file(&fixed.file_path).await;
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_review_picker_lifecycle() {
+        use crate::handlers::notifications::{get_notifications, NotificationQuery};
+        use crate::handlers::orders::{create_order, CreateOrderRequest};
+        use crate::handlers::pickers::{get_market, submit_picker, MarketQuery};
+        use crate::models::PayType;
+
+        let state = create_test_app_state().await;
+        let admin_id = insert_user(&state, "admin", 0).await;
+        let dev_id = insert_user(&state, "dev", 0).await;
+        let buyer_id = insert_user(&state, "gen", 1000).await;
+        let picker_id = Uuid::new_v4();
+        let alias = format!("Review {}", picker_id);
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, ?, 'Test Description', 100, 'test.jpg', 'test.exe', '1.0', 'pending_review', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_id)
+        .bind(&alias)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        let on_market = |state: AppState| {
+            let alias = alias.clone();
+            async move {
+                let query = MarketQuery { page: Some(1), size: Some(10), keyword: Some(alias), sort: None, min_rating: None };
+                !get_market(State(state), Query(query)).await.unwrap().pickers.is_empty()
+            }
+        };
+        let review = |decision: ReviewDecision, notes: Option<&str>| Json(ReviewPickerRequest {
+            decision,
+            notes: notes.map(str::to_string),
+        });
+
+        // 待审核的Picker不在市场中，也不能下单
+        assert!(!on_market(state.clone()).await);
+        let order = create_order(
+            State(state.clone()),
+            Extension(buyer_id),
+            Json(CreateOrderRequest { picker_id, pay_type: PayType::Premium }),
+        ).await;
+        assert!(matches!(order, Err(AppError::NotFound(_))));
+
+        // 拒绝必须填写意见，拒绝后开发者可以重新提交
+        assert!(matches!(
+            admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Reject, None)).await,
+            Err(AppError::BadRequest(_))
+        ));
+        let response = admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Reject, Some("missing icon"))).await.unwrap();
+        assert_eq!(response.message, "Picker rejected");
+        // 重复提交时只有一次生效
+        let (first, second) = tokio::join!(
+            submit_picker(State(state.clone()), Extension(dev_id), Path(picker_id)),
+            submit_picker(State(state.clone()), Extension(dev_id), Path(picker_id)),
+        );
+        assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+        assert!([first, second].into_iter().any(|result| matches!(result, Err(AppError::Conflict(_)) | Err(AppError::BadRequest(_)))));
+
+        let response = admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Approve, None)).await.unwrap();
+        assert_eq!(response.message, "Picker approved and is now on the market");
+        assert!(on_market(state.clone()).await);
+
+        // 已上架的Picker不能再次通过审核
+        assert!(matches!(
+            admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Approve, None)).await,
+            Err(AppError::BadRequest(_))
+        ));
+
+        let notifications = get_notifications(
+            State(state.clone()),
+            Extension(dev_id),
+            Query(NotificationQuery { page: None, size: None, unread_only: Some(true) }),
+        ).await.unwrap();
+        assert_eq!(notifications.unread, 2);
+        assert_eq!(notifications.notifications[1].kind, "picker_rejected");
+        assert!(notifications.notifications[1].message.contains("missing icon"));
+        assert_eq!(audit_count(&state, picker_id).await, 2);
+
+        // 两个管理员同时暂停时只记录一次
+        let (first, second) = tokio::join!(
+            admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Suspend, Some("abuse report"))),
+            admin_review_picker(State(state.clone()), Extension(admin_id), Path(picker_id), review(ReviewDecision::Suspend, Some("abuse report"))),
+        );
+        assert_eq!([&first, &second].iter().filter(|result| result.is_ok()).count(), 1);
+        assert!([first, second].into_iter().any(|result| matches!(result, Err(AppError::Conflict(_)) | Err(AppError::BadRequest(_)))));
+        assert_eq!(audit_count(&state, picker_id).await, 3);
+    }
+}
@@ -2,15 +2,21 @@ pub mod users;
 pub mod pickers;
 pub mod orders;
 pub mod picker_payment_contract;
+pub mod admin;
+pub mod notifications;
+pub mod reviews;
 
 pub use users::*;
 pub use pickers::*;
 pub use orders::*;
 pub use picker_payment_contract::*;
+pub use admin::*;
+pub use notifications::*;
+pub use reviews::*;
 
 use axum::{
     middleware,
-    routing::{get, post, delete},
+    routing::{get, post, put, delete},
     Router,
 };
 use tower_http::{cors::CorsLayer, services::ServeDir};
@@ -18,7 +24,7 @@ use tower_http::{cors::CorsLayer, services::ServeDir};
 use crate::config::AppState;
 use crate::download::download;
 use crate::openapi::create_swagger_routes;
-use crate::middleware::auth_middleware;
+use crate::middleware::{admin_middleware, auth_middleware};
 
 
 /// 健康检查处理函数
@@ -49,6 +55,7 @@ pub fn create_routes() -> Router<AppState> {
         // Picker相关路由（公开）
         .route("/api/pickers", get(get_market))
         .route("/api/pickers/{picker_id}", get(get_picker_detail))
+        .route("/api/pickers/{picker_id}/reviews", get(get_picker_reviews))
         .route("/api/pickers/is-operator", get(is_picker_operator))
         .route("/api/pickers/query-picker-by-wallet", get(query_picker_by_wallet))
         // 下载路由
@@ -69,6 +76,11 @@ pub fn create_protected_routes(state: AppState) -> Router<AppState> {
         .route("/api/users/replace-private-key", post(replace_private_key))
         .route("/api/pickers", post(upload_picker))
         .route("/api/pickers/{picker_id}", delete(delete_picker))
+        .route("/api/pickers/{picker_id}/submit", post(submit_picker))
+        .route("/api/pickers/{picker_id}/versions", post(publish_picker_version))
+        .route("/api/pickers/{picker_id}/reviews", put(submit_picker_review))
+        .route("/api/pickers/{picker_id}/reviews/{review_id}/reply", post(reply_picker_review))
+        .route("/api/pickers/mine", get(get_my_pickers))
         .route("/api/pickers/register-picker", post(register_picker))
         .route("/api/pickers/remove-picker", post(remove_picker))
         .route("/api/pickers/get-all-pickers", get(get_all_pickers))
@@ -78,9 +90,30 @@ pub fn create_protected_routes(state: AppState) -> Router<AppState> {
         .route("/api/pickers/withdraw-funds", post(withdraw_funds))
         .route("/api/orders", post(create_order))
         .route("/api/orders/{order_id}", get(get_order_detail))
+        .route("/api/orders/{order_id}/download-token", post(create_download_token))
         .route("/api/orders", get(get_user_orders))
+        .route("/api/notifications", get(get_notifications))
+        .route("/api/notifications/{notification_id}/read", post(mark_notification_read))
+        .merge(create_admin_routes(state.clone()))
         // 添加文件大小限制 (20MB)
         .layer(axum::extract::DefaultBodyLimit::max(100 * 1024 * 1024))
         // 应用认证中间件到所有受保护的路由
         .layer(middleware::from_fn_with_state(state, auth_middleware))
 }
+
+/// 创建管理员路由，由 create_protected_routes 合并后统一经过认证中间件
+fn create_admin_routes(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/api/admin/users", get(admin_list_users))
+        .route("/api/admin/users/{user_id}/suspend", post(admin_suspend_user))
+        .route("/api/admin/users/{user_id}/reinstate", post(admin_reinstate_user))
+        .route("/api/admin/users/{user_id}/premium", post(admin_adjust_premium))
+        .route("/api/admin/pickers", get(admin_list_pickers))
+        .route("/api/admin/pickers/{picker_id}/review", post(admin_review_picker))
+        .route("/api/admin/pickers/{picker_id}/deactivate", post(admin_deactivate_picker))
+        .route("/api/admin/versions", get(admin_list_pending_versions))
+        .route("/api/admin/pickers/{picker_id}/versions/{version_id}/review", post(admin_review_version))
+        .route("/api/admin/orders", get(admin_list_orders))
+        .route("/api/admin/audit-logs", get(admin_list_audit_logs))
+        .layer(middleware::from_fn_with_state(state, admin_middleware))
+}
@@ -0,0 +1,178 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AppState;
+use crate::models::Notification;
+use crate::utils::AppError;
+
+// 通知查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NotificationQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    /// 只返回未读通知
+    pub unread_only: Option<bool>,
+}
+
+// 通知信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationInfo {
+    pub notification_id: Uuid,
+    pub picker_id: Option<Uuid>,
+    /// 通知类型，例如 picker_approved、picker_rejected
+    pub kind: String,
+    pub message: String,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Notification> for NotificationInfo {
+    fn from(notification: Notification) -> Self {
+        Self {
+            notification_id: notification.notification_id,
+            picker_id: notification.picker_id,
+            kind: notification.kind,
+            message: notification.message,
+            is_read: notification.is_read,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+// 通知列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub notifications: Vec<NotificationInfo>,
+    pub total: u64,
+    pub unread: u64,
+}
+
+// 发送站内通知，与触发通知的操作在同一事务中执行
+pub(crate) async fn notify(
+    conn: &mut SqliteConnection,
+    user_id: Uuid,
+    picker_id: Option<Uuid>,
+    kind: &str,
+    message: &str,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        INSERT INTO notifications (notification_id, user_id, picker_id, kind, message, is_read, created_at)
+        VALUES (?, ?, ?, ?, ?, 0, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(picker_id)
+    .bind(kind)
+    .bind(message)
+    .bind(Utc::now().to_rfc3339())
+    .execute(conn)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to create notification: {:?}", e)))?;
+    Ok(())
+}
+
+// 获取当前用户的通知
+#[utoipa::path(
+    get,
+    path = "/api/notifications",
+    tag = "notifications",
+    summary = "Get Notifications",
+    description = "Get notifications of the current user, newest first",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("unread_only" = Option<bool>, Query, description = "Only return unread notifications")
+    ),
+    responses(
+        (status = 200, description = "Get notifications successfully", body = NotificationListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn get_notifications(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Query(query): Query<NotificationQuery>,
+) -> Result<Json<NotificationListResponse>, AppError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let size = query.size.unwrap_or(10);
+    let offset = (page - 1) * size;
+    let unread_only = query.unread_only.unwrap_or(false);
+
+    let (total, unread): (i64, i64) = sqlx::query_as(
+        "SELECT COUNT(*), COALESCE(SUM(CASE WHEN is_read = 0 THEN 1 ELSE 0 END), 0) FROM notifications WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to count notifications: {:?}", e)))?;
+
+    let notifications: Vec<Notification> = sqlx::query_as(
+        "SELECT * FROM notifications WHERE user_id = ? AND (? = 0 OR is_read = 0) ORDER BY created_at DESC LIMIT ? OFFSET ?",
+    )
+    .bind(user_id)
+    .bind(unread_only)
+    .bind(size as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get notifications: {:?}", e)))?;
+
+    Ok(Json(NotificationListResponse {
+        notifications: notifications.into_iter().map(NotificationInfo::from).collect(),
+        total: if unread_only { unread as u64 } else { total as u64 },
+        unread: unread as u64,
+    }))
+}
+
+// 标记通知为已读
+#[utoipa::path(
+    post,
+    path = "/api/notifications/{notification_id}/read",
+    tag = "notifications",
+    summary = "Mark Notification Read",
+    description = "Mark one notification of the current user as read",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("notification_id" = uuid::Uuid, Path, description = "Notification's unique identifier")
+    ),
+    responses(
+        (status = 200, description = "Notification marked as read", body = String),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Notification not found", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn mark_notification_read(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path(notification_id): Path<Uuid>,
+) -> Result<String, AppError> {
+    let result = sqlx::query("UPDATE notifications SET is_read = 1 WHERE notification_id = ? AND user_id = ?")
+        .bind(notification_id)
+        .bind(user_id)
+        .execute(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update notification: {:?}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Notification not found".to_string()));
+    }
+
+    Ok("Notification marked as read".to_string())
+}
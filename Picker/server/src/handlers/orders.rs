@@ -15,7 +15,7 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::config::AppState;
-use crate::models::{DownloadToken, Order, OrderStatus, PayType, Picker, User};
+use crate::models::{DownloadToken, Order, OrderStatus, PayType, Picker, PickerVersion, User};
 use crate::utils::{decrypt_private_key, AppError};
 use zerocopy::IntoBytes;
 
@@ -42,6 +42,21 @@ pub struct CreateOrderResponse {
     pub message: String,
 }
 
+// 下载Token请求
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct DownloadTokenRequest {
+    /// 需要下载的版本号，只能是审核通过的版本，为空时下载最新版本
+    pub version: Option<String>,
+}
+
+// 下载Token响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DownloadTokenResponse {
+    pub token: String,
+    /// Token对应的版本号
+    pub version: String,
+}
+
 // 订单查询参数
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct OrderQuery {
@@ -976,6 +991,80 @@ pub async fn get_order_detail(
     Ok(Json(order_info))
 }
 
+// 为已支付的订单生成下载Token
+#[utoipa::path(
+    post,
+    path = "/api/orders/{order_id}/download-token",
+    tag = "orders",
+    summary = "Create Download Token",
+    description = "Create a new download token for a paid order. Buyers can download the latest version or any earlier approved version of the purchased Picker",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("order_id" = uuid::Uuid, Path, description = "Order ID")
+    ),
+    request_body = DownloadTokenRequest,
+    responses(
+        (status = 200, description = "Download token created", body = DownloadTokenResponse),
+        (status = 400, description = "Order is not paid", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Order, Picker or version not found", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn create_download_token(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path(order_id): Path<Uuid>,
+    Json(request): Json<DownloadTokenRequest>,
+) -> Result<Json<DownloadTokenResponse>, AppError> {
+    let order = sqlx::query_as::<_, Order>("SELECT * FROM orders WHERE order_id = ? AND user_id = ?")
+        .bind(order_id)
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get order detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Order not found".to_string()))?;
+
+    if order.status != OrderStatus::Success {
+        return Err(AppError::BadRequest("Order not paid".to_string()));
+    }
+
+    let (download_token, version) = match request.version.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(requested) => {
+            let version = sqlx::query_as::<_, PickerVersion>(
+                "SELECT * FROM picker_versions WHERE picker_id = ? AND version = ? AND status = 'approved'",
+            )
+            .bind(order.picker_id)
+            .bind(requested.trim_start_matches('v'))
+            .fetch_optional(&state.db)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to get picker version: {:?}", e)))?
+            .ok_or_else(|| AppError::NotFound(format!("Version {} not found", requested)))?;
+            (DownloadToken::new(order_id).with_version(version.version_id), version.version)
+        }
+        None => {
+            let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+                .bind(order.picker_id)
+                .fetch_optional(&state.db)
+                .await
+                .map_err(|e| AppError::DatabaseError(format!("Failed to get picker info: {:?}", e)))?
+                .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+            (DownloadToken::new(order_id), picker.version)
+        }
+    };
+
+    let token = download_token.token.clone();
+    state
+        .download_tokens
+        .lock()
+        .map_err(|e| AppError::InternalServerError(format!("Failed to lock download tokens: {:?}", e)))?
+        .insert(token.clone(), download_token);
+
+    Ok(Json(DownloadTokenResponse { token, version }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1896,4 +1985,120 @@ mod tests {
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_create_download_token_with_version() {
+        let state = create_test_app_state().await;
+        let user_id = Uuid::new_v4();
+        let dev_user_id = Uuid::new_v4();
+        let picker_id = Uuid::new_v4();
+        let order_id = Uuid::new_v4();
+        let version_id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+
+        for (id, user_type) in [(user_id, "gen"), (dev_user_id, "dev")] {
+            sqlx::query(
+                r#"
+                INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+                VALUES (?, ?, 'Test User', 'hashed_password', ?, 'private_key_123', 'wallet123', 0, ?)
+                "#,
+            )
+            .bind(id)
+            .bind(format!("{}@token-test.com", id))
+            .bind(user_type)
+            .bind(&now)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Test Picker', 'Test Description', 500, 'test.jpg', 'test_v2.exe', '2.0.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_user_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            r#"
+            INSERT INTO picker_versions (version_id, picker_id, version, changelog, file_path, checksum, created_at)
+            VALUES (?, ?, '1.0.0', 'Initial release', 'test_v1.exe', 'checksum', ?)
+            "#,
+        )
+        .bind(version_id)
+        .bind(picker_id)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            r#"
+            INSERT INTO orders (order_id, user_id, picker_id, amount, pay_type, status, tx_hash, created_at, expires_at)
+            VALUES (?, ?, ?, 500, ?, ?, NULL, ?, NULL)
+            "#,
+        )
+        .bind(order_id)
+        .bind(user_id)
+        .bind(picker_id)
+        .bind(&PayType::Premium)
+        .bind(&OrderStatus::Success)
+        .bind(&now)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        // 指定历史版本
+        let response = create_download_token(
+            State(state.clone()),
+            Extension(user_id),
+            Path(order_id),
+            Json(DownloadTokenRequest { version: Some("1.0.0".to_string()) }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.version, "1.0.0");
+        let token = state.download_tokens.lock().unwrap().get(&response.token).cloned().unwrap();
+        assert_eq!(token.version_id, Some(version_id));
+
+        // 未指定版本时下载最新版本
+        let response = create_download_token(
+            State(state.clone()),
+            Extension(user_id),
+            Path(order_id),
+            Json(DownloadTokenRequest::default()),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.version, "2.0.0");
+        let token = state.download_tokens.lock().unwrap().get(&response.token).cloned().unwrap();
+        assert_eq!(token.version_id, None);
+
+        // 其他用户无法为该订单生成Token
+        let result = create_download_token(
+            State(state.clone()),
+            Extension(dev_user_id),
+            Path(order_id),
+            Json(DownloadTokenRequest::default()),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let result = create_download_token(
+            State(state),
+            Extension(user_id),
+            Path(order_id),
+            Json(DownloadTokenRequest { version: Some("3.0.0".to_string()) }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
 }
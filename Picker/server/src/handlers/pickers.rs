@@ -1,6 +1,6 @@
 use std::env;
 use axum::{
-    extract::{Query, State, Path, Multipart},
+    extract::{multipart::Field, Query, State, Path, Multipart},
     response::Json,
     Extension,
 };
@@ -10,10 +10,12 @@ use futures_util::StreamExt;
 // use axum_test::TestServer;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection};
 use utoipa::ToSchema;
 use uuid::Uuid;
 use crate::config::AppState;
-use crate::models::{Picker, UserType, User};
+use crate::models::{Picker, PickerStatus, PickerVersion, PickerVersionStatus, UserType, User};
 use crate::utils::AppError;
 
 // 上传Picker请求
@@ -25,8 +27,12 @@ pub struct UploadPickerRequest {
     pub description: String,
     /// 价格（分为单位）
     pub price: i64,
-    /// 版本号
+    /// 版本号，语义化版本，例如 1.0.0
     pub version: String,
+    /// 首个版本的更新说明，默认为 Initial release
+    pub changelog: Option<String>,
+    /// 为 true 时保存为草稿，稍后再提交审核；默认直接提交审核
+    pub draft: Option<bool>,
     /// 图片文件
     #[schema(value_type = String, format = Binary)]
     pub image: (),
@@ -42,12 +48,50 @@ pub struct UploadPickerResponse {
     pub message: String,
 }
 
+// 发布新版本请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PublishVersionRequest {
+    /// 新版本号，必须大于当前最新版本
+    pub version: String,
+    /// 更新说明
+    pub changelog: String,
+    /// 新版本的Picker文件
+    #[schema(value_type = String, format = Binary)]
+    pub file: (),
+}
+
+// 发布新版本响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublishVersionResponse {
+    pub version_id: Uuid,
+    pub version: String,
+    /// 文件的 SHA-256 校验和
+    pub checksum: String,
+    pub message: String,
+}
+
+// 市场排序方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSort {
+    /// 最新上架
+    #[default]
+    Newest,
+    /// 平均评分从高到低，评分相同时评价多的在前
+    Rating,
+    /// 下载次数从高到低
+    Downloads,
+}
+
 // 市场查询参数
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct MarketQuery {
     pub page: Option<u32>,
     pub size: Option<u32>,
     pub keyword: Option<String>,
+    pub sort: Option<MarketSort>,
+    /// 只返回展示的平均评分（保留一位小数）不低于该值的Picker，取值 0-5
+    pub min_rating: Option<f64>,
 }
 
 // Picker信息
@@ -63,7 +107,65 @@ pub struct PickerInfo {
     pub download_count: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
-    pub status: String,
+    pub status: PickerStatus,
+    /// 平均评分（1-5，保留一位小数），没有评价时为 0
+    pub rating_average: f64,
+    /// 评价数量
+    pub rating_count: i64,
+}
+
+impl From<Picker> for PickerInfo {
+    fn from(picker: Picker) -> Self {
+        Self {
+            rating_average: picker.rating_average(),
+            rating_count: picker.rating_count,
+            picker_id: picker.picker_id,
+            dev_user_id: picker.dev_user_id,
+            alias: picker.alias,
+            description: picker.description,
+            price: picker.price,
+            image_path: picker.image_path,
+            version: picker.version,
+            download_count: picker.download_count,
+            created_at: picker.created_at,
+            updated_at: picker.updated_at,
+            status: picker.status,
+        }
+    }
+}
+
+// Picker版本信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PickerVersionInfo {
+    pub version_id: Uuid,
+    pub version: String,
+    pub changelog: String,
+    /// 文件的 SHA-256 校验和
+    pub checksum: String,
+    pub created_at: DateTime<Utc>,
+    pub status: PickerVersionStatus,
+}
+
+impl From<PickerVersion> for PickerVersionInfo {
+    fn from(version: PickerVersion) -> Self {
+        Self {
+            version_id: version.version_id,
+            version: version.version,
+            changelog: version.changelog,
+            checksum: version.checksum,
+            created_at: version.created_at,
+            status: version.status,
+        }
+    }
+}
+
+// Picker详情响应，包含版本历史
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PickerDetailResponse {
+    #[serde(flatten)]
+    pub picker: PickerInfo,
+    /// 已审核通过的版本历史，最新的在前
+    pub versions: Vec<PickerVersionInfo>,
 }
 
 // 市场响应
@@ -73,6 +175,46 @@ pub struct MarketResponse {
     pub total: u64,
 }
 
+// 开发者Picker查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MyPickerQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+    pub status: Option<PickerStatus>,
+}
+
+// 开发者查看的Picker信息，包含审核信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeveloperPickerInfo {
+    #[serde(flatten)]
+    pub picker: PickerInfo,
+    /// 最近一次审核的意见
+    pub review_notes: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+impl From<Picker> for DeveloperPickerInfo {
+    fn from(mut picker: Picker) -> Self {
+        let review_notes = picker.review_notes.take();
+        let submitted_at = picker.submitted_at;
+        let reviewed_at = picker.reviewed_at;
+        Self {
+            picker: picker.into(),
+            review_notes,
+            submitted_at,
+            reviewed_at,
+        }
+    }
+}
+
+// 开发者Picker列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeveloperPickerListResponse {
+    pub pickers: Vec<DeveloperPickerInfo>,
+    pub total: u64,
+}
+
 // 上传Picker
 #[utoipa::path(
     post,
@@ -118,8 +260,11 @@ pub async fn upload_picker(
     let mut description = String::new();
     let mut price = 0i64;
     let mut version = String::new();
+    let mut draft = false;
     let mut image_path = String::new();
     let mut file_path = String::new();
+    let mut checksum = String::new();
+    let mut changelog = String::new();
 
     // 处理multipart数据
     while let Some(field) = multipart.next_field().await.map_err(|_| AppError::BadRequest("Invalid multipart data".to_string()))? {
@@ -139,6 +284,13 @@ pub async fn upload_picker(
             "version" => {
                 version = field.text().await.map_err(|_| AppError::BadRequest("Invalid version".to_string()))?;
             }
+            "changelog" => {
+                changelog = field.text().await.map_err(|_| AppError::BadRequest("Invalid changelog".to_string()))?;
+            }
+            "draft" => {
+                let draft_str = field.text().await.map_err(|_| AppError::BadRequest("Invalid draft".to_string()))?;
+                draft = draft_str.parse().map_err(|_| AppError::BadRequest("Invalid draft format".to_string()))?;
+            }
             "image" => {
                 let filename = field.file_name().unwrap_or("picker_white.jpg").to_string();
                 let data = field.bytes().await.map_err(|_| AppError::BadRequest("Invalid image data".to_string()))?;
@@ -156,38 +308,8 @@ pub async fn upload_picker(
                 image_path = format!("http://localhost:3000/{}", image_path);
             }
             "file" => {
-                let filename = field.file_name().unwrap_or("picker_unknown.exe").to_string();
-                // let data = field.bytes().await.map_err(|_| AppError::BadRequest("Invalid file data".to_string()))?;
-                
-                // 创建上传目录
-                tokio::fs::create_dir_all("uploads/files").await.map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to create uploads/files directory: {:?}", e))
-                })?;
-                
-                // 生成唯一文件名
-                let unique_filename = format!("{}_{}", filename, Uuid::new_v4());
-                file_path = format!("uploads/files/{}", unique_filename);
-                
-                // 保存文件 - 流式写入
-                let mut file = tokio::fs::File::create(&file_path).await.map_err(|e| {
-                    AppError::InternalServerError(format!("Failed to create file: {:?}", e))
-                })?;
-                
-                let mut stream = field;
-                let mut _total_bytes_written = 0;
-                
-                while let Some(chunk) = stream.next().await {
-                    let chunk = chunk.map_err(|e| {
-                        AppError::BadRequest(format!("Invalid file data: {:?}", e))
-                    })?;
-                    
-                    _total_bytes_written += chunk.len();
-                    
-                    tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await.map_err(|e| {
-                        AppError::InternalServerError(format!("Failed to write to file: {:?}", e))
-                    })?;
-                }
-                            }
+                (file_path, checksum) = save_picker_file(field).await?;
+            }
             _ => {}
         }
     }
@@ -196,20 +318,32 @@ pub async fn upload_picker(
     if alias.is_empty() || description.is_empty() || version.is_empty() || file_path.is_empty() {
         return Err(AppError::BadRequest("Missing required fields".to_string()));
     }
+    let version = parse_version(&version)?.to_string();
+    if changelog.trim().is_empty() {
+        changelog = "Initial release".to_string();
+    }
 
     // 单独处理 image 为空情况
     if image_path.is_empty() {
         image_path = "picker_white.jpg".to_string(); // 提供默认值
     }
 
-    // 创建Picker记录
+    // 创建Picker记录，需要管理员审核通过后才会上架
     let picker_id = Uuid::new_v4();
     let now = Utc::now();
+    let (status, submitted_at) = if draft {
+        (PickerStatus::Draft, None)
+    } else {
+        (PickerStatus::PendingReview, Some(now.to_rfc3339()))
+    };
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
 
     sqlx::query(
         r#"
-        INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'active', 0, ?, ?)
+        INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at, submitted_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 0, ?, ?, ?)
         "#,
     )
     .bind(picker_id)
@@ -220,32 +354,396 @@ pub async fn upload_picker(
     .bind(&image_path)
     .bind(&file_path)
     .bind(&version)
+    .bind(&status)
     .bind(now.to_rfc3339())
     .bind(now.to_rfc3339())
-    .execute(&state.db)
+    .bind(submitted_at)
+    .execute(&mut *tx)
     .await
     .map_err(|e| AppError::DatabaseError(format!("Failed to insert picker: {:?}", e)))?;
 
+    // 上传的文件作为第一个版本
+    insert_picker_version(&mut tx, picker_id, &version, &changelog, &file_path, &checksum, now).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    let message = if draft {
+        "Picker saved as draft"
+    } else {
+        "Picker uploaded successfully, waiting for review"
+    };
     Ok(Json(UploadPickerResponse {
         picker_id,
-        message: "Picker uploaded successfully".to_string(),
+        message: message.to_string(),
+    }))
+}
+
+// 解析版本号，允许省略次版本号和修订号（1.0 视为 1.0.0）以及前缀 v
+fn parse_version(version: &str) -> Result<semver::Version, AppError> {
+    let version = version.trim().trim_start_matches('v');
+    let (core, suffix) = version.split_at(version.find(['-', '+']).unwrap_or(version.len()));
+    let padding = match core.matches('.').count() {
+        0 => ".0.0",
+        1 => ".0",
+        _ => "",
+    };
+    semver::Version::parse(&format!("{}{}{}", core, padding, suffix))
+        .map_err(|_| AppError::BadRequest(format!("Invalid version: {}, expected semantic version such as 1.2.0", version)))
+}
+
+// 流式保存上传的Picker文件，同时计算 SHA-256，返回文件路径和校验和
+async fn save_picker_file(field: Field<'_>) -> Result<(String, String), AppError> {
+    let filename = field.file_name().unwrap_or("picker_unknown.exe").to_string();
+
+    // 创建上传目录
+    tokio::fs::create_dir_all("uploads/files").await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to create uploads/files directory: {:?}", e))
+    })?;
+
+    // 生成唯一文件名
+    let unique_filename = format!("{}_{}", filename, Uuid::new_v4());
+    let file_path = format!("uploads/files/{}", unique_filename);
+
+    // 保存文件 - 流式写入
+    let mut file = tokio::fs::File::create(&file_path).await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to create file: {:?}", e))
+    })?;
+
+    let mut hasher = Sha256::new();
+    let mut stream = field;
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            AppError::BadRequest(format!("Invalid file data: {:?}", e))
+        })?;
+
+        hasher.update(&chunk);
+        tokio::io::AsyncWriteExt::write_all(&mut file, &chunk).await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to write to file: {:?}", e))
+        })?;
+    }
+
+    Ok((file_path, hex::encode(hasher.finalize())))
+}
+
+// 新版本都等待管理员审核，首个版本随Picker一起审核
+async fn insert_picker_version(
+    conn: &mut SqliteConnection,
+    picker_id: Uuid,
+    version: &str,
+    changelog: &str,
+    file_path: &str,
+    checksum: &str,
+    created_at: DateTime<Utc>,
+) -> Result<Uuid, AppError> {
+    let version_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO picker_versions (version_id, picker_id, version, changelog, file_path, checksum, created_at, status)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(version_id)
+    .bind(picker_id)
+    .bind(version)
+    .bind(changelog)
+    .bind(file_path)
+    .bind(checksum)
+    .bind(created_at.to_rfc3339())
+    .bind(PickerVersionStatus::Pending)
+    .execute(conn)
+    .await
+    .map_err(|e| match e.as_database_error() {
+        // 每个Picker只允许一个待审核版本，并发发布时由唯一索引拦下
+        Some(db_error) if db_error.is_unique_violation() => AppError::Conflict("Another version is already waiting for review".to_string()),
+        _ => AppError::DatabaseError(format!("Failed to insert picker version: {:?}", e)),
+    })?;
+    Ok(version_id)
+}
+
+// 获取Picker的全部版本，最新的在前
+pub(crate) async fn get_picker_versions(db: &crate::database::DbPool, picker_id: Uuid) -> Result<Vec<PickerVersion>, AppError> {
+    sqlx::query_as::<_, PickerVersion>(
+        "SELECT * FROM picker_versions WHERE picker_id = ? ORDER BY created_at DESC",
+    )
+    .bind(picker_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get picker versions: {:?}", e)))
+}
+
+// 发布新版本
+#[utoipa::path(
+    post,
+    path = "/api/pickers/{picker_id}/versions",
+    tag = "pickers",
+    summary = "Publish a new Picker version",
+    description = "Developer publishes a new release of their own Picker. The version must be a semantic version greater than all earlier ones. It waits for administrator review and is served to buyers, without buying again, once approved",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
+    ),
+    request_body(
+        content = PublishVersionRequest,
+        content_type = "multipart/form-data",
+        description = "Release file and information"
+    ),
+    responses(
+        (status = 200, description = "Version published", body = PublishVersionResponse),
+        (status = 400, description = "Invalid or not increasing version, missing fields, another version waiting for review, or picker not editable", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Another version was published at the same time", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn publish_picker_version(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path(picker_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<PublishVersionResponse>, AppError> {
+    // 先确认Picker属于当前开发者，再接收文件
+    let picker = sqlx::query_as::<_, Picker>(
+        "SELECT * FROM pickers WHERE picker_id = ? AND dev_user_id = ?",
+    )
+    .bind(picker_id)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+    .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    if matches!(picker.status, PickerStatus::Inactive | PickerStatus::Suspended) {
+        return Err(AppError::BadRequest("Cannot publish versions for an inactive or suspended picker".to_string()));
+    }
+    if picker.status == PickerStatus::PendingReview {
+        return Err(AppError::BadRequest("Cannot publish versions while the picker is pending review".to_string()));
+    }
+
+    let mut version = String::new();
+    let mut changelog = String::new();
+    let mut file_path = String::new();
+    let mut checksum = String::new();
+
+    // 文件保存后出现任何错误都要删除文件
+    let published = async {
+        while let Some(field) = multipart.next_field().await.map_err(|_| AppError::BadRequest("Invalid multipart data".to_string()))? {
+            let name = field.name().unwrap_or("").to_string();
+
+            match name.as_str() {
+                "version" => {
+                    version = field.text().await.map_err(|_| AppError::BadRequest("Invalid version".to_string()))?;
+                }
+                "changelog" => {
+                    changelog = field.text().await.map_err(|_| AppError::BadRequest("Invalid changelog".to_string()))?;
+                }
+                "file" => {
+                    (file_path, checksum) = save_picker_file(field).await?;
+                }
+                _ => {}
+            }
+        }
+
+        if version.is_empty() || changelog.trim().is_empty() || file_path.is_empty() {
+            return Err(AppError::BadRequest("Missing required fields".to_string()));
+        }
+        let new_version = parse_version(&version)?;
+        let versions = get_picker_versions(&state.db, picker_id).await?;
+        // 同一时间只有一个版本等待审核，避免审核顺序打乱最新版本
+        if let Some(pending) = versions.iter().find(|v| v.status == PickerVersionStatus::Pending) {
+            return Err(AppError::BadRequest(format!("Version {} is still waiting for review", pending.version)));
+        }
+        let latest = versions
+            .iter()
+            .filter_map(|v| semver::Version::parse(&v.version).ok())
+            .chain(parse_version(&picker.version).ok())
+            .max();
+        if let Some(latest) = latest {
+            if new_version <= latest {
+                return Err(AppError::BadRequest(format!("Version {} must be greater than the latest version {}", new_version, latest)));
+            }
+        }
+        let new_version = new_version.to_string();
+
+        let mut conn = state.db.acquire().await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to acquire connection: {:?}", e)))?;
+
+        // Picker 记录仍指向当前版本，管理员审核通过后才切换
+        let version_id = insert_picker_version(&mut conn, picker_id, &new_version, changelog.trim(), &file_path, &checksum, Utc::now()).await?;
+        Ok((version_id, new_version))
+    }
+    .await;
+    let (version_id, new_version) = match published {
+        Ok(published) => published,
+        Err(e) => {
+            if !file_path.is_empty() {
+                let _ = tokio::fs::remove_file(&file_path).await;
+            }
+            return Err(e);
+        }
+    };
+
+    Ok(Json(PublishVersionResponse {
+        version_id,
+        version: new_version,
+        checksum,
+        message: "Version published, waiting for review".to_string(),
     }))
 }
 
+// 提交Picker审核
+#[utoipa::path(
+    post,
+    path = "/api/pickers/{picker_id}/submit",
+    tag = "pickers",
+    summary = "Submit a Picker for review",
+    description = "Developer submits a draft or resubmits a rejected Picker for review",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
+    ),
+    responses(
+        (status = 200, description = "Submitted for review", body = String),
+        (status = 400, description = "Picker is not a draft or rejected", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 409, description = "Picker status was changed by another request", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn submit_picker(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path(picker_id): Path<Uuid>,
+) -> Result<String, AppError> {
+    let picker = sqlx::query_as::<_, Picker>(
+        "SELECT * FROM pickers WHERE picker_id = ? AND dev_user_id = ?",
+    )
+    .bind(picker_id)
+    .bind(user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+    .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    if !matches!(picker.status, PickerStatus::Draft | PickerStatus::Rejected) {
+        return Err(AppError::BadRequest("Only draft or rejected pickers can be submitted for review".to_string()));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    // 状态在读取后可能已被并发请求修改，只提交仍是草稿或被拒的Picker
+    let updated = sqlx::query(
+        "UPDATE pickers SET status = ?, submitted_at = ?, updated_at = ? WHERE picker_id = ? AND status IN (?, ?)",
+    )
+    .bind(PickerStatus::PendingReview)
+    .bind(&now)
+    .bind(&now)
+    .bind(picker_id)
+    .bind(PickerStatus::Draft)
+    .bind(PickerStatus::Rejected)
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to submit picker: {:?}", e)))?;
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Conflict("Picker status was changed by another request".to_string()));
+    }
+
+    Ok("Picker submitted for review".to_string())
+}
+
+// 获取开发者自己的Picker
+#[utoipa::path(
+    get,
+    path = "/api/pickers/mine",
+    tag = "pickers",
+    summary = "Get My Pickers",
+    description = "Developer lists their own pickers in any status together with review notes",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
+        ("status" = Option<PickerStatus>, Query, description = "Picker status filter")
+    ),
+    responses(
+        (status = 200, description = "Get pickers successfully", body = DeveloperPickerListResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn get_my_pickers(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Query(query): Query<MyPickerQuery>,
+) -> Result<Json<DeveloperPickerListResponse>, AppError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let size = query.size.unwrap_or(10);
+    let offset = (page - 1) * size;
+
+    let total: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM pickers WHERE dev_user_id = ? AND (? IS NULL OR status = ?)",
+    )
+    .bind(user_id)
+    .bind(&query.status)
+    .bind(&query.status)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get picker total: {:?}", e)))?;
+
+    let pickers: Vec<Picker> = sqlx::query_as(
+        "SELECT * FROM pickers WHERE dev_user_id = ? AND (? IS NULL OR status = ?) ORDER BY updated_at DESC LIMIT ? OFFSET ?",
+    )
+    .bind(user_id)
+    .bind(&query.status)
+    .bind(&query.status)
+    .bind(size as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get pickers: {:?}", e)))?;
+
+    Ok(Json(DeveloperPickerListResponse {
+        pickers: pickers.into_iter().map(DeveloperPickerInfo::from).collect(),
+        total: total.0 as u64,
+    }))
+}
+
+fn push_market_filters(builder: &mut QueryBuilder<'_, Sqlite>, query: &MarketQuery) {
+    builder.push(" WHERE status = 'active'");
+    if let Some(keyword) = &query.keyword {
+        let pattern = format!("%{}%", keyword);
+        builder.push(" AND (alias LIKE ").push_bind(pattern.clone())
+            .push(" OR description LIKE ").push_bind(pattern).push(")");
+    }
+    // 与 Picker::rating_average 相同的方式保留一位小数，按展示的评分过滤
+    if let Some(min_rating) = query.min_rating {
+        builder.push(" AND rating_count > 0 AND ROUND(CAST(rating_total AS REAL) / rating_count * 10) / 10 >= ").push_bind(min_rating);
+    }
+}
+
 // 获取市场列表
 #[utoipa::path(
     get,
     path = "/api/pickers",
     tag = "pickers",
     summary = "Get Picker Market List",
-    description = "Get a list of available pickers, supports pagination and search",
+    description = "Get a list of available pickers, supports pagination, search, sorting and filtering by rating",
     params(
         ("page" = Option<u32>, Query, description = "Page number, default is 1"),
         ("size" = Option<u32>, Query, description = "Number of items per page, default is 10"),
-        ("keyword" = Option<String>, Query, description = "Search keyword")
+        ("keyword" = Option<String>, Query, description = "Search keyword"),
+        ("sort" = Option<MarketSort>, Query, description = "Sort order: newest (default), rating or downloads"),
+        ("min_rating" = Option<f64>, Query, description = "Only return pickers whose displayed average rating is at least this value, between 0 and 5")
     ),
     responses(
         (status = 200, description = "Get market list successfully", body = MarketResponse),
+        (status = 400, description = "Invalid min_rating", body = crate::openapi::ErrorResponse),
         (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
     )
 )]
@@ -259,72 +757,39 @@ pub async fn get_market(
     let page = if page < 1 { 1 } else { page };
     let offset = (page - 1) * size;
 
-    // 构建查询条件和获取数据
-    let (pickers, total) = if let Some(keyword) = &query.keyword {
-        let search_pattern = format!("%{}%", keyword);
-
-        // 获取总数
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) as count FROM pickers WHERE status = 'active' AND (alias LIKE ? OR description LIKE ?)"
-        )
-        .bind(&search_pattern)
-        .bind(&search_pattern)
-        .fetch_one(&state.db)
-        .await
-        .map_err(|e| AppError::DatabaseError(format!("Failed to get market total: {:?}", e)))?;
-
-        // 获取Picker列表
-        let pickers: Vec<Picker> = sqlx::query_as(
-            "SELECT * FROM pickers WHERE status = 'active' AND (alias LIKE ? OR description LIKE ?) ORDER BY created_at DESC LIMIT ? OFFSET ?"
-        )
-        .bind(&search_pattern)
-        .bind(&search_pattern)
-        .bind(size as i64)
-        .bind(offset as i64)
-        .fetch_all(&state.db)
-        .await
-        .map_err(|e| AppError::DatabaseError(format!("Failed to get market pickers: {:?}", e)))?;
+    if let Some(min_rating) = query.min_rating {
+        if !(0.0..=5.0).contains(&min_rating) {
+            return Err(AppError::BadRequest("min_rating must be between 0 and 5".to_string()));
+        }
+    }
 
-        (pickers, total.0)
-    } else {
-        // 获取总数
-        let total: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) as count FROM pickers WHERE status = 'active'"
-        )
+    // 获取总数
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM pickers");
+    push_market_filters(&mut count_builder, &query);
+    let total: (i64,) = count_builder.build_query_as()
         .fetch_one(&state.db)
         .await
         .map_err(|e| AppError::DatabaseError(format!("Failed to get market total: {:?}", e)))?;
 
-        // 获取Picker列表
-        let pickers: Vec<Picker> = sqlx::query_as(
-            "SELECT * FROM pickers WHERE status = 'active' ORDER BY created_at DESC LIMIT ? OFFSET ?"
-        )
-        .bind(size as i64)
-        .bind(offset as i64)
+    // 获取Picker列表
+    let mut builder = QueryBuilder::new("SELECT * FROM pickers");
+    push_market_filters(&mut builder, &query);
+    builder.push(match query.sort.unwrap_or_default() {
+        MarketSort::Newest => " ORDER BY created_at DESC",
+        MarketSort::Rating => " ORDER BY (CASE WHEN rating_count = 0 THEN 0 ELSE CAST(rating_total AS REAL) / rating_count END) DESC, rating_count DESC, created_at DESC",
+        MarketSort::Downloads => " ORDER BY download_count DESC, created_at DESC",
+    });
+    builder.push(" LIMIT ").push_bind(size as i64).push(" OFFSET ").push_bind(offset as i64);
+    let pickers: Vec<Picker> = builder.build_query_as()
         .fetch_all(&state.db)
         .await
         .map_err(|e| AppError::DatabaseError(format!("Failed to get market pickers: {:?}", e)))?;
 
-        (pickers, total.0)
-    };
-
-    let picker_infos: Vec<PickerInfo> = pickers.into_iter().map(|p| PickerInfo {
-        picker_id: p.picker_id,
-        dev_user_id: p.dev_user_id,
-        alias: p.alias,
-        description: p.description,
-        price: p.price,
-        image_path: p.image_path,
-        version: p.version,
-        download_count: p.download_count,
-        created_at: p.created_at,
-        updated_at: p.updated_at,
-        status: p.status,
-    }).collect();
+    let picker_infos: Vec<PickerInfo> = pickers.into_iter().map(PickerInfo::from).collect();
 
     Ok(Json(MarketResponse {
         pickers: picker_infos,
-        total: total as u64,
+        total: total.0 as u64,
     }))
 }
 
@@ -334,12 +799,12 @@ pub async fn get_market(
     path = "/api/pickers/{picker_id}",
     tag = "pickers",
     summary = "Get Picker Details",
-    description = "Get details of a specific picker by its ID",
+    description = "Get details of a specific picker by its ID, including its release history",
     params(
         ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
     ),
     responses(
-        (status = 200, description = "Get picker details successfully", body = PickerInfo),
+        (status = 200, description = "Get picker details successfully", body = PickerDetailResponse),
         (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
         (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
     )
@@ -347,7 +812,7 @@ pub async fn get_market(
 pub async fn get_picker_detail(
     State(state): State<AppState>,
     Path(picker_id): Path<Uuid>,
-) -> Result<Json<PickerInfo>, AppError> {
+) -> Result<Json<PickerDetailResponse>, AppError> {
     let picker = sqlx::query_as::<_, Picker>(
         "SELECT * FROM pickers WHERE picker_id = ? AND status = 'active'",
     )
@@ -357,18 +822,15 @@ pub async fn get_picker_detail(
     .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
     .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
 
-    Ok(Json(PickerInfo {
-        picker_id: picker.picker_id,
-        dev_user_id: picker.dev_user_id,
-        alias: picker.alias,
-        description: picker.description,
-        price: picker.price,
-        image_path: picker.image_path,
-        version: picker.version,
-        download_count: picker.download_count as i64,
-        created_at: picker.created_at,
-        updated_at: picker.updated_at,
-        status: picker.status,
+    let versions = get_picker_versions(&state.db, picker_id).await?;
+
+    Ok(Json(PickerDetailResponse {
+        picker: picker.into(),
+        versions: versions
+            .into_iter()
+            .filter(|v| v.status == PickerVersionStatus::Approved)
+            .map(PickerVersionInfo::from)
+            .collect(),
     }))
 }
 
@@ -443,9 +905,16 @@ pub async fn delete_picker(
         }
     }
 
-    // 删除Picker文件
-    if !picker.file_path.is_empty() {
-        let full_file_path = format!("{}/{}", current_dir.display(), picker.file_path); 
+    // 删除Picker文件，包括所有历史版本的文件
+    let mut file_paths: Vec<String> = get_picker_versions(&state.db, picker_id).await?
+        .into_iter()
+        .map(|v| v.file_path)
+        .collect();
+    if !file_paths.contains(&picker.file_path) {
+        file_paths.push(picker.file_path.clone());
+    }
+    for file_path in file_paths.iter().filter(|p| !p.is_empty()) {
+        let full_file_path = format!("{}/{}", current_dir.display(), file_path); 
 
         // 尝试删除文件，忽略删除失败的情况（文件可能不存在）
         if tokio::fs::try_exists(&full_file_path).await.map_err(|e| AppError::InternalServerError(format!("Failed to check file existence: {:?}", e)))? {
@@ -520,6 +989,8 @@ mod tests {
             page: Some(1),
             size: Some(10),
             keyword: None,
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -592,6 +1063,8 @@ mod tests {
             page: Some(1),
             size: Some(10),
             keyword: Some("game".to_string()),
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -647,6 +1120,8 @@ mod tests {
             page: Some(2),
             size: Some(10),
             keyword: None,
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -705,6 +1180,8 @@ mod tests {
             page: None, // 使用默认值
             size: None, // 使用默认值
             keyword: None,
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -756,11 +1233,12 @@ mod tests {
         assert!(result.is_ok());
 
         let response = result.unwrap();
-        assert_eq!(response.picker_id, picker_id);
-        assert_eq!(response.alias, "Test Picker");
-        assert_eq!(response.description, "Test Description");
-        assert_eq!(response.price, 500);
-        assert_eq!(response.download_count, 10);
+        assert_eq!(response.picker.picker_id, picker_id);
+        assert_eq!(response.picker.alias, "Test Picker");
+        assert_eq!(response.picker.description, "Test Description");
+        assert_eq!(response.picker.price, 500);
+        assert_eq!(response.picker.download_count, 10);
+        assert!(response.versions.is_empty());
     }
 
     #[tokio::test]
@@ -885,6 +1363,8 @@ mod tests {
             page: Some(1),
             size: Some(10),
             keyword: Some("nonexistent".to_string()),
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -938,6 +1418,8 @@ mod tests {
             page: Some(0), // 无效页码
             size: Some(10),
             keyword: None,
+            sort: None,
+            min_rating: None,
         };
 
         let result = get_market(State(state), Query(query)).await;
@@ -1044,4 +1526,272 @@ mod tests {
         let response = app.oneshot(request).await.unwrap();
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[test]
+    fn test_parse_version_lenient() {
+        assert_eq!(parse_version("1.0").unwrap().to_string(), "1.0.0");
+        assert_eq!(parse_version("v2").unwrap().to_string(), "2.0.0");
+        assert_eq!(parse_version("1.2-beta.1").unwrap().to_string(), "1.2.0-beta.1");
+        assert!(parse_version("latest").is_err());
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_publish_picker_version() {
+        let state = create_test_app_state().await;
+        let dev_user_id = Uuid::new_v4();
+        let picker_id = Uuid::new_v4();
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+            VALUES (?, ?, 'Dev User', 'hashed_password', 'dev', 'private_key_123', 'devwallet123', 0, ?)
+            "#,
+        )
+        .bind(dev_user_id)
+        .bind(format!("{}@version-test.com", dev_user_id))
+        .bind(now.to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Test Picker', 'Test Description', 500, 'test.jpg', 'test.exe', '1.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_user_id)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        use axum::Router;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+
+        let app = Router::new()
+            .route("/api/pickers/{picker_id}/versions", axum::routing::post(publish_picker_version))
+            .layer(axum::middleware::from_fn_with_state(state.clone(), move |State(_state): State<AppState>, mut request: axum::http::Request<axum::body::Body>, next: axum::middleware::Next| async move {
+                request.extensions_mut().insert(dev_user_id);
+                next.run(request).await
+            }))
+            .with_state(state.clone());
+
+        let publish = |version: &str| {
+            let boundary = "boundary123";
+            let body = format!("--{boundary}\r\nContent-Disposition: form-data; name=\"version\"\r\n\r\n{version}\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"changelog\"\r\n\r\nBug fixes\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"test.exe\"\r\nContent-Type: application/octet-stream\r\n\r\nnew_file_data\r\n--{boundary}--\r\n");
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/pickers/{}/versions", picker_id))
+                .header("content-type", format!("multipart/form-data; boundary={boundary}"))
+                .body(axum::body::Body::from(body))
+                .unwrap()
+        };
+
+        // 新版本必须大于当前版本
+        let response = app.clone().oneshot(publish("1.0.0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app.clone().oneshot(publish("1.1.0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // 新版本等待审核，Picker仍指向原来的版本
+        let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ?")
+            .bind(picker_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(picker.version, "1.0");
+        assert_eq!(picker.file_path, "test.exe");
+
+        let versions = get_picker_versions(&state.db, picker_id).await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].status, PickerVersionStatus::Pending);
+        assert_eq!(versions[0].checksum.len(), 64);
+
+        // 上一个版本审核完成前不能继续发布
+        let response = app.clone().oneshot(publish("1.2.0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // 待审核的Picker不能发布新版本
+        sqlx::query("UPDATE pickers SET status = 'pending_review' WHERE picker_id = ?")
+            .bind(picker_id)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE picker_versions SET status = 'approved' WHERE picker_id = ?")
+            .bind(picker_id)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        let response = app.oneshot(publish("1.2.0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_concurrent_publish_keeps_one_pending_version() {
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+
+        let state = create_test_app_state().await;
+        let dev_user_id = Uuid::new_v4();
+        let picker_id = Uuid::new_v4();
+        let now = Utc::now();
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+            VALUES (?, ?, 'Dev User', 'hashed_password', 'dev', 'private_key_123', 'devwallet123', 0, ?)
+            "#,
+        )
+        .bind(dev_user_id)
+        .bind(format!("{}@version-race.com", dev_user_id))
+        .bind(now.to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Test Picker', 'Test Description', 500, 'test.jpg', 'test.exe', '1.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_user_id)
+        .bind(now.to_rfc3339())
+        .bind(now.to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        let app = axum::Router::new()
+            .route("/api/pickers/{picker_id}/versions", axum::routing::post(publish_picker_version))
+            .layer(axum::middleware::from_fn(move |mut request: axum::http::Request<axum::body::Body>, next: axum::middleware::Next| async move {
+                request.extensions_mut().insert(dev_user_id);
+                next.run(request).await
+            }))
+            .with_state(state.clone());
+        let prefix = format!("race-{}", picker_id);
+        let publish = |version: &str| {
+            let boundary = "boundary123";
+            let body = format!("--{boundary}\r\nContent-Disposition: form-data; name=\"version\"\r\n\r\n{version}\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"changelog\"\r\n\r\nBug fixes\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{prefix}.exe\"\r\nContent-Type: application/octet-stream\r\n\r\nnew_file_data\r\n--{boundary}--\r\n");
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/pickers/{}/versions", picker_id))
+                .header("content-type", format!("multipart/form-data; boundary={boundary}"))
+                .body(axum::body::Body::from(body))
+                .unwrap()
+        };
+
+        // 同时发布两个版本，只有一个进入审核，另一个的文件被删除
+        let (first, second) = tokio::join!(app.clone().oneshot(publish("1.1.0")), app.clone().oneshot(publish("1.2.0")));
+        let statuses = [first.unwrap().status(), second.unwrap().status()];
+        assert_eq!(statuses.iter().filter(|status| **status == StatusCode::OK).count(), 1);
+        assert!(statuses.iter().any(|status| matches!(*status, StatusCode::CONFLICT | StatusCode::BAD_REQUEST)));
+
+        let versions = get_picker_versions(&state.db, picker_id).await.unwrap();
+        assert_eq!(versions.len(), 1);
+        let mut saved = Vec::new();
+        let mut entries = tokio::fs::read_dir("uploads/files").await.unwrap();
+        while let Some(entry) = entries.next_entry().await.unwrap() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                saved.push(format!("uploads/files/{}", entry.file_name().to_string_lossy()));
+            }
+        }
+        assert_eq!(saved, vec![versions[0].file_path.clone()]);
+
+        // 唯一索引拦下的发布返回 409
+        let mut conn = state.db.acquire().await.unwrap();
+        let result = insert_picker_version(&mut conn, picker_id, "1.3.0", "changes", "test.exe", "checksum", Utc::now()).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let _ = tokio::fs::remove_file(&versions[0].file_path).await;
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_get_market_sort_and_filter_by_rating() {
+        let state = create_test_app_state().await;
+        let dev_user_id = Uuid::new_v4();
+        // 用唯一关键字隔离其他测试插入的Picker
+        let keyword = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+            VALUES (?, ?, 'Dev User', 'hashed_password', 'dev', 'private_key_123', 'devwallet123', 0, ?)
+            "#,
+        )
+        .bind(dev_user_id)
+        .bind(format!("{}@rating-test.com", dev_user_id))
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+        // (别名, 评价数量, 评分总和)
+        for (alias, rating_count, rating_total) in [("unrated", 0, 0), ("average", 2, 7), ("best", 3, 14)] {
+            sqlx::query(
+                r#"
+                INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at, rating_count, rating_total)
+                VALUES (?, ?, ?, ?, 500, 'test.jpg', 'test.exe', '1.0.0', 'active', 0, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(dev_user_id)
+            .bind(alias)
+            .bind(&keyword)
+            .bind(Utc::now().to_rfc3339())
+            .bind(Utc::now().to_rfc3339())
+            .bind(rating_count)
+            .bind(rating_total)
+            .execute(&state.db)
+            .await
+            .unwrap();
+        }
+
+        let query = MarketQuery {
+            page: None,
+            size: None,
+            keyword: Some(keyword.clone()),
+            sort: Some(MarketSort::Rating),
+            min_rating: None,
+        };
+        let response = get_market(State(state.clone()), Query(query)).await.unwrap();
+        let aliases: Vec<&str> = response.pickers.iter().map(|p| p.alias.as_str()).collect();
+        assert_eq!(aliases, ["best", "average", "unrated"]);
+        assert_eq!(response.pickers[0].rating_average, 4.7);
+        assert_eq!(response.pickers[0].rating_count, 3);
+
+        let query = MarketQuery {
+            page: None,
+            size: None,
+            keyword: Some(keyword.clone()),
+            sort: None,
+            min_rating: Some(4.0),
+        };
+        let response = get_market(State(state.clone()), Query(query)).await.unwrap();
+        assert_eq!(response.total, 1);
+        assert_eq!(response.pickers[0].alias, "best");
+
+        // 按展示的 4.7 过滤，而不是 14 / 3 的原始平均值
+        let query = |min_rating: f64| MarketQuery {
+            page: None,
+            size: None,
+            keyword: Some(keyword.clone()),
+            sort: None,
+            min_rating: Some(min_rating),
+        };
+        let response = get_market(State(state.clone()), Query(query(4.7))).await.unwrap();
+        assert_eq!(response.total, 1);
+        assert_eq!(response.pickers[0].rating_average, 4.7);
+
+        for min_rating in [-0.5, 5.1, f64::NAN] {
+            assert!(matches!(get_market(State(state.clone()), Query(query(min_rating))).await, Err(AppError::BadRequest(_))));
+        }
+    }
 }
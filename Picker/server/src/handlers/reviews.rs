@@ -0,0 +1,525 @@
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    Extension,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::config::AppState;
+use crate::handlers::notifications::notify;
+use crate::models::{Picker, PickerReview};
+use crate::utils::AppError;
+
+// 评价和回复内容的最大长度
+const MAX_REVIEW_LENGTH: usize = 2000;
+
+// 评价查询参数
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReviewQuery {
+    pub page: Option<u32>,
+    pub size: Option<u32>,
+}
+
+// 发表或修改评价请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubmitReviewRequest {
+    /// 评分，1-5 星
+    pub rating: i64,
+    /// 评价内容
+    pub content: String,
+}
+
+// 开发者回复评价请求
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplyReviewRequest {
+    pub reply: String,
+}
+
+// 评价信息
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewInfo {
+    pub review_id: Uuid,
+    pub picker_id: Uuid,
+    pub user_id: Uuid,
+    /// 评价者的用户名
+    pub user_name: String,
+    pub rating: i64,
+    pub content: String,
+    /// 开发者的回复
+    pub dev_reply: Option<String>,
+    pub replied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// 评价及评价者用户名
+#[derive(FromRow)]
+struct ReviewRow {
+    #[sqlx(flatten)]
+    review: PickerReview,
+    user_name: String,
+}
+
+impl From<ReviewRow> for ReviewInfo {
+    fn from(row: ReviewRow) -> Self {
+        Self {
+            review_id: row.review.review_id,
+            picker_id: row.review.picker_id,
+            user_id: row.review.user_id,
+            user_name: row.user_name,
+            rating: row.review.rating,
+            content: row.review.content,
+            dev_reply: row.review.dev_reply,
+            replied_at: row.review.replied_at,
+            created_at: row.review.created_at,
+            updated_at: row.review.updated_at,
+        }
+    }
+}
+
+// 评价列表响应
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReviewListResponse {
+    pub reviews: Vec<ReviewInfo>,
+    pub total: u64,
+    /// 平均评分，保留一位小数，没有评价时为 0
+    pub rating_average: f64,
+    pub rating_count: i64,
+}
+
+const REVIEW_SELECT: &str = "SELECT r.*, u.user_name FROM picker_reviews r JOIN users u ON r.user_id = u.user_id";
+
+// 校验评价或回复的文本内容，返回去掉首尾空白后的内容
+fn validate_text(text: &str, field: &str) -> Result<String, AppError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(AppError::BadRequest(format!("{} cannot be empty", field)));
+    }
+    if text.chars().count() > MAX_REVIEW_LENGTH {
+        return Err(AppError::BadRequest(format!("{} cannot exceed {} characters", field, MAX_REVIEW_LENGTH)));
+    }
+    Ok(text.to_string())
+}
+
+async fn get_active_picker(state: &AppState, picker_id: Uuid) -> Result<Picker, AppError> {
+    sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ? AND status = 'active'")
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))
+}
+
+async fn get_review_info(state: &AppState, review_id: Uuid) -> Result<ReviewInfo, AppError> {
+    sqlx::query_as::<_, ReviewRow>(&format!("{} WHERE r.review_id = ?", REVIEW_SELECT))
+        .bind(review_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get review: {:?}", e)))?
+        .map(ReviewInfo::from)
+        .ok_or_else(|| AppError::NotFound("Review not found".to_string()))
+}
+
+// 获取Picker的评价
+#[utoipa::path(
+    get,
+    path = "/api/pickers/{picker_id}/reviews",
+    tag = "reviews",
+    summary = "Get Picker Reviews",
+    description = "Get verified-purchase reviews of an active picker with the aggregate rating, most recently updated first",
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier"),
+        ("page" = Option<u32>, Query, description = "Page number, default is 1"),
+        ("size" = Option<u32>, Query, description = "Number of items per page, default is 10")
+    ),
+    responses(
+        (status = 200, description = "Get reviews successfully", body = ReviewListResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn get_picker_reviews(
+    State(state): State<AppState>,
+    Path(picker_id): Path<Uuid>,
+    Query(query): Query<ReviewQuery>,
+) -> Result<Json<ReviewListResponse>, AppError> {
+    let page = query.page.unwrap_or(1).max(1);
+    let size = query.size.unwrap_or(10);
+    let offset = (page - 1) * size;
+
+    let picker = get_active_picker(&state, picker_id).await?;
+
+    let reviews: Vec<ReviewRow> = sqlx::query_as(&format!(
+        "{} WHERE r.picker_id = ? ORDER BY r.updated_at DESC LIMIT ? OFFSET ?",
+        REVIEW_SELECT
+    ))
+    .bind(picker_id)
+    .bind(size as i64)
+    .bind(offset as i64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get reviews: {:?}", e)))?;
+
+    Ok(Json(ReviewListResponse {
+        reviews: reviews.into_iter().map(ReviewInfo::from).collect(),
+        total: picker.rating_count as u64,
+        rating_average: picker.rating_average(),
+        rating_count: picker.rating_count,
+    }))
+}
+
+// 发表或修改评价
+#[utoipa::path(
+    put,
+    path = "/api/pickers/{picker_id}/reviews",
+    tag = "reviews",
+    summary = "Submit or Update Review",
+    description = "Create the current user's review of a picker, or update it if one already exists. Only users with a successful order for the picker can review it",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier")
+    ),
+    request_body = SubmitReviewRequest,
+    responses(
+        (status = 200, description = "Review saved", body = ReviewInfo),
+        (status = 400, description = "Invalid rating or content, or reviewing own picker", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Picker has not been purchased", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker not found", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn submit_picker_review(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path(picker_id): Path<Uuid>,
+    Json(request): Json<SubmitReviewRequest>,
+) -> Result<Json<ReviewInfo>, AppError> {
+    if !(1..=5).contains(&request.rating) {
+        return Err(AppError::BadRequest("Rating must be between 1 and 5".to_string()));
+    }
+    let content = validate_text(&request.content, "Review content")?;
+
+    let picker = get_active_picker(&state, picker_id).await?;
+    if picker.dev_user_id == user_id {
+        return Err(AppError::BadRequest("Developers cannot review their own pickers".to_string()));
+    }
+
+    // 只有购买成功的用户可以评价
+    let purchased: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM orders WHERE user_id = ? AND picker_id = ? AND status = 'success'",
+    )
+    .bind(user_id)
+    .bind(picker_id)
+    .fetch_one(&state.db)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to check orders: {:?}", e)))?;
+    if purchased.0 == 0 {
+        return Err(AppError::Forbidden("Only users who purchased this picker can review it".to_string()));
+    }
+
+    let now = Utc::now();
+    // IMMEDIATE 事务开始时就持有写锁，读取旧评分到更新评分统计之间不会有其他评价写入
+    let mut tx = state.db.begin_with("BEGIN IMMEDIATE").await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    let previous: Option<(i64,)> = sqlx::query_as(
+        "SELECT rating FROM picker_reviews WHERE picker_id = ? AND user_id = ?",
+    )
+    .bind(picker_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to get review: {:?}", e)))?;
+
+    // 每个用户只有一条评价，再次提交时覆盖评分和内容
+    let (review_id,): (Uuid,) = sqlx::query_as(
+        r#"
+        INSERT INTO picker_reviews (review_id, picker_id, user_id, rating, content, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT (picker_id, user_id) DO UPDATE SET rating = excluded.rating, content = excluded.content, updated_at = excluded.updated_at
+        RETURNING review_id
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(picker_id)
+    .bind(user_id)
+    .bind(request.rating)
+    .bind(&content)
+    .bind(now.to_rfc3339())
+    .bind(now.to_rfc3339())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| AppError::DatabaseError(format!("Failed to save review: {:?}", e)))?;
+
+    // 评价和Picker的评分统计在同一事务中更新
+    let (added, previous_rating) = match previous {
+        Some((rating,)) => (0, rating),
+        None => (1, 0),
+    };
+    sqlx::query("UPDATE pickers SET rating_count = rating_count + ?, rating_total = rating_total + ? WHERE picker_id = ?")
+        .bind(added)
+        .bind(request.rating - previous_rating)
+        .bind(picker_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update picker rating: {:?}", e)))?;
+
+    if previous.is_none() {
+        let message = format!("Your picker \"{}\" received a new {}-star review", picker.alias, request.rating);
+        notify(&mut tx, picker.dev_user_id, Some(picker_id), "picker_reviewed", &message).await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(get_review_info(&state, review_id).await?))
+}
+
+// 开发者回复评价
+#[utoipa::path(
+    post,
+    path = "/api/pickers/{picker_id}/reviews/{review_id}/reply",
+    tag = "reviews",
+    summary = "Reply to Review",
+    description = "Developer replies to a review of their own picker. Replying again replaces the previous reply",
+    security(
+        ("bearer_auth" = [])
+    ),
+    params(
+        ("picker_id" = uuid::Uuid, Path, description = "Picker's unique identifier"),
+        ("review_id" = uuid::Uuid, Path, description = "Review's unique identifier")
+    ),
+    request_body = ReplyReviewRequest,
+    responses(
+        (status = 200, description = "Reply saved", body = ReviewInfo),
+        (status = 400, description = "Invalid reply", body = crate::openapi::ErrorResponse),
+        (status = 401, description = "Unauthorized access", body = crate::openapi::ErrorResponse),
+        (status = 404, description = "Picker or review not found", body = crate::openapi::ErrorResponse),
+        (status = 500, description = "Internal server error", body = crate::openapi::ErrorResponse)
+    )
+)]
+pub async fn reply_picker_review(
+    State(state): State<AppState>,
+    Extension(user_id): Extension<Uuid>,
+    Path((picker_id, review_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<ReplyReviewRequest>,
+) -> Result<Json<ReviewInfo>, AppError> {
+    let reply = validate_text(&request.reply, "Reply")?;
+
+    let picker = sqlx::query_as::<_, Picker>("SELECT * FROM pickers WHERE picker_id = ? AND dev_user_id = ?")
+        .bind(picker_id)
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get picker detail: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Picker not found".to_string()))?;
+
+    let review = sqlx::query_as::<_, PickerReview>("SELECT * FROM picker_reviews WHERE review_id = ? AND picker_id = ?")
+        .bind(review_id)
+        .bind(picker_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to get review: {:?}", e)))?
+        .ok_or_else(|| AppError::NotFound("Review not found".to_string()))?;
+
+    let mut tx = state.db.begin().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to begin transaction: {:?}", e)))?;
+
+    sqlx::query("UPDATE picker_reviews SET dev_reply = ?, replied_at = ? WHERE review_id = ?")
+        .bind(&reply)
+        .bind(Utc::now().to_rfc3339())
+        .bind(review_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to update review: {:?}", e)))?;
+
+    let message = format!("The developer of \"{}\" replied to your review", picker.alias);
+    notify(&mut tx, review.user_id, Some(picker_id), "review_replied", &message).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to commit transaction: {:?}", e)))?;
+
+    Ok(Json(get_review_info(&state, review_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils_tests::create_test_app_state;
+    use serial_test::serial;
+
+    async fn insert_user(state: &AppState, user_type: &str) -> Uuid {
+        let user_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO users (user_id, email, user_name, user_password, user_type, private_key, wallet_address, premium_balance, created_at)
+            VALUES (?, ?, 'Review User', 'hashed_password', ?, 'private_key_123', 'wallet123', 0, ?)
+            "#,
+        )
+        .bind(user_id)
+        .bind(format!("{}@review-test.com", user_id))
+        .bind(user_type)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+        user_id
+    }
+
+    async fn insert_picker(state: &AppState, dev_user_id: Uuid) -> Uuid {
+        let picker_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO pickers (picker_id, dev_user_id, alias, description, price, image_path, file_path, version, status, download_count, created_at, updated_at)
+            VALUES (?, ?, 'Review Picker', 'Test Description', 500, 'test.jpg', 'test.exe', '1.0.0', 'active', 0, ?, ?)
+            "#,
+        )
+        .bind(picker_id)
+        .bind(dev_user_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+        picker_id
+    }
+
+    async fn insert_success_order(state: &AppState, user_id: Uuid, picker_id: Uuid) {
+        sqlx::query(
+            r#"
+            INSERT INTO orders (order_id, user_id, picker_id, amount, pay_type, status, tx_hash, created_at, expires_at)
+            VALUES (?, ?, ?, 500, 'premium', 'success', NULL, ?, NULL)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_id)
+        .bind(picker_id)
+        .bind(Utc::now().to_rfc3339())
+        .execute(&state.db)
+        .await
+        .unwrap();
+    }
+
+    async fn submit(state: &AppState, user_id: Uuid, picker_id: Uuid, rating: i64) -> Result<Json<ReviewInfo>, AppError> {
+        submit_picker_review(
+            State(state.clone()),
+            Extension(user_id),
+            Path(picker_id),
+            Json(SubmitReviewRequest { rating, content: "Works well".to_string() }),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_review_requires_purchase_and_updates_rating() {
+        let state = create_test_app_state().await;
+        let dev_user_id = insert_user(&state, "dev").await;
+        let buyer_id = insert_user(&state, "gen").await;
+        let other_buyer_id = insert_user(&state, "gen").await;
+        let picker_id = insert_picker(&state, dev_user_id).await;
+
+        // 未购买不能评价
+        let result = submit(&state, buyer_id, picker_id, 5).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        insert_success_order(&state, buyer_id, picker_id).await;
+        insert_success_order(&state, other_buyer_id, picker_id).await;
+
+        let result = submit(&state, buyer_id, picker_id, 6).await;
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+
+        // 修改评价不会增加评价数量
+        let created = submit(&state, buyer_id, picker_id, 5).await.unwrap();
+        let updated = submit(&state, buyer_id, picker_id, 3).await.unwrap();
+        assert_eq!(created.review_id, updated.review_id);
+        assert_eq!(updated.rating, 3);
+        assert_eq!(submit(&state, other_buyer_id, picker_id, 4).await.unwrap().rating, 4);
+
+        let response = get_picker_reviews(
+            State(state.clone()),
+            Path(picker_id),
+            Query(ReviewQuery { page: None, size: None }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.rating_count, 2);
+        assert_eq!(response.rating_average, 3.5);
+        assert_eq!(response.reviews.len(), 2);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_concurrent_reviews_keep_rating_consistent() {
+        let state = create_test_app_state().await;
+        let dev_user_id = insert_user(&state, "dev").await;
+        let buyer_id = insert_user(&state, "gen").await;
+        let picker_id = insert_picker(&state, dev_user_id).await;
+        insert_success_order(&state, buyer_id, picker_id).await;
+
+        // 同一用户同时提交两次评价，只保留一条且评分统计与之一致
+        let (first, second) = tokio::join!(
+            submit(&state, buyer_id, picker_id, 2),
+            submit(&state, buyer_id, picker_id, 4),
+        );
+        assert_eq!(first.unwrap().review_id, second.unwrap().review_id);
+
+        let review: (i64,) = sqlx::query_as("SELECT rating FROM picker_reviews WHERE picker_id = ? AND user_id = ?")
+            .bind(picker_id)
+            .bind(buyer_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        let picker = get_active_picker(&state, picker_id).await.unwrap();
+        assert_eq!(picker.rating_count, 1);
+        assert_eq!(picker.rating_total, review.0);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reply_picker_review() {
+        let state = create_test_app_state().await;
+        let dev_user_id = insert_user(&state, "dev").await;
+        let other_dev_id = insert_user(&state, "dev").await;
+        let buyer_id = insert_user(&state, "gen").await;
+        let picker_id = insert_picker(&state, dev_user_id).await;
+        insert_success_order(&state, buyer_id, picker_id).await;
+
+        let review = submit(&state, buyer_id, picker_id, 4).await.unwrap();
+
+        // 只有Picker的开发者可以回复
+        let result = reply_picker_review(
+            State(state.clone()),
+            Extension(other_dev_id),
+            Path((picker_id, review.review_id)),
+            Json(ReplyReviewRequest { reply: "Thanks".to_string() }),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        let replied = reply_picker_review(
+            State(state.clone()),
+            Extension(dev_user_id),
+            Path((picker_id, review.review_id)),
+            Json(ReplyReviewRequest { reply: "Thanks for the feedback".to_string() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(replied.dev_reply.as_deref(), Some("Thanks for the feedback"));
+        assert!(replied.replied_at.is_some());
+
+        let notified: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM notifications WHERE user_id = ? AND kind = 'review_replied'")
+            .bind(buyer_id)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        assert_eq!(notified.0, 1);
+    }
+}
@@ -16,7 +16,7 @@ use alloy::primitives::{Address, U256, Uint};
 use alloy::providers::{Provider, ProviderBuilder};
 
 use crate::config::{AppState, Claims, PendingRegistration};
-use crate::models::{User, UserType, VerificationCode};
+use crate::models::{User, UserStatus, UserType, VerificationCode};
 use crate::utils::{generate_wallet, hash_password_with_user_id, verify_password_with_user_id, AppError};
 
 // 注册请求
@@ -84,6 +84,7 @@ pub struct UserInfo {
     pub wallet_address: String,
     pub premium_balance: i64,
     pub created_at: DateTime<Utc>,
+    pub status: UserStatus,
 }
 
 impl From<User> for UserInfo {
@@ -96,6 +97,7 @@ impl From<User> for UserInfo {
             wallet_address: user.wallet_address,
             premium_balance: user.premium_balance,
             created_at: user.created_at,
+            status: user.status,
         }
     }
 }
@@ -187,10 +189,10 @@ pub async fn register(
         return Err(AppError::UnprocessableEntity("Email is already in registration process, please verify or wait for expiration".to_string()));
     }
 
-    // 验证用户类型
+    // 验证用户类型，管理员只能通过配置文件指定，不能自行注册
     match payload.user_type {
         UserType::Gen | UserType::Dev => (),
-        // _ => return Err(AppError::UnprocessableEntity("Invalid user type".to_string())),
+        UserType::Admin => return Err(AppError::UnprocessableEntity("Invalid user type".to_string())),
     }
 
     // 生成验证码
@@ -299,6 +301,7 @@ pub async fn verify(
     .bind(match pending_registration.user_type {
         UserType::Gen => "gen",
         UserType::Dev => "dev",
+        UserType::Admin => "admin",
     })
     .bind(&private_key)
     .bind(&wallet_address)
@@ -346,6 +349,7 @@ pub async fn verify(
     responses(
         (status = 200, description = "Login successful", body = LoginResponse),
         (status = 401, description = "Email or password incorrect", body = crate::openapi::ErrorResponse),
+        (status = 403, description = "Account suspended", body = crate::openapi::ErrorResponse),
         (status = 404, description = "User not found", body = crate::openapi::ErrorResponse)
     )
 )]
@@ -370,6 +374,11 @@ pub async fn login(
         return Err(AppError::Unauthorized("Email or password incorrect".to_string()));
     }
 
+    // 被封禁的用户不能登录
+    if user.status == UserStatus::Suspended {
+        return Err(AppError::Forbidden("Account has been suspended".to_string()));
+    }
+
     // 生成JWT token
     let claims = Claims::new(user.user_id);
     let token = encode(
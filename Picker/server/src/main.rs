@@ -1,6 +1,6 @@
 use pickers_server::{
     config::AppState,
-    database::{create_pool, init_database},
+    database::{create_pool, init_database, promote_admins},
     handlers::{create_protected_routes, create_routes},
     utils::AppError,
 };
@@ -27,6 +27,15 @@ async fn main() -> Result<(), AppError> {
     
     // 创建应用状态
     let app_state = AppState::new(pool);
+
+    // 设置配置文件中指定的管理员
+    let promoted = promote_admins(&app_state.db, &app_state.admin_emails).await.map_err(|e| {
+        error!("Failed to promote administrators: {}", e);
+        AppError::InternalServerError(format!("Failed to promote administrators: {:?}", e))
+    })?;
+    if promoted > 0 {
+        info!("Promoted {} user(s) to administrator", promoted);
+    }
     
     // 创建定时任务来清理过期的验证码和下载令牌
     let cleanup_state = app_state.clone();
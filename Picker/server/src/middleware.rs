@@ -10,6 +10,7 @@ use serde_json::json;
 use uuid::Uuid;
 
 use crate::config::{AppState, Claims};
+use crate::models::{UserStatus, UserType};
 
 pub async fn auth_middleware(
     State(state): State<AppState>,
@@ -75,7 +76,7 @@ pub async fn auth_middleware(
     })?;
 
     // 验证用户是否存在
-    let user_exists = sqlx::query("SELECT 1 FROM users WHERE user_id = ? LIMIT 1")
+    let user_status: Option<(UserStatus,)> = sqlx::query_as("SELECT status FROM users WHERE user_id = ? LIMIT 1")
         .bind(user_id)
         .fetch_optional(&state.db)
         .await
@@ -89,19 +90,75 @@ pub async fn auth_middleware(
             )
         })?;
 
-    if user_exists.is_none() {
-        return Err((
+    match user_status {
+        None => {
+            return Err((
+                StatusCode::UNAUTHORIZED,
+                Json(json!({
+                    "error": "Unauthorized",
+                    "message": "User not found"
+                }))
+            ));
+        }
+        // 被封禁的用户已签发的token也立即失效
+        Some((UserStatus::Suspended,)) => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "error": "Forbidden",
+                    "message": "Account has been suspended"
+                }))
+            ));
+        }
+        Some((UserStatus::Active,)) => {}
+    }
+
+    // 将用户ID添加到请求扩展中
+    request.extensions_mut().insert(user_id);
+
+    Ok(next.run(request).await)
+}
+
+// 管理员权限校验，需在 auth_middleware 之后执行
+pub async fn admin_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let user_id = request.extensions().get::<Uuid>().copied().ok_or_else(|| {
+        (
             StatusCode::UNAUTHORIZED,
             Json(json!({
                 "error": "Unauthorized",
-                "message": "User not found"
+                "message": "Missing authenticated user"
+            }))
+        )
+    })?;
+
+    let user_type: Option<(UserType,)> = sqlx::query_as("SELECT user_type FROM users WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(|_| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "error": "Internal Server Error",
+                    "message": "Database error during authorization"
+                }))
+            )
+        })?;
+
+    if !matches!(user_type, Some((UserType::Admin,))) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Administrator privileges required"
             }))
         ));
     }
 
-    // 将用户ID添加到请求扩展中
-    request.extensions_mut().insert(user_id);
-
     Ok(next.run(request).await)
 }
 
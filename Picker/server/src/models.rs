@@ -11,6 +11,43 @@ use utoipa::ToSchema;
 pub enum UserType {
     Gen,
     Dev,
+    Admin,
+}
+
+// 用户状态枚举，被管理员封禁的用户无法登录和访问受保护接口
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    Active,
+    Suspended,
+}
+
+// Picker状态枚举
+// 上传后进入审核，审核通过才会上架；被拒绝的可以重新提交，上架后可被管理员暂停
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PickerStatus {
+    Draft,
+    PendingReview,
+    Active,
+    Rejected,
+    Suspended,
+    Inactive,
+}
+
+impl PickerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PickerStatus::Draft => "draft",
+            PickerStatus::PendingReview => "pending_review",
+            PickerStatus::Active => "active",
+            PickerStatus::Rejected => "rejected",
+            PickerStatus::Suspended => "suspended",
+            PickerStatus::Inactive => "inactive",
+        }
+    }
 }
 
 // 支付类型枚举
@@ -44,6 +81,7 @@ pub struct User {
     pub private_key: String,
     pub premium_balance: i64,
     pub created_at: DateTime<Utc>,
+    pub status: UserStatus,
 }
 
 // Picker模型
@@ -60,7 +98,62 @@ pub struct Picker {
     pub updated_at: DateTime<Utc>,
     pub image_path: String,
     pub version: String,
-    pub status: String,
+    pub status: PickerStatus,
+    pub review_notes: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    // 评价数量和评分总和，随评价的新增和修改在同一事务中更新
+    pub rating_count: i64,
+    pub rating_total: i64,
+}
+
+impl Picker {
+    // 平均评分，保留一位小数，没有评价时为 0
+    pub fn rating_average(&self) -> f64 {
+        if self.rating_count == 0 {
+            return 0.0;
+        }
+        (self.rating_total as f64 / self.rating_count as f64 * 10.0).round() / 10.0
+    }
+}
+
+// Picker版本状态枚举
+// 新版本发布后等待管理员审核，审核通过后才能下载
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PickerVersionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+// Picker版本模型，Picker的 version 和 file_path 始终指向最新的已审核版本
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PickerVersion {
+    pub version_id: Uuid,
+    pub picker_id: Uuid,
+    pub version: String,
+    pub changelog: String,
+    pub file_path: String,
+    pub checksum: String,
+    pub created_at: DateTime<Utc>,
+    pub status: PickerVersionStatus,
+    pub review_notes: Option<String>,
+}
+
+// Picker评价模型，只有购买成功的用户可以评价，开发者可以回复
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PickerReview {
+    pub review_id: Uuid,
+    pub picker_id: Uuid,
+    pub user_id: Uuid,
+    pub rating: i64,
+    pub content: String,
+    pub dev_reply: Option<String>,
+    pub replied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 // 订单模型
@@ -77,6 +170,31 @@ pub struct Order {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
+// 管理员操作审计日志
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AdminAuditLog {
+    pub log_id: Uuid,
+    pub admin_user_id: Uuid,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Uuid,
+    pub reason: String,
+    pub details: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// 站内通知
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub notification_id: Uuid,
+    pub user_id: Uuid,
+    pub picker_id: Option<Uuid>,
+    pub kind: String,
+    pub message: String,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
 // JWT Claims
 #[derive(Debug, Clone)]
 pub struct Claims {
@@ -107,6 +225,8 @@ pub struct VerificationCode {
 pub struct DownloadToken {
     pub token: String,
     pub order_id: Uuid,
+    // 指定下载的版本，为空时下载最新版本
+    pub version_id: Option<Uuid>,
     pub expires_at: DateTime<Utc>,
 }
 
@@ -117,9 +237,15 @@ impl DownloadToken {
         Self {
             token,
             order_id,
+            version_id: None,
             expires_at,
         }
     }
+
+    pub fn with_version(mut self, version_id: Uuid) -> Self {
+        self.version_id = Some(version_id);
+        self
+    }
     
     pub fn is_expired(&self) -> bool {
         chrono::Utc::now() > self.expires_at
@@ -10,7 +10,7 @@ mod tests {
     #[test]
     fn test_user_type_enum() {
         // 测试UserType枚举的序列化和反序列化
-        let user_types = vec![UserType::Gen, UserType::Dev];
+        let user_types = vec![UserType::Gen, UserType::Dev, UserType::Admin];
         
         for user_type in user_types {
             let serialized = serde_json::to_string(&user_type).unwrap();
@@ -82,6 +82,7 @@ mod tests {
             private_key: "private_key".to_string(),
             premium_balance: 1000,
             created_at,
+            status: UserStatus::Active,
         };
         
         // 测试序列化和反序列化
@@ -116,8 +117,14 @@ mod tests {
             updated_at,
             image_path: "/path/to/image".to_string(),
             version: "1.0.0".to_string(),
-            status: "active".to_string(),
+            status: PickerStatus::Active,
+            review_notes: None,
+            submitted_at: None,
+            reviewed_at: None,
+            rating_count: 3,
+            rating_total: 13,
         };
+        assert_eq!(picker.rating_average(), 4.3);
         
         // 测试序列化和反序列化
         let serialized = serde_json::to_string(&picker).unwrap();
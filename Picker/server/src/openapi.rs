@@ -19,16 +19,43 @@ use crate::download::DownloadQuery;
         // 受保护路由
         crate::handlers::users::get_profile,
         crate::handlers::pickers::upload_picker,
+        crate::handlers::pickers::submit_picker,
+        crate::handlers::pickers::get_my_pickers,
+        crate::handlers::pickers::publish_picker_version,
+        crate::handlers::reviews::get_picker_reviews,
+        crate::handlers::reviews::submit_picker_review,
+        crate::handlers::reviews::reply_picker_review,
         crate::handlers::orders::create_order,
         crate::handlers::orders::get_user_orders,
         crate::handlers::orders::get_order_detail,
+        crate::handlers::orders::create_download_token,
+        crate::handlers::notifications::get_notifications,
+        crate::handlers::notifications::mark_notification_read,
+        // 管理员路由
+        crate::handlers::admin::admin_list_users,
+        crate::handlers::admin::admin_list_pickers,
+        crate::handlers::admin::admin_list_orders,
+        crate::handlers::admin::admin_list_audit_logs,
+        crate::handlers::admin::admin_suspend_user,
+        crate::handlers::admin::admin_reinstate_user,
+        crate::handlers::admin::admin_adjust_premium,
+        crate::handlers::admin::admin_review_picker,
+        crate::handlers::admin::admin_deactivate_picker,
+        crate::handlers::admin::admin_list_pending_versions,
+        crate::handlers::admin::admin_review_version,
     ),
     components(
         schemas(
             // 枚举类型
             UserType,
+            UserStatus,
+            PickerStatus,
+            ReviewDecision,
+            VersionReviewDecision,
+            PickerVersionStatus,
             PayType,
             OrderStatus,
+            MarketSort,
             // 请求结构体
             RegisterRequest,
             VerifyRequest,
@@ -37,6 +64,22 @@ use crate::download::DownloadQuery;
             CreateOrderRequest,
             OrderQuery,
             DownloadQuery,
+            AdminUserQuery,
+            AdminPickerQuery,
+            AdminOrderQuery,
+            AuditLogQuery,
+            AdminActionRequest,
+            AdjustPremiumRequest,
+            ReviewPickerRequest,
+            ReviewVersionRequest,
+            AdminVersionQuery,
+            MyPickerQuery,
+            NotificationQuery,
+            PublishVersionRequest,
+            DownloadTokenRequest,
+            ReviewQuery,
+            SubmitReviewRequest,
+            ReplyReviewRequest,
             // 响应结构体
             RegisterResponse,
             VerifyResponse,
@@ -48,6 +91,24 @@ use crate::download::DownloadQuery;
             CreateOrderResponse,
             OrderInfo,
             OrderListResponse,
+            AdminUserListResponse,
+            AdminPickerListResponse,
+            AdminVersionInfo,
+            AdminVersionListResponse,
+            AuditLogInfo,
+            AuditLogListResponse,
+            AdminActionResponse,
+            AdjustPremiumResponse,
+            DeveloperPickerInfo,
+            DeveloperPickerListResponse,
+            NotificationInfo,
+            NotificationListResponse,
+            PickerVersionInfo,
+            PickerDetailResponse,
+            PublishVersionResponse,
+            DownloadTokenResponse,
+            ReviewInfo,
+            ReviewListResponse,
             // 错误响应
             ErrorResponse,
         )
@@ -58,6 +119,9 @@ use crate::download::DownloadQuery;
         (name = "pickers", description = "Picker management endpoints"),
         (name = "orders", description = "Order management endpoints"),
         (name = "download", description = "File download endpoints"),
+        (name = "reviews", description = "Picker rating and review endpoints"),
+        (name = "notifications", description = "User notification endpoints"),
+        (name = "admin", description = "Administrator moderation endpoints"),
     ),
     info(
         title = "Picker Server API",
@@ -17,7 +17,10 @@ use aes_gcm::{Aes256Gcm, KeyInit, aead::{Aead, Nonce}};
 pub enum AppError {
     BadRequest(String),
     Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
+    // 并发修改导致状态已变化
+    Conflict(String),
     UnprocessableEntity(String),
     InternalServerError(String),
     DatabaseError(String),
@@ -28,7 +31,9 @@ impl IntoResponse for AppError {
         let (status, error_message) = match self {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
             AppError::UnprocessableEntity(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
             AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             AppError::DatabaseError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
@@ -39,6 +39,7 @@ async fn create_test_app() -> Router {
         premium_free: 30,
         premium_period: 30,
         premium_start: true,
+        admin_emails: Vec::new(),
     };
 
     create_routes().with_state(state)